@@ -1,5 +1,12 @@
 extern crate varlink_generator;
 
 fn main() {
-    varlink_generator::cargo_build_tosource("src/org.example.more.varlink", true);
+    varlink_generator::cargo_build_tosource_options(
+        "src/org.example.more.varlink",
+        true,
+        &varlink_generator::GeneratorOptions {
+            allow_more: Some(vec!["TestMore".into()]),
+            ..Default::default()
+        },
+    );
 }
@@ -110,30 +110,14 @@ fn run_client(connection: Arc<RwLock<varlink::Connection>>) -> Result<()> {
     for reply in iface.test_more(10).more()? {
         let reply = reply?;
         //assert!(reply.state.is_some());
-        let state = reply.state;
-        match state {
-            State {
-                start: Some(true),
-                end: None,
-                progress: None,
-                ..
-            } => {
+        match reply.state {
+            State::start(_) => {
                 eprintln!("--- Start ---");
             }
-            State {
-                start: None,
-                end: Some(true),
-                progress: None,
-                ..
-            } => {
+            State::end(_) => {
                 eprintln!("--- End ---");
             }
-            State {
-                start: None,
-                end: None,
-                progress: Some(progress),
-                ..
-            } => {
+            State::progress(progress) => {
                 eprintln!("Progress: {}", progress);
                 /*
                 if progress > 50 {
@@ -142,7 +126,6 @@ fn run_client(connection: Arc<RwLock<varlink::Connection>>) -> Result<()> {
                 }
                 */
             }
-            _ => eprintln!("Got unknown state: {:?}", state),
         }
     }
 
@@ -175,34 +158,18 @@ impl VarlinkInterface for MyOrgExampleMore {
 
         call.set_continues(true);
 
-        call.reply(State {
-            start: Some(true),
-            end: None,
-            progress: None,
-        })?;
+        call.reply(State::start(true))?;
 
         for i in 0..n {
             thread::sleep(time::Duration::from_millis(self.sleep_duration));
-            call.reply(State {
-                progress: Some(i * 100 / n),
-                start: None,
-                end: None,
-            })?;
+            call.reply(State::progress(i * 100 / n))?;
         }
 
-        call.reply(State {
-            progress: Some(100),
-            start: None,
-            end: None,
-        })?;
+        call.reply(State::progress(100))?;
 
         call.set_continues(false);
 
-        call.reply(State {
-            end: Some(true),
-            progress: None,
-            start: None,
-        })
+        call.reply(State::end(true))
     }
 }
 
@@ -22,6 +22,17 @@ impl ::std::fmt::Display for ErrorKind {
         }
     }
 }
+impl ErrorKind {
+    #[doc = r" The parameters of an error of the interface as a `std::error::Error`,"]
+    #[doc = r" whose `Display` includes their values."]
+    #[allow(dead_code)]
+    pub fn into_args_error(self) -> Option<Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            ErrorKind::TestMoreError(Some(v)) => Some(Box::new(v)),
+            _ => None,
+        }
+    }
+}
 pub struct Error(
     pub ErrorKind,
     pub Option<Box<dyn std::error::Error + 'static + Send + Sync>>,
@@ -120,6 +131,35 @@ impl From<&varlink::Reply> for ErrorKind {
         }
     }
 }
+impl ::std::convert::TryFrom<ErrorKind> for varlink::Reply {
+    type Error = Error;
+    fn try_from(e: ErrorKind) -> std::result::Result<Self, Error> {
+        match e {
+            ErrorKind::TestMoreError(v) => Ok(varlink::Reply::error(
+                "org.example.more.TestMoreError",
+                match v {
+                    Some(v) => Some(
+                        serde_json::to_value(v)
+                            .map_err(varlink::map_context!())
+                            .map_err(Error::from)?,
+                    ),
+                    None => None,
+                },
+            )),
+            ErrorKind::Varlink_Error | ErrorKind::VarlinkReply_Error => Err(Error::from(e)),
+        }
+    }
+}
+impl std::fmt::Display for TestMoreError_Args {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "org.example.more.TestMoreError (reason: {:?})",
+            self.r#reason
+        )
+    }
+}
+impl std::error::Error for TestMoreError_Args {}
 #[allow(dead_code)]
 pub trait VarlinkCallError: varlink::CallTrait {
     fn reply_test_more_error(&mut self, r#reason: String) -> varlink::Result<()> {
@@ -133,22 +173,104 @@ pub trait VarlinkCallError: varlink::CallTrait {
     }
 }
 impl VarlinkCallError for varlink::Call<'_> {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-pub struct r#State {
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct State_Fields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub r#start: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub r#progress: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub r#end: Option<bool>,
 }
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(try_from = "State_Fields", into = "State_Fields")]
+pub enum r#State {
+    r#start(bool),
+    r#progress(i64),
+    r#end(bool),
+}
+impl Default for r#State {
+    fn default() -> Self {
+        r#State::r#start(Default::default())
+    }
+}
+impl ::std::convert::TryFrom<State_Fields> for r#State {
+    type Error = &'static str;
+    fn try_from(fields: State_Fields) -> ::std::result::Result<Self, Self::Error> {
+        match fields {
+            State_Fields {
+                r#start: Some(v),
+                r#progress: None,
+                r#end: None,
+            } => Ok(r#State::r#start(v)),
+            State_Fields {
+                r#start: None,
+                r#progress: Some(v),
+                r#end: None,
+            } => Ok(r#State::r#progress(v)),
+            State_Fields {
+                r#start: None,
+                r#progress: None,
+                r#end: Some(v),
+            } => Ok(r#State::r#end(v)),
+            _ => Err("exactly one field of `State` has to be set"),
+        }
+    }
+}
+impl From<r#State> for State_Fields {
+    fn from(v: r#State) -> Self {
+        match v {
+            r#State::r#start(v) => State_Fields {
+                r#start: Some(v),
+                r#progress: None,
+                r#end: None,
+            },
+            r#State::r#progress(v) => State_Fields {
+                r#start: None,
+                r#progress: Some(v),
+                r#end: None,
+            },
+            r#State::r#end(v) => State_Fields {
+                r#start: None,
+                r#progress: None,
+                r#end: Some(v),
+            },
+        }
+    }
+}
+impl r#State {
+    #[allow(dead_code, unreachable_patterns)]
+    pub fn as_start(&self) -> Option<&bool> {
+        match self {
+            r#State::r#start(v) => Some(v),
+            _ => None,
+        }
+    }
+    #[allow(dead_code, unreachable_patterns)]
+    pub fn as_progress(&self) -> Option<&i64> {
+        match self {
+            r#State::r#progress(v) => Some(v),
+            _ => None,
+        }
+    }
+    #[allow(dead_code, unreachable_patterns)]
+    pub fn as_end(&self) -> Option<&bool> {
+        match self {
+            r#State::r#end(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct TestMoreError_Args {
     pub r#reason: String,
 }
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Ping_Reply {
     pub r#pong: String,
 }
 impl varlink::VarlinkReply for Ping_Reply {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Ping_Args {
     pub r#ping: String,
 }
@@ -159,24 +281,12 @@ pub trait Call_Ping: VarlinkCallError {
     }
 }
 impl Call_Ping for varlink::Call<'_> {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-pub struct StopServing_Reply {}
-impl varlink::VarlinkReply for StopServing_Reply {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-pub struct StopServing_Args {}
-#[allow(dead_code)]
-pub trait Call_StopServing: VarlinkCallError {
-    fn reply(&mut self) -> varlink::Result<()> {
-        self.reply_struct(varlink::Reply::parameters(None))
-    }
-}
-impl Call_StopServing for varlink::Call<'_> {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct TestMore_Reply {
     pub r#state: State,
 }
 impl varlink::VarlinkReply for TestMore_Reply {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct TestMore_Args {
     pub r#n: i64,
 }
@@ -187,11 +297,63 @@ pub trait Call_TestMore: VarlinkCallError {
     }
 }
 impl Call_TestMore for varlink::Call<'_> {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct StopServing_Reply {}
+impl varlink::VarlinkReply for StopServing_Reply {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct StopServing_Args {}
+#[allow(dead_code)]
+pub trait Call_StopServing: VarlinkCallError {
+    fn reply(&mut self) -> varlink::Result<()> {
+        self.reply_struct(varlink::Reply::parameters(None))
+    }
+}
+impl Call_StopServing for varlink::Call<'_> {}
+#[doc = "The methods of the `org.example.more` interface"]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Ping,
+    TestMore,
+    StopServing,
+}
+#[allow(dead_code)]
+impl Method {
+    #[doc = r" All methods in the order of the interface definition"]
+    pub const ALL: &'static [Method] = &[Method::Ping, Method::TestMore, Method::StopServing];
+    #[doc = r" The fully qualified varlink name of the method"]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Ping => "org.example.more.Ping",
+            Method::TestMore => "org.example.more.TestMore",
+            Method::StopServing => "org.example.more.StopServing",
+        }
+    }
+}
+impl ::std::str::FromStr for Method {
+    type Err = varlink::Error;
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "org.example.more.Ping" => Ok(Method::Ping),
+            "org.example.more.TestMore" => Ok(Method::TestMore),
+            "org.example.more.StopServing" => Ok(Method::StopServing),
+            _ => Err(varlink::context!(varlink::ErrorKind::MethodNotFound(
+                s.into()
+            ))),
+        }
+    }
+}
+impl ::std::fmt::Display for Method {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+#[doc = "Server side of the `org.example.more` interface.\n\nThe methods are called for these varlink methods:\n\n- `org.example.more.Ping`: `ping()`\n- `org.example.more.TestMore`: `test_more()`\n- `org.example.more.StopServing`: `stop_serving()`"]
 #[allow(dead_code)]
 pub trait VarlinkInterface {
     fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()>;
-    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()>;
     fn test_more(&self, call: &mut dyn Call_TestMore, r#n: i64) -> varlink::Result<()>;
+    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()>;
     fn call_upgraded(
         &self,
         _call: &mut varlink::Call,
@@ -200,11 +362,47 @@ pub trait VarlinkInterface {
         Ok(Vec::new())
     }
 }
+impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Box<T> {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()> {
+        (**self).ping(call, r#ping)
+    }
+    fn test_more(&self, call: &mut dyn Call_TestMore, r#n: i64) -> varlink::Result<()> {
+        (**self).test_more(call, r#n)
+    }
+    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()> {
+        (**self).stop_serving(call)
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        (**self).call_upgraded(call, bufreader)
+    }
+}
+impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Arc<T> {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()> {
+        (**self).ping(call, r#ping)
+    }
+    fn test_more(&self, call: &mut dyn Call_TestMore, r#n: i64) -> varlink::Result<()> {
+        (**self).test_more(call, r#n)
+    }
+    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()> {
+        (**self).stop_serving(call)
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        (**self).call_upgraded(call, bufreader)
+    }
+}
 #[allow(dead_code)]
 pub trait VarlinkClientInterface {
     fn ping(&mut self, r#ping: String) -> varlink::MethodCall<Ping_Args, Ping_Reply, Error>;
-    fn stop_serving(&mut self) -> varlink::MethodCall<StopServing_Args, StopServing_Reply, Error>;
     fn test_more(&mut self, r#n: i64) -> varlink::MethodCall<TestMore_Args, TestMore_Reply, Error>;
+    fn stop_serving(&mut self) -> varlink::MethodCall<StopServing_Args, StopServing_Reply, Error>;
 }
 #[allow(dead_code)]
 pub struct VarlinkClient {
@@ -215,6 +413,19 @@ impl VarlinkClient {
     pub fn new(connection: Arc<RwLock<varlink::Connection>>) -> Self {
         VarlinkClient { connection }
     }
+    #[doc = r" Call the method `method` of this interface with untyped parameters and replies."]
+    #[allow(dead_code)]
+    pub fn call_raw(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> varlink::MethodCall<serde_json::Value, serde_json::Value, Error> {
+        varlink::MethodCall::<serde_json::Value, serde_json::Value, Error>::new(
+            self.connection.clone(),
+            format!("{}.{}", "org.example.more", method),
+            params,
+        )
+    }
 }
 impl VarlinkClientInterface for VarlinkClient {
     fn ping(&mut self, r#ping: String) -> varlink::MethodCall<Ping_Args, Ping_Reply, Error> {
@@ -224,13 +435,6 @@ impl VarlinkClientInterface for VarlinkClient {
             Ping_Args { r#ping },
         )
     }
-    fn stop_serving(&mut self) -> varlink::MethodCall<StopServing_Args, StopServing_Reply, Error> {
-        varlink::MethodCall::<StopServing_Args, StopServing_Reply, Error>::new(
-            self.connection.clone(),
-            "org.example.more.StopServing",
-            StopServing_Args {},
-        )
-    }
     fn test_more(&mut self, r#n: i64) -> varlink::MethodCall<TestMore_Args, TestMore_Reply, Error> {
         varlink::MethodCall::<TestMore_Args, TestMore_Reply, Error>::new(
             self.connection.clone(),
@@ -238,6 +442,13 @@ impl VarlinkClientInterface for VarlinkClient {
             TestMore_Args { r#n },
         )
     }
+    fn stop_serving(&mut self) -> varlink::MethodCall<StopServing_Args, StopServing_Reply, Error> {
+        varlink::MethodCall::<StopServing_Args, StopServing_Reply, Error>::new(
+            self.connection.clone(),
+            "org.example.more.StopServing",
+            StopServing_Args {},
+        )
+    }
 }
 #[allow(dead_code)]
 pub struct VarlinkInterfaceProxy {
@@ -249,7 +460,7 @@ pub fn new(inner: Box<dyn VarlinkInterface + Send + Sync>) -> VarlinkInterfacePr
 }
 impl varlink::Interface for VarlinkInterfaceProxy {
     fn get_description(&self) -> &'static str {
-        "# Example Varlink service\ninterface org.example.more\n\n# Enum, returning either start, progress or end\n# progress: [0-100]\ntype State (\n  start: ?bool,\n  progress: ?int,\n  end: ?bool\n)\n\n# Returns the same string\nmethod Ping(ping: string) -> (pong: string)\n\n# Dummy progress method\n# n: number of progress steps\nmethod TestMore(n: int) -> (state: State)\n\n# Stop serving\nmethod StopServing() -> ()\n\n# Something failed in TestMore\nerror TestMoreError (reason: string)\n"
+        "# Example Varlink service\ninterface org.example.more\n\n# Enum, returning either start, progress or end\n# progress: [0-100]\n# @oneof\ntype State (\n  start: ?bool,\n  progress: ?int,\n  end: ?bool\n)\n\n# Returns the same string\nmethod Ping(ping: string) -> (pong: string)\n\n# Dummy progress method\n# n: number of progress steps\nmethod TestMore(n: int) -> (state: State)\n\n# Stop serving\nmethod StopServing() -> ()\n\n# Something failed in TestMore\nerror TestMoreError (reason: string)\n"
     }
     fn get_name(&self) -> &'static str {
         "org.example.more"
@@ -263,6 +474,14 @@ impl varlink::Interface for VarlinkInterfaceProxy {
     }
     fn call(&self, call: &mut varlink::Call) -> varlink::Result<()> {
         let req = call.request.unwrap();
+        if call.wants_more()
+            && matches!(
+                req.method.as_ref(),
+                "org.example.more.Ping" | "org.example.more.StopServing"
+            )
+        {
+            return call.reply_invalid_parameter("more".into());
+        }
         match req.method.as_ref() {
             "org.example.more.Ping" => {
                 if let Some(args) = req.parameters.clone() {
@@ -279,9 +498,6 @@ impl varlink::Interface for VarlinkInterfaceProxy {
                     call.reply_invalid_parameter("parameters".into())
                 }
             }
-            "org.example.more.StopServing" => {
-                self.inner.stop_serving(call as &mut dyn Call_StopServing)
-            }
             "org.example.more.TestMore" => {
                 if let Some(args) = req.parameters.clone() {
                     let args: TestMore_Args = match serde_json::from_value(args) {
@@ -298,6 +514,9 @@ impl varlink::Interface for VarlinkInterfaceProxy {
                     call.reply_invalid_parameter("parameters".into())
                 }
             }
+            "org.example.more.StopServing" => {
+                self.inner.stop_serving(call as &mut dyn Call_StopServing)
+            }
             m => call.reply_method_not_found(String::from(m)),
         }
     }
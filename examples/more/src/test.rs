@@ -44,3 +44,126 @@ fn error_is_sync_send() {
     use crate::org_example_more::Error;
     assert_impl_all!(Error: Send, Sync);
 }
+
+#[test]
+fn test_arc_handler() -> Result<()> {
+    use std::sync::Arc;
+    use varlink::ConnectionHandler;
+
+    let handler = Arc::new(crate::MyOrgExampleMore { sleep_duration: 0 });
+    let service = varlink::VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(crate::org_example_more::new(Box::new(
+            handler.clone(),
+        )))],
+    );
+
+    let mut request = concat!(
+        r#"{"method":"org.example.more.Ping","parameters":{"ping":"pong"}}"#,
+        "\0"
+    )
+    .as_bytes();
+    let mut w = vec![];
+    let (unhandled, upgraded) = service.handle(&mut request, &mut w, None)?;
+    assert!(unhandled.is_empty());
+    assert!(upgraded.is_none());
+    assert_eq!(w, b"{\"parameters\":{\"pong\":\"pong\"}}\0");
+    assert_eq!(Arc::strong_count(&handler), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_more_rejected() -> Result<()> {
+    use varlink::ConnectionHandler;
+
+    let service = varlink::VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(crate::org_example_more::new(Box::new(
+            crate::MyOrgExampleMore { sleep_duration: 0 },
+        )))],
+    );
+
+    let mut request = concat!(
+        r#"{"method":"org.example.more.Ping","parameters":{"ping":"pong"},"more":true}"#,
+        "\0"
+    )
+    .as_bytes();
+    let mut w = vec![];
+    service.handle(&mut request, &mut w, None)?;
+    assert_eq!(
+        w,
+        concat!(
+            r#"{"error":"org.varlink.service.InvalidParameter","parameters":{"parameter":"more"}}"#,
+            "\0"
+        )
+        .as_bytes()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_method_enum() {
+    use crate::org_example_more::Method;
+    use std::str::FromStr;
+
+    assert_eq!(
+        Method::ALL,
+        &[Method::Ping, Method::TestMore, Method::StopServing]
+    );
+    for m in Method::ALL {
+        assert_eq!(Method::from_str(m.as_str()).unwrap(), *m);
+    }
+    assert_eq!(Method::TestMore.as_str(), "org.example.more.TestMore");
+    assert_eq!(Method::TestMore.to_string(), "org.example.more.TestMore");
+    assert!(Method::from_str("org.example.more.Foo").is_err());
+}
+
+#[test]
+fn test_oneof_state() -> Result<()> {
+    use crate::org_example_more::State;
+
+    let state = State::progress(42);
+    let json = serde_json::to_value(&state)?;
+    assert_eq!(json, serde_json::json!({"progress": 42}));
+    assert_eq!(serde_json::from_value::<State>(json)?, state);
+    assert_eq!(state.as_progress(), Some(&42));
+    assert_eq!(state.as_start(), None);
+
+    assert!(
+        serde_json::from_value::<State>(serde_json::json!({"start": true, "end": true})).is_err()
+    );
+    assert!(serde_json::from_value::<State>(serde_json::json!({})).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_error_kind_to_reply() {
+    use crate::org_example_more::{ErrorKind, TestMoreError_Args};
+    use std::convert::TryFrom;
+
+    let kind = ErrorKind::TestMoreError(Some(TestMoreError_Args {
+        reason: "failed".into(),
+    }));
+    let reply = varlink::Reply::try_from(kind.clone()).unwrap();
+    assert_eq!(
+        reply.error.as_deref(),
+        Some("org.example.more.TestMoreError")
+    );
+    assert_eq!(
+        reply.parameters,
+        Some(serde_json::json!({"reason": "failed"}))
+    );
+    assert_eq!(ErrorKind::from(&reply), kind);
+
+    // not an error of the interface
+    let e = varlink::Reply::try_from(ErrorKind::Varlink_Error).unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::Varlink_Error);
+}
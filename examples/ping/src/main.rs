@@ -112,6 +112,12 @@ fn run_client(connection: &Arc<RwLock<varlink::Connection>>) -> Result<()> {
         assert_eq!(ping, reply.pong);
         eprintln!("Pong: '{}'", reply.pong);
 
+        let reply = iface
+            .call_raw("Ping", serde_json::json!({ "ping": ping }))
+            .call()?;
+        assert_eq!(reply["pong"], ping);
+        eprintln!("Pong: {}", reply["pong"]);
+
         let _reply = iface.upgrade().upgrade()?;
         eprintln!("Client: upgrade()");
     }
@@ -22,6 +22,17 @@ impl ::std::fmt::Display for ErrorKind {
         }
     }
 }
+impl ErrorKind {
+    #[doc = r" The parameters of an error of the interface as a `std::error::Error`,"]
+    #[doc = r" whose `Display` includes their values."]
+    #[allow(dead_code)]
+    pub fn into_args_error(self) -> Option<Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            ErrorKind::PingError(Some(v)) => Some(Box::new(v)),
+            _ => None,
+        }
+    }
+}
 pub struct Error(
     pub ErrorKind,
     pub Option<Box<dyn std::error::Error + 'static + Send + Sync>>,
@@ -120,6 +131,35 @@ impl From<&varlink::Reply> for ErrorKind {
         }
     }
 }
+impl ::std::convert::TryFrom<ErrorKind> for varlink::Reply {
+    type Error = Error;
+    fn try_from(e: ErrorKind) -> std::result::Result<Self, Error> {
+        match e {
+            ErrorKind::PingError(v) => Ok(varlink::Reply::error(
+                "org.example.ping.PingError",
+                match v {
+                    Some(v) => Some(
+                        serde_json::to_value(v)
+                            .map_err(varlink::map_context!())
+                            .map_err(Error::from)?,
+                    ),
+                    None => None,
+                },
+            )),
+            ErrorKind::Varlink_Error | ErrorKind::VarlinkReply_Error => Err(Error::from(e)),
+        }
+    }
+}
+impl std::fmt::Display for PingError_Args {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "org.example.ping.PingError (parameter: {:?})",
+            self.r#parameter
+        )
+    }
+}
+impl std::error::Error for PingError_Args {}
 #[allow(dead_code)]
 pub trait VarlinkCallError: varlink::CallTrait {
     fn reply_ping_error(&mut self, r#parameter: i64) -> varlink::Result<()> {
@@ -133,16 +173,16 @@ pub trait VarlinkCallError: varlink::CallTrait {
     }
 }
 impl VarlinkCallError for varlink::Call<'_> {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct PingError_Args {
     pub r#parameter: i64,
 }
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Ping_Reply {
     pub r#pong: String,
 }
 impl varlink::VarlinkReply for Ping_Reply {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Ping_Args {
     pub r#ping: String,
 }
@@ -153,10 +193,10 @@ pub trait Call_Ping: VarlinkCallError {
     }
 }
 impl Call_Ping for varlink::Call<'_> {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Upgrade_Reply {}
 impl varlink::VarlinkReply for Upgrade_Reply {}
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct Upgrade_Args {}
 #[allow(dead_code)]
 pub trait Call_Upgrade: VarlinkCallError {
@@ -165,6 +205,43 @@ pub trait Call_Upgrade: VarlinkCallError {
     }
 }
 impl Call_Upgrade for varlink::Call<'_> {}
+#[doc = "The methods of the `org.example.ping` interface"]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Ping,
+    Upgrade,
+}
+#[allow(dead_code)]
+impl Method {
+    #[doc = r" All methods in the order of the interface definition"]
+    pub const ALL: &'static [Method] = &[Method::Ping, Method::Upgrade];
+    #[doc = r" The fully qualified varlink name of the method"]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Ping => "org.example.ping.Ping",
+            Method::Upgrade => "org.example.ping.Upgrade",
+        }
+    }
+}
+impl ::std::str::FromStr for Method {
+    type Err = varlink::Error;
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "org.example.ping.Ping" => Ok(Method::Ping),
+            "org.example.ping.Upgrade" => Ok(Method::Upgrade),
+            _ => Err(varlink::context!(varlink::ErrorKind::MethodNotFound(
+                s.into()
+            ))),
+        }
+    }
+}
+impl ::std::fmt::Display for Method {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+#[doc = "Server side of the `org.example.ping` interface.\n\nThe methods are called for these varlink methods:\n\n- `org.example.ping.Ping`: `ping()`\n- `org.example.ping.Upgrade`: `upgrade()`"]
 #[allow(dead_code)]
 pub trait VarlinkInterface {
     fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()>;
@@ -177,6 +254,36 @@ pub trait VarlinkInterface {
         Ok(Vec::new())
     }
 }
+impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Box<T> {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()> {
+        (**self).ping(call, r#ping)
+    }
+    fn upgrade(&self, call: &mut dyn Call_Upgrade) -> varlink::Result<()> {
+        (**self).upgrade(call)
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        (**self).call_upgraded(call, bufreader)
+    }
+}
+impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Arc<T> {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()> {
+        (**self).ping(call, r#ping)
+    }
+    fn upgrade(&self, call: &mut dyn Call_Upgrade) -> varlink::Result<()> {
+        (**self).upgrade(call)
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        (**self).call_upgraded(call, bufreader)
+    }
+}
 #[allow(dead_code)]
 pub trait VarlinkClientInterface {
     fn ping(&mut self, r#ping: String) -> varlink::MethodCall<Ping_Args, Ping_Reply, Error>;
@@ -191,6 +298,19 @@ impl VarlinkClient {
     pub fn new(connection: Arc<RwLock<varlink::Connection>>) -> Self {
         VarlinkClient { connection }
     }
+    #[doc = r" Call the method `method` of this interface with untyped parameters and replies."]
+    #[allow(dead_code)]
+    pub fn call_raw(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> varlink::MethodCall<serde_json::Value, serde_json::Value, Error> {
+        varlink::MethodCall::<serde_json::Value, serde_json::Value, Error>::new(
+            self.connection.clone(),
+            format!("{}.{}", "org.example.ping", method),
+            params,
+        )
+    }
 }
 impl VarlinkClientInterface for VarlinkClient {
     fn ping(&mut self, r#ping: String) -> varlink::MethodCall<Ping_Args, Ping_Reply, Error> {
@@ -1,5 +1,12 @@
 extern crate varlink_generator;
 
 fn main() {
-    varlink_generator::cargo_build("src/org.varlink.certification.varlink");
+    varlink_generator::cargo_build_options(
+        "src/org.varlink.certification.varlink",
+        &varlink_generator::GeneratorOptions {
+            generate_builders: true,
+            emit_doc_comments: true,
+            ..Default::default()
+        },
+    );
 }
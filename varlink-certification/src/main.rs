@@ -119,7 +119,13 @@ fn run_client(connection: Arc<RwLock<varlink::Connection>>) -> Result<()> {
     eprintln!("{:#?}", ret);
 
     let ret = iface
-        .test06(client_id.clone(), ret.bool, ret.int, ret.float, ret.string)
+        .test06_builder()
+        .client_id(client_id.clone())
+        .r#bool(ret.bool)
+        .int(ret.int)
+        .float(ret.float)
+        .string(ret.string)
+        .call()?
         .call()?;
     eprintln!("{:#?}", ret);
 
@@ -251,14 +257,8 @@ macro_rules! check_call_normal {
                 serde_json::to_value($c.get_request().unwrap()).map_err(varlink::map_context!())?;
             let wants = serde_json::to_value(wants).map_err(varlink::map_context!())?;
             return $c.reply_certification_error(
-                serde_json::to_value(varlink::Request {
-                    more: None,
-                    oneway: None,
-                    upgrade: None,
-                    method: $test.into(),
-                    parameters: Some(wants),
-                })
-                .map_err(varlink::map_context!())?,
+                serde_json::to_value(varlink::Request::create($test, Some(wants)))
+                    .map_err(varlink::map_context!())?,
                 got,
             );
         }
@@ -297,14 +297,8 @@ macro_rules! check_call_more {
                 serde_json::to_value($c.get_request().unwrap()).map_err(varlink::map_context!())?;
             let wants = serde_json::to_value(wants).map_err(varlink::map_context!())?;
             return $c.reply_certification_error(
-                serde_json::to_value(varlink::Request {
-                    more: None,
-                    oneway: None,
-                    upgrade: None,
-                    method: $test.into(),
-                    parameters: Some(wants),
-                })
-                .map_err(varlink::map_context!())?,
+                serde_json::to_value(varlink::Request::create($test, Some(wants)))
+                    .map_err(varlink::map_context!())?,
                 got,
             );
         }
@@ -343,14 +337,8 @@ macro_rules! check_call_oneway {
                 serde_json::to_value($c.get_request().unwrap()).map_err(varlink::map_context!())?;
             let wants = serde_json::to_value(wants).map_err(varlink::map_context!())?;
             return $c.reply_certification_error(
-                serde_json::to_value(varlink::Request {
-                    more: None,
-                    oneway: None,
-                    upgrade: None,
-                    method: $test.into(),
-                    parameters: Some(wants),
-                })
-                .map_err(varlink::map_context!())?,
+                serde_json::to_value(varlink::Request::create($test, Some(wants)))
+                    .map_err(varlink::map_context!())?,
                 got,
             );
         }
@@ -385,12 +373,10 @@ impl org_varlink_certification::VarlinkInterface for CertInterface {
 
                 _ => false,
             },
-            varlink::Request {
-                more: None,
-                oneway: Some(false),
-                upgrade: None,
-                method: "org.varlink.certification.Start".into(),
-                parameters: None,
+            {
+                let mut request = varlink::Request::create("org.varlink.certification.Start", None);
+                request.oneway = Some(false);
+                request
             }
         );
 
@@ -44,6 +44,79 @@ fn test_tcp() -> Result<()> {
     run_self_test("tcp:127.0.0.1:23456")
 }
 
+#[test]
+fn test_args_builder() {
+    use crate::org_varlink_certification::{Test06_Args, VarlinkClient};
+    use std::sync::{Arc, RwLock};
+
+    let client = VarlinkClient::new(Arc::new(RwLock::new(Connection::default())));
+
+    let args = client
+        .test06_builder()
+        .client_id("id".into())
+        .r#bool(true)
+        .int(1)
+        .float(1.5)
+        .string("s".into())
+        .build()
+        .unwrap();
+    assert_eq!(
+        args,
+        Test06_Args {
+            client_id: "id".into(),
+            r#bool: true,
+            int: 1,
+            float: 1.5,
+            string: "s".into(),
+        }
+    );
+
+    let err = client
+        .test06_builder()
+        .client_id("id".into())
+        .build()
+        .unwrap_err();
+    match err.kind() {
+        varlink::ErrorKind::InvalidParameter(p) => assert_eq!(p, "bool"),
+        kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn test_default() {
+    use crate::org_varlink_certification::{MyType, MyType_enum, Test06_Args};
+
+    let args = Test06_Args::default();
+    assert_eq!(args.client_id, "");
+    assert!(!args.r#bool);
+
+    let t = MyType::default();
+    assert_eq!(t.r#enum, MyType_enum::one);
+    assert_eq!(t.nullable, None);
+    assert!(t.stringset.is_empty());
+}
+
+#[test]
+fn test_try_from_value() {
+    use crate::org_varlink_certification::{ErrorKind, Interface};
+    use std::convert::TryFrom;
+
+    let interface = Interface::try_from(serde_json::json!({
+        "foo": null,
+        "anon": { "foo": true, "bar": false }
+    }))
+    .unwrap();
+    assert!(interface.anon.foo);
+    assert!(!interface.anon.bar);
+
+    let err = Interface::try_from(serde_json::json!({ "anon": 1 })).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Varlink_Error);
+    match err.source_varlink_kind() {
+        Some(varlink::ErrorKind::SerdeJsonDe(_)) => {}
+        kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
+
 #[test]
 fn test_wrong_address_1() {
     crate::run_server("tcpd:0.0.0.0:12345", 1).unwrap_err();
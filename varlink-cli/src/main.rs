@@ -1,34 +1,47 @@
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
+use std::error::Error as _;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
 use std::path::Path;
 use std::str;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use clap::{App, Arg, SubCommand};
 use colored_json::{ColorMode, ColoredFormatter, Colour, Output, PrettyFormatter, Style, Styler};
 
 use varlink::{
     Connection, GetInterfaceDescriptionReply, MethodCall, OrgVarlinkServiceClient,
-    OrgVarlinkServiceInterface,
+    OrgVarlinkServiceInterface, ServiceInfo,
 };
 use varlink_parser::{Format, FormatColored, IDL};
-use varlink_stdinterfaces::org_varlink_resolver::{VarlinkClient, VarlinkClientInterface};
+use varlink_stdinterfaces::org_varlink_resolver::{
+    ErrorKind as ResolverErrorKind, VarlinkClient, VarlinkClientInterface,
+};
 
 #[cfg(target_os = "linux")]
 mod proxy;
 #[cfg(target_os = "linux")]
 mod watchclose_epoll;
 
+#[cfg(test)]
+mod test;
+
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + 'static + Send + Sync>>;
 
-fn varlink_format(filename: &str, line_len: Option<&str>, should_colorize: bool) -> Result<()> {
+fn read_file(filename: &str) -> Result<String> {
     let mut buffer = String::new();
     File::open(Path::new(filename))
         .map_err(|e| format!("Failed to open '{filename}': {e}"))?
         .read_to_string(&mut buffer)
         .map_err(|e| format!("Failed to read '{filename}': {e}"))?;
+    Ok(buffer)
+}
 
+fn varlink_format(filename: &str, line_len: Option<&str>, should_colorize: bool) -> Result<()> {
+    let buffer = read_file(filename)?;
     let idl =
         IDL::try_from(buffer.as_str()).map_err(|e| format!("Failed to parse '{filename}': {e}"))?;
 
@@ -46,19 +59,51 @@ fn varlink_format(filename: &str, line_len: Option<&str>, should_colorize: bool)
     Ok(())
 }
 
-fn varlink_info(
+/// Write the interface definition in `filename` as JSON, see `varlink_parser::IDL::to_json()`.
+fn varlink_describe(filename: &str, out: &mut dyn Write) -> Result<()> {
+    let buffer = read_file(filename)?;
+    let idl =
+        IDL::try_from(buffer.as_str()).map_err(|e| format!("Failed to parse '{filename}': {e}"))?;
+    serde_json::to_writer_pretty(&mut *out, &idl.to_json())?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Let calls on `connection` fail, if the service does not reply within `timeout` seconds.
+fn set_timeout(connection: &Arc<RwLock<Connection>>, timeout: Option<u64>) -> Result<()> {
+    if let Some(timeout) = timeout {
+        connection
+            .write()
+            .unwrap()
+            .set_timeout(Some(Duration::from_secs(timeout)))
+            .map_err(|e| format!("Failed to set timeout: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Describe `e`, naming the timeout, if the service did not reply in time, or the likely
+/// reason, why the service can't be reached.
+fn call_error(e: &varlink::Error, timeout: Option<u64>) -> String {
+    match (e.kind(), timeout) {
+        (varlink::ErrorKind::Timeout, Some(timeout)) => format!("timed out after {timeout} s"),
+        (varlink::ErrorKind::ConnectionRefused, _) => {
+            "connection refused, is the service running?".into()
+        }
+        (varlink::ErrorKind::AddressNotFound, _) => {
+            "no such socket, is the service running?".into()
+        }
+        (varlink::ErrorKind::BrokenPipe, _) => "the service closed the connection".into(),
+        _ => e.to_string(),
+    }
+}
+
+fn varlink_service_info(
     address: Option<&str>,
     resolver: &str,
     activate: Option<&str>,
     bridge: Option<&str>,
-    should_colorize: bool,
-) -> Result<()> {
-    let bold: fn(w: &str) -> String = if should_colorize {
-        |w| Style::new().bold().paint(w).to_string()
-    } else {
-        |w| w.to_string()
-    };
-
+    timeout: Option<u64>,
+) -> Result<ServiceInfo> {
     let connection = match activate {
         Some(activate) => Connection::with_activate(activate)
             .map_err(|e| format!("Failed to connect with activate '{activate}': {e}"))?,
@@ -69,27 +114,53 @@ fn varlink_info(
                 let address = address.unwrap();
                 if address.rfind(':').is_none() {
                     let conn = Connection::new(resolver).map_err(|e| {
-                        format!("Failed to connect with resolver '{resolver}': {e}")
+                        format!(
+                            "Failed to connect with resolver '{resolver}': {}",
+                            call_error(&e, None)
+                        )
                     })?;
                     let mut resolver = VarlinkClient::new(conn);
                     let address = match resolver.resolve(address.into()).call() {
                         Ok(r) => r.address,
                         _ => return Err(format!("Interface '{}' not found", address).into()),
                     };
-                    Connection::with_address(&address)
-                        .map_err(|e| format!("Failed to connect to '{address}': {e}"))?
+                    Connection::with_address(&address).map_err(|e| {
+                        format!("Failed to connect to '{address}': {}", call_error(&e, None))
+                    })?
                 } else {
-                    Connection::with_address(&address)
-                        .map_err(|e| format!("Failed to connect to '{address}': {e}"))?
+                    Connection::with_address(&address).map_err(|e| {
+                        format!("Failed to connect to '{address}': {}", call_error(&e, None))
+                    })?
                 }
             }
         },
     };
 
+    set_timeout(&connection, timeout)?;
+
     let mut call = OrgVarlinkServiceClient::new(connection);
     let info = call
         .get_info()
-        .map_err(|e| format!("Cannot call GetInfo(): {e}"))?;
+        .map_err(|e| format!("Cannot call GetInfo(): {}", call_error(&e, timeout)))?;
+
+    Ok(info)
+}
+
+fn varlink_info(
+    address: Option<&str>,
+    resolver: &str,
+    activate: Option<&str>,
+    bridge: Option<&str>,
+    timeout: Option<u64>,
+    should_colorize: bool,
+) -> Result<()> {
+    let bold: fn(w: &str) -> String = if should_colorize {
+        |w| Style::new().bold().paint(w).to_string()
+    } else {
+        |w| w.to_string()
+    };
+
+    let info = varlink_service_info(address, resolver, activate, bridge, timeout)?;
 
     println!("{} {}", bold("Vendor:"), info.vendor);
     println!("{} {}", bold("Product:"), info.product);
@@ -104,24 +175,86 @@ fn varlink_info(
     Ok(())
 }
 
-fn varlink_help(
+fn varlink_info_has(
+    interface: &str,
+    address: Option<&str>,
+    resolver: &str,
+    activate: Option<&str>,
+    bridge: Option<&str>,
+    timeout: Option<u64>,
+) -> Result<bool> {
+    let info = varlink_service_info(address, resolver, activate, bridge, timeout)?;
+    Ok(info.interfaces.iter().any(|i| i == interface))
+}
+
+fn varlink_resolve(interface: &str, resolver: &str, timeout: Option<u64>) -> Result<String> {
+    let conn = Connection::new(resolver).map_err(|e| {
+        format!(
+            "Failed to connect with resolver '{resolver}': {}",
+            call_error(&e, None)
+        )
+    })?;
+    set_timeout(&conn, timeout)?;
+
+    let mut resolver = VarlinkClient::new(conn);
+    match resolver.resolve(interface.into()).call() {
+        Ok(r) => Ok(r.address),
+        Err(e) => match e.kind() {
+            ResolverErrorKind::InterfaceNotFound(_) => {
+                Err(format!("Interface '{interface}' not found").into())
+            }
+            _ => {
+                let reason = match e.source().and_then(|e| e.downcast_ref::<varlink::Error>()) {
+                    Some(e) => call_error(e, timeout),
+                    None => e.to_string(),
+                };
+                Err(format!("Failed to resolve '{interface}': {reason}").into())
+            }
+        },
+    }
+}
+
+/// Interface descriptions fetched by this process, by the way to connect and the interface
+static DESCRIPTIONS: Mutex<BTreeMap<(String, String), String>> = Mutex::new(BTreeMap::new());
+
+/// Get the description of the interface `url`, which is `[ADDRESS/]INTERFACE`.
+///
+/// With `use_cache`, a description fetched before by this process is reused without connecting
+/// again.
+fn varlink_interface_description(
     url: &str,
     resolver: &str,
     activate: Option<&str>,
     bridge: Option<&str>,
-    columns: Option<&str>,
-    should_colorize: bool,
-) -> Result<()> {
-    let address: &str;
-    let interface: &str;
+    timeout: Option<u64>,
+    use_cache: bool,
+) -> Result<String> {
+    let (address, interface) = match url.rfind('/') {
+        Some(del) => (Some(&url[0..del]), &url[(del + 1)..]),
+        None => (None, url),
+    };
+
+    if interface.find('.').is_none() {
+        return Err(format!("Invalid address {}", url).into());
+    }
 
-    let connection = if let Some(del) = url.rfind('/') {
-        address = &url[0..del];
-        interface = &url[(del + 1)..];
+    let key = match (address, activate, bridge) {
+        (Some(address), _, _) => address.to_string(),
+        (None, Some(activate), _) => format!("activate:{activate}"),
+        (None, None, Some(bridge)) => format!("bridge:{bridge}"),
+        (None, None, None) => format!("resolver:{resolver}"),
+    };
+    let key = (key, interface.to_string());
+    if use_cache {
+        if let Some(desc) = DESCRIPTIONS.lock().unwrap().get(&key) {
+            return Ok(desc.clone());
+        }
+    }
+
+    let connection = if let Some(address) = address {
         Connection::with_address(&address)
-            .map_err(|e| format!("Cannot connect to '{address}': {e}"))?
+            .map_err(|e| format!("Cannot connect to '{address}': {}", call_error(&e, None)))?
     } else {
-        interface = url;
         match activate {
             Some(activate) => Connection::with_activate(activate)
                 .map_err(|e| format!("Failed to connect with activate '{activate}': {e}"))?,
@@ -130,61 +263,105 @@ fn varlink_help(
                     .map_err(|e| format!("Failed to connect with bridge '{bridge}': {e}"))?,
                 None => {
                     let conn = Connection::new(resolver).map_err(|e| {
-                        format!("Failed to connect with resolver '{resolver}': {e}")
+                        format!(
+                            "Failed to connect with resolver '{resolver}': {}",
+                            call_error(&e, None)
+                        )
                     })?;
                     let mut resolver = VarlinkClient::new(conn);
                     let address = match resolver.resolve(interface.into()).call() {
                         Ok(r) => r.address,
                         _ => return Err(format!("Interface '{interface}' not found").into()),
                     };
-                    Connection::with_address(&address)
-                        .map_err(|e| format!("Failed to connect to '{address}': {e}"))?
+                    Connection::with_address(&address).map_err(|e| {
+                        format!("Failed to connect to '{address}': {}", call_error(&e, None))
+                    })?
                 }
             },
         }
     };
 
-    if interface.find('.').is_none() {
-        return Err(format!("Invalid address {}", url).into());
-    }
+    set_timeout(&connection, timeout)?;
 
     let mut call = OrgVarlinkServiceClient::new(connection);
-    match call
+    let desc = match call
         .get_interface_description(interface.to_string())
-        .map_err(|e| format!("Can't get interface description for '{interface}': {e}"))?
-    {
+        .map_err(|e| {
+            format!(
+                "Can't get interface description for '{interface}': {}",
+                call_error(&e, timeout)
+            )
+        })? {
         GetInterfaceDescriptionReply {
             description: Some(desc),
-        } => {
-            if should_colorize {
-                println!(
-                    "{}",
-                    IDL::try_from(desc.as_str())
-                        .map_err(|e| format!("Can't parse '{desc}': {e}"))?
-                        .get_multiline_colored(
-                            0,
-                            columns.unwrap_or("80").parse::<usize>().unwrap_or(80),
-                        )
-                );
-            } else {
-                println!(
-                    "{}",
-                    IDL::try_from(desc.as_str())
-                        .map_err(|e| format!("Can't parse '{desc}': {e}"))?
-                        .get_multiline(0, columns.unwrap_or("80").parse::<usize>().unwrap_or(80))
-                );
-            }
-        }
+        } => desc,
         _ => return Err(format!("No description for {}", url).into()),
     };
 
+    if use_cache {
+        DESCRIPTIONS.lock().unwrap().insert(key, desc.clone());
+    }
+    Ok(desc)
+}
+
+fn varlink_help(desc: &str, columns: Option<&str>, should_colorize: bool) -> Result<()> {
+    let idl = IDL::try_from(desc).map_err(|e| format!("Can't parse '{desc}': {e}"))?;
+    let columns = columns.unwrap_or("80").parse::<usize>().unwrap_or(80);
+    if should_colorize {
+        println!("{}", idl.get_multiline_colored(0, columns));
+    } else {
+        println!("{}", idl.get_multiline(0, columns));
+    }
+
     Ok(())
 }
 
+/// The JSON arguments for `varlink call`.
+///
+/// `args` wins, if it is not `-`, then the content of `args_file`. With `-` as `args`,
+/// the arguments are read from `stdin`.
+fn read_call_args(
+    args: Option<&str>,
+    args_file: Option<&str>,
+    stdin: &mut dyn Read,
+) -> Result<Option<String>> {
+    match (args, args_file) {
+        (Some(args), _) if args != "-" => Ok(Some(args.to_string())),
+        (_, Some(filename)) => {
+            let mut buffer = String::new();
+            File::open(Path::new(filename))
+                .map_err(|e| format!("Failed to open '{filename}': {e}"))?
+                .read_to_string(&mut buffer)
+                .map_err(|e| format!("Failed to read '{filename}': {e}"))?;
+            Ok(Some(buffer))
+        }
+        (Some(_), None) => {
+            let mut buffer = String::new();
+            stdin
+                .read_to_string(&mut buffer)
+                .map_err(|e| format!("Failed to read arguments from stdin: {e}"))?;
+            Ok(Some(buffer))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+/// How `varlink call` calls the method and prints the replies
+struct CallOptions {
+    /// wait for multiple replies
+    more: bool,
+    /// print the replies exactly as received
+    raw: bool,
+    /// print the replies as compact JSON, one per line, and errors as JSON on stderr
+    json: bool,
+    /// seconds to wait for a reply, `None` waits forever
+    timeout: Option<u64>,
+}
+
 fn varlink_call(
     url: &str,
     args: Option<&str>,
-    more: bool,
+    options: &CallOptions,
     resolver: &str,
     activate: Option<&str>,
     bridge: Option<&str>,
@@ -225,7 +402,10 @@ fn varlink_call(
                         return Err(format!("Invalid address {}", url).into());
                     }
                     let conn = Connection::new(resolver).map_err(|e| {
-                        format!("Failed to connect with resolver '{resolver}': {e}")
+                        format!(
+                            "Failed to connect with resolver '{resolver}': {}",
+                            call_error(&e, None)
+                        )
                     })?;
                     let mut resolver = VarlinkClient::new(conn);
                     address = match resolver.resolve(interface.into()).call() {
@@ -236,12 +416,15 @@ fn varlink_call(
                         _ => return Err(format!("Interface '{}' not found", interface).into()),
                     };
                 }
-                Connection::with_address(address)
-                    .map_err(|e| format!("Failed to connect to '{address}': {e}"))?
+                Connection::with_address(address).map_err(|e| {
+                    format!("Failed to connect to '{address}': {}", call_error(&e, None))
+                })?
             }
         },
     };
 
+    set_timeout(&connection, options.timeout)?;
+
     let args = match args {
         Some(args) => serde_json::from_str(args)
             .map_err(|e| format!("Failed to parse JSON for '{args}': {e}"))?,
@@ -254,6 +437,29 @@ fn varlink_call(
         args.clone(),
     );
 
+    if options.raw {
+        return print_raw_replies(&mut call, options.more, &mut std::io::stdout()).map_err(|e| {
+            let e = match e.downcast_ref::<varlink::Error>() {
+                Some(e) => call_error(e, options.timeout),
+                None => e.to_string(),
+            };
+            format!("Failed to call method '{method}({args})': {e}").into()
+        });
+    }
+
+    if options.json {
+        let ok = print_json_replies(
+            &mut call,
+            options,
+            &mut std::io::stdout(),
+            &mut std::io::stderr(),
+        )?;
+        if !ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let color_mode = if should_colorize {
         ColorMode::On
     } else {
@@ -275,25 +481,123 @@ fn varlink_call(
         },
     );
 
-    if !more {
+    if !options.more {
         let ret = call.call();
-        print_call_ret(color_mode, cf, ret, should_colorize, method, &args)?
+        print_call_ret(color_mode, cf, ret, options, should_colorize, method, &args)?
     } else {
-        for ret in call
-            .more()
-            .map_err(|e| format!("Failed to call method '{method}({args})': {e}"))?
-        {
-            print_call_ret(color_mode, cf.clone(), ret, should_colorize, method, &args)?
+        for ret in call.more().map_err(|e| {
+            format!(
+                "Failed to call method '{method}({args})': {}",
+                call_error(&e, options.timeout)
+            )
+        })? {
+            print_call_ret(
+                color_mode,
+                cf.clone(),
+                ret,
+                options,
+                should_colorize,
+                method,
+                &args,
+            )?
         }
     }
 
     Ok(())
 }
 
+/// Write the replies of `call` as received from the service, one per line.
+fn print_raw_replies(
+    call: &mut MethodCall<serde_json::Value, serde_json::Value, varlink::Error>,
+    more: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    fn print_raw(
+        call: &MethodCall<serde_json::Value, serde_json::Value, varlink::Error>,
+        ret: varlink::Result<serde_json::Value>,
+        out: &mut dyn Write,
+    ) -> Result<()> {
+        if let Some(raw) = call.raw_reply() {
+            out.write_all(raw)?;
+            out.write_all(b"\n")?;
+        }
+        ret?;
+        Ok(())
+    }
+
+    call.set_keep_raw_reply(true);
+
+    if !more {
+        let ret = call.call();
+        return print_raw(call, ret, out);
+    }
+
+    let call = call.more()?;
+    while let Some(ret) = call.next() {
+        print_raw(call, ret, out)?;
+    }
+    Ok(())
+}
+
+/// Write the replies of `call` as compact JSON, one per line.
+///
+/// An error ends the call and is written to `err` as `{"error":...,"parameters":{...}}`.
+/// Returns `false`, if the call failed.
+fn print_json_replies(
+    call: &mut MethodCall<serde_json::Value, serde_json::Value, varlink::Error>,
+    options: &CallOptions,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> Result<bool> {
+    let mut print_json = |ret: varlink::Result<serde_json::Value>| -> Result<bool> {
+        match ret {
+            Ok(reply) => {
+                writeln!(out, "{}", serde_json::to_string(&reply)?)?;
+                Ok(true)
+            }
+            Err(e) => {
+                writeln!(
+                    err,
+                    "{}",
+                    serde_json::to_string(&json_error(&e, options.timeout))?
+                )?;
+                Ok(false)
+            }
+        }
+    };
+
+    if !options.more {
+        return print_json(call.call());
+    }
+
+    let call = match call.more() {
+        Ok(call) => call,
+        Err(e) => return print_json(Err(e)),
+    };
+    for ret in call {
+        if !print_json(ret)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The JSON object for `e`, with the varlink error name and its parameters.
+fn json_error(e: &varlink::Error, timeout: Option<u64>) -> serde_json::Value {
+    use serde_json::json;
+
+    match (e.error_name(), e.error_parameters()) {
+        (Some(error), Some(parameters)) => json!({ "error": error, "parameters": parameters }),
+        (Some(error), None) => json!({ "error": error }),
+        (None, _) => json!({ "error": call_error(e, timeout) }),
+    }
+}
+
 fn print_call_ret(
     color_mode: ColorMode,
     cf: ColoredFormatter<PrettyFormatter>,
     ret: varlink::Result<serde_json::Value>,
+    options: &CallOptions,
     should_colorize: bool,
     method: &str,
     args: &serde_json::Value,
@@ -337,6 +641,12 @@ fn print_call_ret(
                 red(error),
                 cf.to_colored_json(parameters, color_mode).unwrap()
             ),
+            varlink::ErrorKind::Timeout => format!(
+                "Failed to call method '{}({})': {}",
+                &method,
+                &args,
+                call_error(&e, options.timeout)
+            ),
             _ => format!("Failed to call method '{}({})'", &method, &args),
         }
     })?;
@@ -350,6 +660,20 @@ fn print_call_ret(
     Ok(())
 }
 
+/// Connect to the `--connect` address of `bridge`.
+///
+/// An interface name without a `:` is resolved with the resolver, like the interface of `call`.
+#[cfg(target_os = "linux")]
+fn varlink_bridge_connect(address: &str, resolver: &str) -> Result<Arc<RwLock<Connection>>> {
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        varlink_resolve(address, resolver, None)?
+    };
+    Ok(Connection::with_address_no_rw(&address)
+        .map_err(|e| format!("Failed to connect to '{address}': {}", call_error(&e, None)))?)
+}
+
 #[cfg(target_os = "linux")]
 fn varlink_bridge(
     address: Option<&str>,
@@ -367,21 +691,7 @@ fn varlink_bridge(
                 .map_err(|e| format!("Failed to connect with bridge '{bridge}': {e}"))?,
             None => {
                 if let Some(address) = address {
-                    if address.rfind(':').is_none() {
-                        let conn = Connection::new(resolver).map_err(|e| {
-                            format!("Failed to connect with resolver '{resolver}': {e}")
-                        })?;
-                        let mut resolver = VarlinkClient::new(conn);
-                        let address = match resolver.resolve(address.into()).call() {
-                            Ok(r) => r.address,
-                            _ => return Err(format!("Interface '{}' not found", address).into()),
-                        };
-                        Connection::with_address_no_rw(&address)
-                            .map_err(|e| format!("Failed to connect to '{address}': {e}"))?
-                    } else {
-                        Connection::with_address_no_rw(&address)
-                            .map_err(|e| format!("Failed to connect to '{address}': {e}"))?
-                    }
+                    varlink_bridge_connect(address, resolver)?
                 } else {
                     let stdin = ::std::io::stdin();
                     let stdout = ::std::io::stdout();
@@ -399,7 +709,8 @@ fn varlink_bridge(
 
     if let Err(ref e) = r {
         if let Some(io_e) = e.downcast_ref::<std::io::Error>() {
-            if io_e.kind() == std::io::ErrorKind::BrokenPipe {
+            // the client went away
+            if varlink::ErrorKind::from(io_e) == varlink::ErrorKind::BrokenPipe {
                 return Ok(());
             }
         }
@@ -416,7 +727,7 @@ fn varlink_bridge(
     _activate: Option<&str>,
     _bridge: Option<&str>,
 ) -> Result<()> {
-    Err("Not implemented for this architecture. Waiting for a stable rust async interface.".into())
+    Err(varlink::Error::from(varlink::ErrorKind::OperationNotSupported("bridge".into())).into())
 }
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -427,17 +738,20 @@ fn main() {
 
     let mut app = App::new("varlink")
         .version(VERSION)
-        /*
-                .arg(
-                    Arg::with_name("timeout")
-                        .short("t")
-                        .long("timeout")
-                        .value_name("SECONDS")
-                        .help("time in seconds to wait for a reply")
-                        .takes_value(true),
-                )
-        */
+        .arg(
+            Arg::with_name("timeout")
+                .short("t")
+                .long("timeout")
+                .value_name("SECONDS")
+                .help("time in seconds to wait for a reply")
+                .takes_value(true),
+        )
         .arg(Arg::with_name("debug").long("debug").help("print debug"))
+        .arg(
+            Arg::with_name("no-cache")
+                .long("no-cache")
+                .help("fetch interface descriptions again, instead of reusing them"),
+        )
         .arg(
             Arg::with_name("color")
                 .long("color")
@@ -490,7 +804,7 @@ fn main() {
                         .short("C")
                         .long("connect")
                         .value_name("ADDRESS")
-                        .help("connect directly to ADDRESS")
+                        .help("connect directly to ADDRESS, or to the service of an interface name")
                         .required(false)
                         .takes_value(true),
                 ),
@@ -506,12 +820,42 @@ fn main() {
                         .long("more")
                         .help("wait for multiple method returns if supported"),
                 )
+                .arg(
+                    Arg::with_name("raw")
+                        .short("r")
+                        .long("raw")
+                        .help("print the replies exactly as received"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .short("j")
+                        .long("json")
+                        .conflicts_with("raw")
+                        .help("print the replies as compact JSON, one per line")
+                        .long_help(
+                            "Print every reply as compact JSON on a line of its own. An error \
+                             is printed to stderr as JSON object with the fields `error` and \
+                             `parameters`.",
+                        ),
+                )
                 .arg(
                     Arg::with_name("METHOD")
                         .value_name("[ADDRESS/]INTERFACE.METHOD")
                         .required(true),
                 )
-                .arg(Arg::with_name("ARGUMENTS").required(false)),
+                .arg(
+                    Arg::with_name("args-file")
+                        .short("f")
+                        .long("args-file")
+                        .value_name("PATH")
+                        .help("read the JSON arguments from PATH")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("ARGUMENTS")
+                        .required(false)
+                        .help("JSON arguments of the method, '-' reads them from stdin"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("format")
@@ -531,11 +875,31 @@ fn main() {
                         .help("The varlink interface definition file to format"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("describe")
+                .version(VERSION)
+                .about("Print a varlink service file as JSON")
+                .arg(
+                    Arg::with_name("FILE")
+                        .required(true)
+                        .help("The varlink interface definition file to describe"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("info")
                 .version(VERSION)
                 .about("Print information about a service")
                 .long_about("Prints information about the service running at ADDRESS.")
+                .arg(
+                    Arg::with_name("has")
+                        .long("has")
+                        .value_name("INTERFACE")
+                        .help(
+                            "print nothing, exit with 0 if the service implements INTERFACE, \
+                             non-zero otherwise",
+                        )
+                        .takes_value(true),
+                )
                 .arg(Arg::with_name("ADDRESS").required(false)),
         )
         .subcommand(
@@ -611,6 +975,13 @@ fn do_main(app: &mut App) -> Result<()> {
         "off" => false,
         _ => ColorMode::should_colorize(Output::StdOut),
     };
+    let timeout = match matches.value_of("timeout") {
+        Some(t) => Some(
+            t.parse::<u64>()
+                .map_err(|e| format!("Invalid timeout '{t}': {e}"))?,
+        ),
+        None => None,
+    };
 
     match matches.subcommand() {
         ("completions", Some(sub_matches)) => {
@@ -623,6 +994,10 @@ fn do_main(app: &mut App) -> Result<()> {
 
             varlink_format(filename, cols, should_colorize)?
         }
+        ("describe", Some(sub_matches)) => {
+            let filename = sub_matches.value_of("FILE").unwrap();
+            varlink_describe(filename, &mut std::io::stdout())?
+        }
         ("info", Some(sub_matches)) => {
             let address = sub_matches.value_of("ADDRESS");
             if address.is_none() && activate.is_none() && bridge.is_none() {
@@ -632,7 +1007,21 @@ fn do_main(app: &mut App) -> Result<()> {
                 return Err("No ADDRESS or activation or bridge".to_string().into());
             }
 
-            varlink_info(address, resolver, activate, bridge, should_colorize)?
+            if let Some(interface) = sub_matches.value_of("has") {
+                if !varlink_info_has(interface, address, resolver, activate, bridge, timeout)? {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            varlink_info(
+                address,
+                resolver,
+                activate,
+                bridge,
+                timeout,
+                should_colorize,
+            )?
         }
         ("bridge", Some(sub_matches)) => {
             let address = sub_matches.value_of("connect");
@@ -641,17 +1030,38 @@ fn do_main(app: &mut App) -> Result<()> {
         ("help", Some(sub_matches)) => {
             let interface = sub_matches.value_of("INTERFACE").unwrap();
             let cols = sub_matches.value_of("COLUMNS");
-            varlink_help(interface, resolver, activate, bridge, cols, should_colorize)?
+            let desc = varlink_interface_description(
+                interface,
+                resolver,
+                activate,
+                bridge,
+                timeout,
+                !matches.is_present("no-cache"),
+            )?;
+            varlink_help(&desc, cols, should_colorize)?
+        }
+        ("resolve", Some(sub_matches)) => {
+            let interface = sub_matches.value_of("INTERFACE").unwrap();
+            println!("{}", varlink_resolve(interface, resolver, timeout)?);
         }
         ("call", Some(sub_matches)) => {
             let method = sub_matches.value_of("METHOD").unwrap();
-            let args = sub_matches.value_of("ARGUMENTS");
-            let more = sub_matches.is_present("more");
+            let args = read_call_args(
+                sub_matches.value_of("ARGUMENTS"),
+                sub_matches.value_of("args-file"),
+                &mut io::stdin(),
+            )?;
+            let options = CallOptions {
+                more: sub_matches.is_present("more"),
+                raw: sub_matches.is_present("raw"),
+                json: sub_matches.is_present("json"),
+                timeout,
+            };
 
             varlink_call(
                 method,
-                args,
-                more,
+                args.as_deref(),
+                &options,
                 resolver,
                 activate,
                 bridge,
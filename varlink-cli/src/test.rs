@@ -0,0 +1,564 @@
+use crate::Result;
+use std::{thread, time};
+
+#[test]
+fn test_info_has() -> Result<()> {
+    let address = "unix:test_varlink_cli_info_has";
+
+    let child = thread::spawn(move || {
+        let service = varlink::VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        if let Err(e) = varlink::listen(
+            service,
+            &address,
+            &varlink::ListenConfig {
+                idle_timeout: 2,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != varlink::ErrorKind::Timeout {
+                panic!("error: {}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_secs(1));
+
+    let resolver = "unix:/run/org.varlink.resolver";
+    assert!(crate::varlink_info_has(
+        "org.varlink.service",
+        Some(address),
+        resolver,
+        None,
+        None,
+        None
+    )?);
+    assert!(!crate::varlink_info_has(
+        "org.example.unknown",
+        Some(address),
+        resolver,
+        None,
+        None,
+        None
+    )?);
+
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    Ok(())
+}
+
+#[test]
+fn test_call_raw() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use varlink::{Connection, MethodCall};
+
+    // key order and float formatting would not survive a round trip through serde_json
+    const REPLY: &str = r#"{"parameters":{"b":1.0,"a":2}}"#;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = format!("tcp:{}", listener.local_addr()?);
+
+    let child = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = Vec::new();
+        reader.read_until(0, &mut request).unwrap();
+        (&stream)
+            .write_all(format!("{}\0", REPLY).as_bytes())
+            .unwrap();
+    });
+
+    let connection = Connection::with_address(&address)?;
+    let mut call = MethodCall::<serde_json::Value, serde_json::Value, varlink::Error>::new(
+        connection,
+        "org.example.raw.Get",
+        serde_json::json!({}),
+    );
+    let mut out = Vec::new();
+    crate::print_raw_replies(&mut call, false, &mut out)?;
+    assert_eq!(String::from_utf8(out)?, format!("{}\n", REPLY));
+
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    Ok(())
+}
+
+#[test]
+fn test_call_timeout() -> Result<()> {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = format!("tcp:{}", listener.local_addr()?);
+
+    // accept the connection, but never reply
+    let child = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = Vec::new();
+        let _ = stream.read_to_end(&mut buf);
+    });
+
+    let options = crate::CallOptions {
+        more: false,
+        raw: false,
+        json: false,
+        timeout: Some(1),
+    };
+    let start = time::Instant::now();
+    let e = crate::varlink_call(
+        &format!("{}/org.example.hang.Hang", address),
+        None,
+        &options,
+        "unix:/run/org.varlink.resolver",
+        None,
+        None,
+        false,
+    )
+    .unwrap_err();
+    assert!(start.elapsed() < time::Duration::from_secs(5));
+    assert!(
+        e.to_string().ends_with("timed out after 1 s"),
+        "unexpected error: {}",
+        e
+    );
+
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    Ok(())
+}
+
+#[test]
+fn test_call_json() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use varlink::{Connection, MethodCall};
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = format!("tcp:{}", listener.local_addr()?);
+
+    let child = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = Vec::new();
+        reader.read_until(0, &mut request).unwrap();
+        (&stream)
+            .write_all(
+                concat!(
+                    r#"{"continues":true,"parameters":{"n":1}}"#,
+                    "\0",
+                    r#"{"parameters":{"n":2,"s":"two"}}"#,
+                    "\0"
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        request.clear();
+        reader.read_until(0, &mut request).unwrap();
+        (&stream)
+            .write_all(
+                concat!(
+                    r#"{"error":"org.example.json.Failed","parameters":{"reason":"test"}}"#,
+                    "\0"
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+    });
+
+    let connection = Connection::with_address(&address)?;
+    let mut options = crate::CallOptions {
+        more: true,
+        raw: false,
+        json: true,
+        timeout: None,
+    };
+
+    let mut call = MethodCall::<serde_json::Value, serde_json::Value, varlink::Error>::new(
+        connection.clone(),
+        "org.example.json.Get",
+        serde_json::json!({}),
+    );
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    assert!(crate::print_json_replies(
+        &mut call, &options, &mut out, &mut err
+    )?);
+    assert!(err.is_empty());
+    let replies = String::from_utf8(out)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<std::result::Result<Vec<serde_json::Value>, _>>()?;
+    assert_eq!(
+        replies,
+        vec![
+            serde_json::json!({ "n": 1 }),
+            serde_json::json!({ "n": 2, "s": "two" })
+        ]
+    );
+
+    options.more = false;
+    let mut call = MethodCall::<serde_json::Value, serde_json::Value, varlink::Error>::new(
+        connection,
+        "org.example.json.Fail",
+        serde_json::json!({}),
+    );
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    assert!(!crate::print_json_replies(
+        &mut call, &options, &mut out, &mut err
+    )?);
+    assert!(out.is_empty());
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&err)?,
+        serde_json::json!({
+            "error": "org.example.json.Failed",
+            "parameters": { "reason": "test" }
+        })
+    );
+
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    Ok(())
+}
+
+#[test]
+fn test_call_args_stdin() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = format!("tcp:{}", listener.local_addr()?);
+
+    let child = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request = Vec::new();
+        reader.read_until(0, &mut request).unwrap();
+        request.pop();
+        (&stream).write_all(b"{\"parameters\":{}}\0").unwrap();
+        serde_json::from_slice::<serde_json::Value>(&request).unwrap()
+    });
+
+    let mut stdin = r#"{ "name": "from stdin", "list": [1, 2, 3] }"#.as_bytes();
+
+    // the positional argument wins, then the file
+    assert_eq!(
+        crate::read_call_args(Some("{}"), Some("/nonexistent"), &mut stdin)?.as_deref(),
+        Some("{}")
+    );
+    assert!(crate::read_call_args(Some("-"), Some("/nonexistent"), &mut stdin).is_err());
+    assert_eq!(crate::read_call_args(None, None, &mut stdin)?, None);
+
+    let args = crate::read_call_args(Some("-"), None, &mut stdin)?;
+    let options = crate::CallOptions {
+        more: false,
+        raw: false,
+        json: true,
+        timeout: None,
+    };
+    crate::varlink_call(
+        &format!("{}/org.example.args.Set", address),
+        args.as_deref(),
+        &options,
+        "unix:/run/org.varlink.resolver",
+        None,
+        None,
+        false,
+    )?;
+
+    let request = child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    assert_eq!(
+        request["parameters"],
+        serde_json::json!({ "name": "from stdin", "list": [1, 2, 3] })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_resolve() -> Result<()> {
+    use varlink_stdinterfaces::org_varlink_resolver::{
+        self, Call_GetInfo, Call_Resolve, VarlinkInterface,
+    };
+
+    struct Resolver;
+
+    impl VarlinkInterface for Resolver {
+        fn get_info(&self, call: &mut dyn Call_GetInfo) -> varlink::Result<()> {
+            call.reply(
+                "org.varlink".into(),
+                "test resolver".into(),
+                "0.1".into(),
+                "http://varlink.org".into(),
+                vec!["org.example.foo".into()],
+            )
+        }
+
+        fn resolve(&self, call: &mut dyn Call_Resolve, interface: String) -> varlink::Result<()> {
+            match interface.as_ref() {
+                "org.example.foo" => call.reply("unix:/run/org.example.foo".into()),
+                _ => call.reply_interface_not_found(interface),
+            }
+        }
+    }
+
+    let address = "unix:test_varlink_cli_resolve";
+
+    let child = thread::spawn(move || {
+        let service = varlink::VarlinkService::new(
+            "org.varlink",
+            "test resolver",
+            "0.1",
+            "http://varlink.org",
+            vec![Box::new(org_varlink_resolver::new(Box::new(Resolver)))],
+        );
+        if let Err(e) = varlink::listen(
+            service,
+            &address,
+            &varlink::ListenConfig {
+                idle_timeout: 1,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != varlink::ErrorKind::Timeout {
+                panic!("error: {}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    assert_eq!(
+        crate::varlink_resolve("org.example.foo", address, None)?,
+        "unix:/run/org.example.foo"
+    );
+    assert_eq!(
+        crate::varlink_resolve("org.example.bar", address, None)
+            .unwrap_err()
+            .to_string(),
+        "Interface 'org.example.bar' not found"
+    );
+
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    Ok(())
+}
+
+#[test]
+fn test_help_cache() -> Result<()> {
+    use std::io::BufReader;
+    use std::net::TcpListener;
+    use varlink::ConnectionHandler;
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = format!("tcp:{}", listener.local_addr()?);
+
+    // serves exactly one connection
+    let child = thread::spawn(move || -> varlink::Result<()> {
+        let service = varlink::VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = &stream;
+        service.handle(&mut BufReader::new(&stream), &mut writer, None)?;
+        Ok(())
+    });
+
+    let url = format!("{}/org.varlink.service", address);
+    let resolver = "unix:/run/org.varlink.resolver";
+    let desc = crate::varlink_interface_description(&url, resolver, None, None, None, true)?;
+    assert!(desc.starts_with("# The Varlink Service Interface"));
+
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())??;
+
+    // the service is gone, only this process remembers the description
+    assert_eq!(
+        crate::varlink_interface_description(&url, resolver, None, None, None, true)?,
+        desc
+    );
+    assert!(crate::varlink_interface_description(&url, resolver, None, None, None, false).is_err());
+
+    // another address with the same interface is not taken from the cache
+    let other = format!("{}0/org.varlink.service", address);
+    assert!(
+        crate::varlink_interface_description(&other, resolver, None, None, None, true).is_err()
+    );
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_bridge_connect_resolve() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::Shutdown;
+    use std::os::unix::net::UnixStream;
+    use varlink_stdinterfaces::org_varlink_resolver::{
+        self, Call_GetInfo, Call_Resolve, VarlinkInterface,
+    };
+
+    const ADDRESS: &str = "unix:test_varlink_cli_bridge_resolve";
+
+    // resolves the interface to the resolver itself
+    struct Resolver;
+
+    impl VarlinkInterface for Resolver {
+        fn get_info(&self, call: &mut dyn Call_GetInfo) -> varlink::Result<()> {
+            call.reply(
+                "org.varlink".into(),
+                "test resolver".into(),
+                "0.1".into(),
+                "http://varlink.org".into(),
+                vec!["org.varlink.resolver".into()],
+            )
+        }
+
+        fn resolve(&self, call: &mut dyn Call_Resolve, interface: String) -> varlink::Result<()> {
+            match interface.as_ref() {
+                "org.varlink.resolver" => call.reply(ADDRESS.into()),
+                _ => call.reply_interface_not_found(interface),
+            }
+        }
+    }
+
+    let child = thread::spawn(move || {
+        let service = varlink::VarlinkService::new(
+            "org.varlink",
+            "test resolver",
+            "0.1",
+            "http://varlink.org",
+            vec![Box::new(org_varlink_resolver::new(Box::new(Resolver)))],
+        );
+        if let Err(e) = varlink::listen(
+            service,
+            ADDRESS,
+            &varlink::ListenConfig {
+                idle_timeout: 1,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != varlink::ErrorKind::Timeout {
+                panic!("error: {}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    match crate::varlink_bridge_connect("org.example.bar", ADDRESS) {
+        Err(e) => assert_eq!(e.to_string(), "Interface 'org.example.bar' not found"),
+        Ok(_) => panic!("org.example.bar should not be resolved"),
+    }
+
+    let connection = crate::varlink_bridge_connect("org.varlink.resolver", ADDRESS)?;
+    let (client, bridged) = UnixStream::pair()?;
+    let bridge = thread::spawn(move || {
+        crate::proxy::handle_connect(connection, bridged.try_clone().unwrap(), bridged)
+            .map_err(|e| e.to_string())
+    });
+
+    (&client).write_all(b"{\"method\":\"org.varlink.service.GetInfo\"}\0")?;
+    let mut reply = Vec::new();
+    BufReader::new(&client).read_until(0, &mut reply)?;
+    reply.pop();
+    let reply: serde_json::Value = serde_json::from_slice(&reply)?;
+    assert_eq!(reply["parameters"]["product"], "test resolver");
+
+    // like in `varlink_bridge`, closing the client may end the bridge with a broken pipe
+    client.shutdown(Shutdown::Both)?;
+    if let Err(e) = bridge
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?
+    {
+        assert_eq!(
+            e,
+            std::io::Error::from(std::io::ErrorKind::BrokenPipe).to_string()
+        );
+    }
+    child
+        .join()
+        .map_err(|_| "Error joining thread".to_string())?;
+    Ok(())
+}
+
+#[test]
+fn test_describe() -> Result<()> {
+    use varlink::Interface;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("org.varlink.service.varlink");
+    let path = path.to_str().unwrap();
+    let service = varlink::VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![],
+    );
+    std::fs::write(path, service.get_description())?;
+
+    let mut out = Vec::new();
+    crate::varlink_describe(path, &mut out)?;
+    let json: serde_json::Value = serde_json::from_slice(&out)?;
+    assert_eq!(json["interface"], "org.varlink.service");
+    let methods = json["methods"].as_array().unwrap();
+    assert_eq!(methods.len(), 2);
+    assert_eq!(methods[0]["name"], "GetInfo");
+    assert_eq!(json["errors"].as_array().unwrap().len(), 4);
+
+    std::fs::write(
+        path,
+        "interface org.example.broken\nmethod F() -> (a: Missing)\n",
+    )?;
+    assert!(crate::varlink_describe(path, &mut Vec::new()).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_connect_error() -> Result<()> {
+    use std::net::TcpListener;
+
+    let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    let options = crate::CallOptions {
+        more: false,
+        raw: false,
+        json: false,
+        timeout: None,
+    };
+    let e = crate::varlink_call(
+        &format!("tcp:127.0.0.1:{}/org.example.gone.Get", port),
+        None,
+        &options,
+        "unix:/run/org.varlink.resolver",
+        None,
+        None,
+        false,
+    )
+    .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        format!(
+            "Failed to connect to 'tcp:127.0.0.1:{}': connection refused, is the service running?",
+            port
+        )
+    );
+    Ok(())
+}
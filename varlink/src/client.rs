@@ -8,6 +8,9 @@ use std::os::unix::io::{AsRawFd, IntoRawFd};
 #[cfg(unix)]
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::process::Child;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
 #[cfg(unix)]
 use libc::{close, dup2, getpid};
@@ -18,14 +21,97 @@ use uds_windows::UnixStream;
 use crate::error::*;
 use crate::stream::Stream;
 
+/// A user provided transport for an address scheme
+///
+/// Register it with [`register_transport`], to connect to addresses with a scheme, which is
+/// not known to `varlink` itself. Closures `Fn(&str) -> Result<Box<dyn Stream>>` implement
+/// this trait.
+pub trait TransportFactory: Send + Sync {
+    /// Connect to `address`, which is the part of the varlink address after `<scheme>:`.
+    fn connect(&self, address: &str) -> Result<Box<dyn Stream>>;
+}
+
+impl<F> TransportFactory for F
+where
+    F: Fn(&str) -> Result<Box<dyn Stream>> + Send + Sync,
+{
+    fn connect(&self, address: &str) -> Result<Box<dyn Stream>> {
+        self(address)
+    }
+}
+
+type Transports = Vec<(String, Arc<dyn TransportFactory>)>;
+
+static TRANSPORTS: RwLock<Transports> = RwLock::new(Vec::new());
+
+/// Register `factory` for varlink addresses starting with `<scheme>:`.
+///
+/// A factory registered again for the same `scheme` replaces the previous one. The schemes
+/// `tcp` and `unix` can't be overridden.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # #[cfg(unix)]
+/// # fn main() {
+/// use std::os::unix::net::UnixStream;
+/// use varlink::{map_context, Result, Stream};
+///
+/// varlink::register_transport("myunix", |path: &str| -> Result<Box<dyn Stream>> {
+///     Ok(Box::new(UnixStream::connect(path).map_err(map_context!())?))
+/// });
+///
+/// let connection = varlink::Connection::with_address("myunix:/run/org.example.ftl");
+/// # }
+/// # #[cfg(windows)]
+/// # fn main() {}
+/// ```
+pub fn register_transport<F: TransportFactory + 'static>(scheme: &str, factory: F) {
+    let mut transports = TRANSPORTS.write().unwrap();
+    transports.retain(|(s, _)| s != scheme);
+    transports.push((scheme.into(), Arc::new(factory)));
+}
+
+fn registered_transport(address: &str) -> Option<(Arc<dyn TransportFactory>, &str)> {
+    let (scheme, rest) = address.split_once(':')?;
+    TRANSPORTS
+        .read()
+        .unwrap()
+        .iter()
+        .find(|(s, _)| s == scheme)
+        .map(|(_, factory)| (factory.clone(), rest))
+}
+
+/// Map the error of connecting a socket, which distinguishes a missing socket file
+/// from other `io::ErrorKind::NotFound` errors, like a missing command to activate.
+pub(crate) fn connect_error(e: std::io::Error) -> Error {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => context!(e, ErrorKind::AddressNotFound),
+        _ => context!(e, ErrorKind::from(&e)),
+    }
+}
+
 #[allow(clippy::try_err)]
 pub fn varlink_connect<S: ?Sized + AsRef<str>>(address: &S) -> Result<(Box<dyn Stream>, String)> {
     let address = address.as_ref();
     let new_address: String = address.into();
 
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    if let Some(addr) = new_address.strip_prefix("vsock:") {
+        let (cid, port) = crate::vsock::parse_address(addr)?;
+        let stream = crate::vsock::VsockStream::connect(cid, port).map_err(connect_error)?;
+        return Ok((Box::new(stream), new_address));
+    }
+
+    #[cfg(windows)]
+    if let Some(addr) = new_address.strip_prefix("npipe:") {
+        let stream = crate::npipe::NamedPipeStream::connect(addr).map_err(connect_error)?;
+        return Ok((Box::new(stream), new_address));
+    }
+
     if let Some(addr) = new_address.strip_prefix("tcp:") {
         Ok((
-            Box::new(TcpStream::connect(addr).map_err(map_context!())?),
+            Box::new(TcpStream::connect(addr).map_err(connect_error)?),
             new_address,
         ))
     } else if let Some(addr) = new_address.strip_prefix("unix:@") {
@@ -34,9 +120,20 @@ pub fn varlink_connect<S: ?Sized + AsRef<str>>(address: &S) -> Result<(Box<dyn S
     } else if let Some(addr) = new_address.strip_prefix("unix:") {
         let addr = addr.split(';').next().unwrap_or(addr);
         Ok((
-            Box::new(UnixStream::connect(addr).map_err(map_context!())?),
+            Box::new(UnixStream::connect(addr).map_err(connect_error)?),
             new_address,
         ))
+    } else if let Some((factory, addr)) = registered_transport(&new_address) {
+        Ok((factory.connect(addr)?, new_address))
+    } else if new_address.starts_with("vsock:") {
+        // only reached without the `vsock` feature or on other platforms than Linux
+        Err(context!(ErrorKind::OperationNotSupported("vsock".into())))
+    } else if new_address.starts_with("npipe:") {
+        // only reached on other platforms than Windows
+        Err(context!(ErrorKind::OperationNotSupported("npipe".into())))
+    } else if new_address.starts_with("tcp+tls:") {
+        // a TLS connection needs a client configuration, see `Connection::with_tls()`
+        Err(context!(ErrorKind::OperationNotSupported("tcp+tls".into())))
     } else {
         Err(context!(ErrorKind::InvalidAddress))?
     }
@@ -48,19 +145,21 @@ fn get_abstract_unixstream(addr: &str) -> Result<UnixStream> {
     use std::os::unix::net::SocketAddr;
 
     let addr = SocketAddr::from_abstract_name(addr).map_err(map_context!())?;
-    UnixStream::connect_addr(&addr).map_err(map_context!())
+    UnixStream::connect_addr(&addr).map_err(connect_error)
 }
 
 #[cfg(not(any(target_os = "linux", target_os = "android")))]
 fn get_abstract_unixstream(_addr: &str) -> Result<UnixStream> {
-    Err(context!(ErrorKind::InvalidAddress))
+    Err(context!(ErrorKind::OperationNotSupported(
+        "abstract unix sockets".into()
+    )))
 }
 
 #[cfg(windows)]
 pub fn varlink_exec<S: ?Sized + AsRef<str>>(
     _address: &S,
 ) -> Result<(Child, String, Option<TempDir>)> {
-    Err(context!(ErrorKind::MethodNotImplemented(
+    Err(context!(ErrorKind::OperationNotSupported(
         "varlink_exec".into()
     )))
 }
@@ -156,3 +255,106 @@ pub fn varlink_bridge<S: ?Sized + AsRef<str>>(address: &S) -> Result<(Child, Box
         .map_err(map_context!())?;
     Ok((child, Box::new(stream0)))
 }
+
+#[cfg(windows)]
+pub fn varlink_ssh(
+    _user_host: &str,
+    _remote_address: &str,
+) -> Result<(Child, Box<dyn Stream>, JoinHandle<String>)> {
+    Err(context!(ErrorKind::OperationNotSupported(
+        "varlink_ssh".into()
+    )))
+}
+
+/// Start `ssh` to `user_host` running `varlink bridge --connect=remote_address`.
+///
+/// Returns the ssh process, the stream to its stdin/stdout and a thread collecting its stderr.
+#[cfg(unix)]
+pub fn varlink_ssh(
+    user_host: &str,
+    remote_address: &str,
+) -> Result<(Child, Box<dyn Stream>, JoinHandle<String>)> {
+    use std::io::Read;
+    use std::os::fd::OwnedFd;
+    use std::process::{Command, Stdio};
+    use std::thread;
+
+    let (stream0, stream1) = UnixStream::pair().map_err(map_context!())?;
+    let childout = stream1.try_clone().map_err(map_context!())?;
+
+    // ssh passes the remote command to the remote shell, so the address has to be quoted
+    let remote_command = format!(
+        "varlink bridge --connect='{}'",
+        remote_address.replace('\'', "'\\''")
+    );
+
+    let mut child = Command::new("ssh")
+        .arg("-T")
+        .arg("--")
+        .arg(user_host)
+        .arg(remote_command)
+        .stdin(Stdio::from(OwnedFd::from(stream1)))
+        .stdout(Stdio::from(OwnedFd::from(childout)))
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(map_context!())?;
+
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _r = stderr.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    });
+
+    Ok((child, Box::new(stream0), stderr))
+}
+
+/// Turn the error `e` of the first call through `ssh` into a more specific error,
+/// if ssh itself failed.
+pub fn ssh_error(child: &mut Child, stderr: JoinHandle<String>, e: Error) -> Error {
+    match e.kind() {
+        ErrorKind::ConnectionClosed | ErrorKind::BrokenPipe | ErrorKind::Io(_) => {}
+        _ => return e,
+    }
+
+    // ssh exits right after closing the connection, if it failed itself
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                // don't wait for the stderr of a hanging ssh either
+                let _ = child.kill();
+                let _ = child.wait();
+                return e;
+            }
+            Err(_) => return e,
+        }
+    };
+    if status.success() {
+        return e;
+    }
+
+    let message = match stderr.join() {
+        Ok(message) => message.trim().to_string(),
+        Err(_) => return e,
+    };
+
+    // ssh exits with 255, if it failed itself and not the remote command
+    let kind = if status.code() == Some(255)
+        && (message.contains("Permission denied")
+            || message.contains("Too many authentication failures")
+            || message.contains("Host key verification failed"))
+    {
+        ErrorKind::SshAuthenticationFailed(message)
+    } else {
+        ErrorKind::SshConnectionFailed(message)
+    };
+
+    Error(
+        kind,
+        Some(Box::new(e)),
+        Some(concat!(file!(), ":", line!(), ": ")),
+    )
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::io;
 
 #[derive(Clone, PartialEq, Debug)]
@@ -18,6 +19,16 @@ pub enum ErrorKind {
     Timeout,
     ConnectionClosed,
     InvalidAddress,
+    /// Nobody listens on the address, e.g. a TCP port or an abstract unix socket
+    ConnectionRefused,
+    /// The unix socket file of the address does not exist
+    AddressNotFound,
+    /// The other side closed the connection, while writing to it
+    BrokenPipe,
+    OperationNotSupported(String),
+    MessageTooLarge,
+    SshAuthenticationFailed(String),
+    SshConnectionFailed(String),
     Generic,
 }
 
@@ -43,6 +54,15 @@ impl ::std::fmt::Display for ErrorKind {
             ErrorKind::Timeout => write!(f, "Timeout Error"),
             ErrorKind::ConnectionClosed => write!(f, "Connection Closed"),
             ErrorKind::InvalidAddress => write!(f, "Invalid varlink address URI"),
+            ErrorKind::ConnectionRefused => write!(f, "Connection refused"),
+            ErrorKind::AddressNotFound => write!(f, "Address not found"),
+            ErrorKind::BrokenPipe => write!(f, "Broken pipe"),
+            ErrorKind::OperationNotSupported(v) => write!(f, "Operation not supported: '{}'", v),
+            ErrorKind::MessageTooLarge => write!(f, "Message too large"),
+            ErrorKind::SshAuthenticationFailed(v) => {
+                write!(f, "SSH authentication failed: '{}'", v)
+            }
+            ErrorKind::SshConnectionFailed(v) => write!(f, "SSH connection failed: '{}'", v),
             ErrorKind::Generic => Ok(()),
         }
     }
@@ -50,15 +70,57 @@ impl ::std::fmt::Display for ErrorKind {
 
 impl From<&std::io::Error> for ErrorKind {
     fn from(e: &io::Error) -> Self {
+        // an error of this crate passed through an `io::Read`
+        if let Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<Error>()) {
+            return e.kind().clone();
+        }
         match e.kind() {
-            io::ErrorKind::BrokenPipe
-            | io::ErrorKind::ConnectionAborted
-            | io::ErrorKind::ConnectionReset => ErrorKind::ConnectionClosed,
+            io::ErrorKind::ConnectionAborted | io::ErrorKind::ConnectionReset => {
+                ErrorKind::ConnectionClosed
+            }
+            io::ErrorKind::BrokenPipe => ErrorKind::BrokenPipe,
+            io::ErrorKind::ConnectionRefused => ErrorKind::ConnectionRefused,
             kind => ErrorKind::Io(kind),
         }
     }
 }
 
+impl ErrorKind {
+    /// The name of the varlink error, which was replied by the service.
+    ///
+    /// Returns `None` for errors, which are not varlink error replies.
+    pub fn error_name(&self) -> Option<&str> {
+        match self {
+            ErrorKind::InterfaceNotFound(_) => Some("org.varlink.service.InterfaceNotFound"),
+            ErrorKind::InvalidParameter(_) => Some("org.varlink.service.InvalidParameter"),
+            ErrorKind::MethodNotFound(_) => Some("org.varlink.service.MethodNotFound"),
+            ErrorKind::MethodNotImplemented(_) => Some("org.varlink.service.MethodNotImplemented"),
+            ErrorKind::VarlinkErrorReply(reply) => reply.error.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The parameters of the varlink error, which was replied by the service.
+    ///
+    /// Unlike [`error_name`](#method.error_name), this can't always borrow from `self`: the
+    /// standard `org.varlink.service` errors only keep the value of their single parameter,
+    /// so their parameters object is built on every call. The parameters of other error
+    /// replies are borrowed.
+    pub fn error_parameters(&self) -> Option<Cow<'_, serde_json::Value>> {
+        use serde_json::json;
+
+        match self {
+            ErrorKind::InterfaceNotFound(v) => Some(Cow::Owned(json!({ "interface": v }))),
+            ErrorKind::InvalidParameter(v) => Some(Cow::Owned(json!({ "parameter": v }))),
+            ErrorKind::MethodNotFound(v) | ErrorKind::MethodNotImplemented(v) => {
+                Some(Cow::Owned(json!({ "method": v })))
+            }
+            ErrorKind::VarlinkErrorReply(reply) => reply.parameters.as_ref().map(Cow::Borrowed),
+            _ => None,
+        }
+    }
+}
+
 impl From<&serde_json::error::Error> for ErrorKind {
     fn from(e: &serde_json::error::Error) -> Self {
         ErrorKind::SerdeJsonSer(e.classify())
@@ -75,6 +137,16 @@ impl Error {
     pub fn kind(&self) -> &ErrorKind {
         &self.0
     }
+
+    /// see [`ErrorKind::error_name`](enum.ErrorKind.html#method.error_name)
+    pub fn error_name(&self) -> Option<&str> {
+        self.0.error_name()
+    }
+
+    /// see [`ErrorKind::error_parameters`](enum.ErrorKind.html#method.error_parameters)
+    pub fn error_parameters(&self) -> Option<Cow<'_, serde_json::Value>> {
+        self.0.error_parameters()
+    }
 }
 
 impl From<ErrorKind> for Error {
@@ -148,4 +220,92 @@ mod tests {
     fn error_is_sync_send() {
         assert_impl_all!(crate::error::Error: Send, Sync);
     }
+
+    #[test]
+    fn io_error_kinds() {
+        use crate::ErrorKind;
+        use std::io;
+
+        let cases = vec![
+            (io::ErrorKind::BrokenPipe, ErrorKind::BrokenPipe),
+            (
+                io::ErrorKind::ConnectionRefused,
+                ErrorKind::ConnectionRefused,
+            ),
+            (io::ErrorKind::ConnectionReset, ErrorKind::ConnectionClosed),
+            (
+                io::ErrorKind::ConnectionAborted,
+                ErrorKind::ConnectionClosed,
+            ),
+            (
+                io::ErrorKind::PermissionDenied,
+                ErrorKind::Io(io::ErrorKind::PermissionDenied),
+            ),
+        ];
+
+        for (io_kind, kind) in cases {
+            assert_eq!(ErrorKind::from(&io::Error::from(io_kind)), kind);
+        }
+        assert_eq!(ErrorKind::BrokenPipe.to_string(), "Broken pipe");
+    }
+
+    #[test]
+    fn error_name_and_parameters() {
+        use crate::{ErrorKind, Reply};
+        use serde_json::json;
+
+        let cases = vec![
+            (
+                ErrorKind::InterfaceNotFound("org.example.foo".into()),
+                Some("org.varlink.service.InterfaceNotFound"),
+                Some(json!({ "interface": "org.example.foo" })),
+            ),
+            (
+                ErrorKind::InvalidParameter("name".into()),
+                Some("org.varlink.service.InvalidParameter"),
+                Some(json!({ "parameter": "name" })),
+            ),
+            (
+                ErrorKind::MethodNotFound("org.example.foo.Bar".into()),
+                Some("org.varlink.service.MethodNotFound"),
+                Some(json!({ "method": "org.example.foo.Bar" })),
+            ),
+            (
+                ErrorKind::MethodNotImplemented("org.example.foo.Bar".into()),
+                Some("org.varlink.service.MethodNotImplemented"),
+                Some(json!({ "method": "org.example.foo.Bar" })),
+            ),
+            (
+                ErrorKind::VarlinkErrorReply(Reply::error(
+                    "org.example.foo.NotFound",
+                    Some(json!({ "name": "bar" })),
+                )),
+                Some("org.example.foo.NotFound"),
+                Some(json!({ "name": "bar" })),
+            ),
+            (
+                ErrorKind::VarlinkErrorReply(Reply::error("org.example.foo.Failed", None)),
+                Some("org.example.foo.Failed"),
+                None,
+            ),
+            (ErrorKind::ConnectionClosed, None, None),
+            (ErrorKind::Timeout, None, None),
+        ];
+
+        for (kind, name, parameters) in cases {
+            let e = context!(kind);
+            assert_eq!(e.error_name(), name);
+            assert_eq!(e.error_parameters().as_deref(), parameters.as_ref());
+        }
+
+        // the parameters of a reply are not copied
+        let reply = ErrorKind::VarlinkErrorReply(Reply::error(
+            "org.example.foo.NotFound",
+            Some(json!({ "name": "bar" })),
+        ));
+        assert!(matches!(
+            reply.error_parameters(),
+            Some(std::borrow::Cow::Borrowed(_))
+        ));
+    }
 }
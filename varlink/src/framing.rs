@@ -0,0 +1,84 @@
+//! Length-prefixed framing for upgraded connections
+//!
+//! After a successful `upgrade` call, the connection carries an application specific
+//! protocol. For binary protocols, these helpers implement a simple framing: every frame
+//! is a 32 bit unsigned big endian length, followed by that many bytes of payload.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use std::io;
+//! # fn main() -> varlink::Result<()> {
+//! let mut wire = Vec::new();
+//! varlink::write_framed(&mut wire, b"\x00\x01binary\x00data")?;
+//!
+//! let mut buf = Vec::new();
+//! varlink::read_framed(&mut io::Cursor::new(wire), &mut buf)?;
+//! assert_eq!(buf, b"\x00\x01binary\x00data");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use crate::error::*;
+
+/// The largest payload accepted by [`read_framed`], 16 MiB.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Read one frame from `reader` and store its payload in `buf`.
+///
+/// `buf` is cleared before reading. Returns the length of the payload.
+/// If the connection is closed, an error of kind `ErrorKind::ConnectionClosed` is returned.
+/// Frames longer than [`MAX_FRAME_LEN`] are rejected, see [`read_framed_with_limit`].
+pub fn read_framed<R: Read + ?Sized>(reader: &mut R, buf: &mut Vec<u8>) -> Result<usize> {
+    read_framed_with_limit(reader, buf, MAX_FRAME_LEN)
+}
+
+/// Like [`read_framed`], but fails with `ErrorKind::MessageTooLarge` for frames with a payload
+/// longer than `max_len` bytes.
+///
+/// The payload of a rejected frame is not read, so the connection can't be used afterwards.
+pub fn read_framed_with_limit<R: Read + ?Sized>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<usize> {
+    let mut header = [0u8; 4];
+
+    reader.read_exact(&mut header).map_err(map_eof)?;
+
+    let len = u32::from_be_bytes(header) as usize;
+    if len > max_len {
+        return Err(context!(ErrorKind::MessageTooLarge));
+    }
+
+    buf.clear();
+    buf.resize(len, 0);
+    reader.read_exact(buf).map_err(map_eof)?;
+
+    Ok(len)
+}
+
+/// Write `data` as one frame to `writer` and flush it.
+pub fn write_framed<W: Write + ?Sized>(writer: &mut W, data: &[u8]) -> Result<()> {
+    let len = u32::try_from(data.len()).map_err(|_| {
+        let e = io::Error::new(io::ErrorKind::InvalidInput, "frame too large");
+        context!(e, ErrorKind::Io(io::ErrorKind::InvalidInput))
+    })?;
+
+    writer
+        .write_all(&len.to_be_bytes())
+        .map_err(map_context!())?;
+    writer.write_all(data).map_err(map_context!())?;
+    writer.flush().map_err(map_context!())?;
+    Ok(())
+}
+
+fn map_eof(e: io::Error) -> Error {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => context!(e, ErrorKind::ConnectionClosed),
+        _ => context!(e, ErrorKind::from(&e)),
+    }
+}
@@ -0,0 +1,98 @@
+//! Strictly validated varlink integers
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Unexpected, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// A varlink `int`, which only deserializes from integral JSON numbers in the range of `i64`
+///
+/// JSON numbers can also be fractions or exceed the range of `i64`. Deserializing those
+/// fails with an error naming the offending value. Integral numbers written with a
+/// fraction or exponent, like `1.0` or `1e3`, are accepted.
+///
+/// Use it for all `int` values of an interface with the generator option `int_type`.
+///
+/// # Examples
+///
+/// ```rust
+/// varlink_derive::varlink!(
+///     org_example_count,
+///     r#"
+/// interface org.example.count
+/// method Count(n: int) -> (count: int)
+/// "#,
+///     int_type = "varlink::VarlinkInt",
+/// );
+///
+/// let reply: org_example_count::Count_Reply =
+///     serde_json::from_value(serde_json::json!({ "count": 42 })).unwrap();
+/// assert_eq!(reply.count, varlink::VarlinkInt(42));
+///
+/// assert!(serde_json::from_value::<org_example_count::Count_Reply>(
+///     serde_json::json!({ "count": 3.5 })
+/// )
+/// .is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct VarlinkInt(pub i64);
+
+impl From<i64> for VarlinkInt {
+    fn from(v: i64) -> Self {
+        VarlinkInt(v)
+    }
+}
+
+impl From<VarlinkInt> for i64 {
+    fn from(v: VarlinkInt) -> Self {
+        v.0
+    }
+}
+
+impl fmt::Display for VarlinkInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for VarlinkInt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+struct VarlinkIntVisitor;
+
+impl<'de> Visitor<'de> for VarlinkIntVisitor {
+    type Value = VarlinkInt;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an integer in the range of i64")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(VarlinkInt(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        i64::try_from(v)
+            .map(VarlinkInt)
+            .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        // `i64::MAX as f64` rounds up to 2^63, which is out of range
+        if v.fract() == 0.0 && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+            Ok(VarlinkInt(v as i64))
+        } else {
+            Err(E::invalid_value(Unexpected::Float(v), &self))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VarlinkInt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_i64(VarlinkIntVisitor)
+    }
+}
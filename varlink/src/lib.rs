@@ -186,7 +186,8 @@
 //! Currently supported address URIs are:
 //!
 //! - TCP `tcp:127.0.0.1:12345` hostname/IP address and port
-//! - UNIX socket `unix:/run/org.example.ftl` optional access `;mode=0666` parameter
+//! - UNIX socket `unix:/run/org.example.ftl` optional access `;mode=0666` parameter and
+//!   ownership `;uid=0`, `;gid=10`, `;owner=root` or `;group=wheel` parameters
 //! - UNIX abstract namespace socket `unix:@org.example.ftl` (on Linux only)
 //!
 //! # Client
@@ -249,6 +250,7 @@ use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::process::Child;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use serde::de::{self, DeserializeOwned};
 use serde::ser::{Serialize, SerializeMap, Serializer};
@@ -256,23 +258,43 @@ use serde_derive::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tempfile::TempDir;
 
-pub use crate::client::varlink_connect;
-use crate::client::{varlink_bridge, varlink_exec};
-pub use crate::stream::Stream;
+pub use crate::client::{register_transport, varlink_connect, TransportFactory};
+use crate::client::{ssh_error, varlink_bridge, varlink_exec, varlink_ssh};
+pub use crate::framing::{read_framed, read_framed_with_limit, write_framed, MAX_FRAME_LEN};
+pub use crate::int::VarlinkInt;
+pub use crate::pool::{ConnectionPool, PooledConnection};
+pub use crate::stream::{PeerCredentials, Stream};
 pub type VarlinkStream = Box<dyn Stream>;
 pub type ServerStream = Box<dyn Stream>;
 
-pub use crate::server::{listen, ListenConfig, Listener};
+#[cfg(windows)]
+pub use crate::npipe::{NamedPipeListener, NamedPipeStream};
+pub use crate::server::{address_from_args_or_env, listen, ListenConfig, Listener};
+#[cfg(feature = "tls")]
+pub use crate::tls::{tls_client_config, tls_server_config, TlsStream};
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+pub use crate::vsock::{VsockListener, VsockStream};
+#[cfg(feature = "tls")]
+pub use rustls;
 
 #[macro_use]
 pub mod error;
 pub use error::{Error, ErrorKind, Result};
 
 mod client;
+mod framing;
+mod int;
+#[cfg(windows)]
+mod npipe;
+mod pool;
 mod server;
 mod stream;
 #[cfg(test)]
 mod test;
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+mod vsock;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
 pub struct ErrorInterfaceNotFound {
@@ -377,8 +399,10 @@ pub trait Interface {
 
 /// The structure of a varlink request. Used to serialize json into it.
 ///
-/// There should be no need to use this directly.
+/// There should be no need to use this directly. Requests are built with
+/// [`create`](#method.create), as more fields might be added.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
+#[non_exhaustive]
 pub struct Request<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub more: Option<bool>,
@@ -389,6 +413,10 @@ pub struct Request<'a> {
     pub method: Cow<'a, str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parameters: Option<Value>,
+    /// Fields not defined by the varlink protocol, e.g. `x-request-id`.
+    /// They are preserved, so that bridges and proxies can pass them on.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
 }
 
 impl<'a> Request<'a> {
@@ -399,6 +427,7 @@ impl<'a> Request<'a> {
             upgrade: None,
             method: method.into(),
             parameters,
+            extra: serde_json::Map::new(),
         }
     }
 }
@@ -416,6 +445,13 @@ impl StringHashSet {
             inner: HashSet::new(),
         }
     }
+
+    /// Return `true`, if the set contains no elements.
+    ///
+    /// Usable as `skip_serializing_if` function, in contrast to the method of `HashSet`.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
 }
 
 impl Deref for StringHashSet {
@@ -453,39 +489,112 @@ impl<'de> de::Deserialize<'de> for StringHashSet {
     where
         D: de::Deserializer<'de>,
     {
-        struct Visitor;
+        deserializer.deserialize_map(StringHashSetVisitor { strict: false })
+    }
+}
 
-        impl<'de> de::Visitor<'de> for Visitor {
-            type Value = StringHashSet;
+struct StringHashSetVisitor {
+    /// reject elements with other values than `{}` or `null`
+    strict: bool,
+}
 
-            fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                formatter.write_str("a map")
-            }
+impl<'de> de::Visitor<'de> for StringHashSetVisitor {
+    type Value = StringHashSet;
 
-            #[inline]
-            fn visit_unit<E>(self) -> ::std::result::Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                Ok(StringHashSet::new())
-            }
+    fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        formatter.write_str("a map")
+    }
 
-            #[inline]
-            fn visit_map<V>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error>
-            where
-                V: de::MapAccess<'de>,
-            {
-                let mut values = StringHashSet::new();
+    #[inline]
+    fn visit_unit<E>(self) -> ::std::result::Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(StringHashSet::new())
+    }
 
-                while let Some(key) = visitor.next_key()? {
-                    values.insert(key);
+    #[inline]
+    fn visit_map<V>(self, mut visitor: V) -> ::std::result::Result<Self::Value, V::Error>
+    where
+        V: de::MapAccess<'de>,
+    {
+        let mut values = StringHashSet::new();
+
+        while let Some(key) = visitor.next_key::<String>()? {
+            if self.strict {
+                match visitor.next_value::<Value>()? {
+                    Value::Null => {}
+                    Value::Object(ref m) if m.is_empty() => {}
+                    v => {
+                        return Err(de::Error::custom(format!(
+                            "invalid value `{}` of set element `{}`, expected an empty object",
+                            v, key
+                        )))
+                    }
                 }
-
-                Ok(values)
+            } else {
+                visitor.next_value::<de::IgnoredAny>()?;
             }
+            values.insert(key);
         }
 
-        deserializer.deserialize_map(Visitor)
+        Ok(values)
+    }
+}
+
+/// A [`StringHashSet`], which only accepts `{}` or `null` as values of its elements
+///
+/// The varlink specification encodes a set as a JSON object with an empty object for
+/// every element. `StringHashSet` ignores the values, this one rejects anything else.
+/// Use it with the generator option `set_type = "varlink::StrictStringHashSet"`.
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct StrictStringHashSet(pub StringHashSet);
+
+impl StrictStringHashSet {
+    pub fn new() -> StrictStringHashSet {
+        StrictStringHashSet(StringHashSet::new())
+    }
+
+    /// Return `true`, if the set contains no elements.
+    ///
+    /// Usable as `skip_serializing_if` function, in contrast to the method of `HashSet`.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Deref for StrictStringHashSet {
+    type Target = HashSet<String>;
+
+    fn deref(&self) -> &HashSet<String> {
+        &self.0
+    }
+}
+
+impl DerefMut for StrictStringHashSet {
+    fn deref_mut(&mut self) -> &mut HashSet<String> {
+        &mut self.0
+    }
+}
+
+impl Serialize for StrictStringHashSet {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for StrictStringHashSet {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_map(StringHashSetVisitor { strict: true })
+            .map(StrictStringHashSet)
     }
 }
 
@@ -572,6 +681,10 @@ pub struct Call<'a> {
     pub request: Option<&'a Request<'a>>,
     continues: bool,
     upgraded: bool,
+    peer_credentials: Option<PeerCredentials>,
+    /// the error replied to the client, for the `tracing` events
+    #[cfg(feature = "tracing")]
+    reply_error: Option<Cow<'static, str>>,
 }
 
 /// CallTrait provides convenience methods for the `Call` struct, which is passed as
@@ -678,6 +791,23 @@ pub trait CallTrait {
 
     fn get_request(&self) -> Option<&Request>;
 
+    /// The request id of the call, if the client sent one in the `x-request-id`
+    /// extension field of the request.
+    ///
+    /// varlink itself has no notion of a request id, but some deployments tunnel one
+    /// to correlate the logs of clients, bridges and services.
+    fn request_id(&self) -> Option<&str> {
+        self.get_request()?.extra.get("x-request-id")?.as_str()
+    }
+
+    /// The uid, gid and pid of the client, if the server knows them.
+    ///
+    /// [`listen`](fn.listen.html) provides them for unix socket connections on Linux.
+    /// A service can use them to reject unauthorized callers.
+    fn peer_credentials(&self) -> Option<&PeerCredentials> {
+        None
+    }
+
     /// reply with the standard varlink `org.varlink.service.MethodNotFound` error
     fn reply_method_not_found(&mut self, method_name: String) -> Result<()> {
         self.reply_struct(Reply::error(
@@ -716,6 +846,98 @@ pub trait CallTrait {
             ),
         ))
     }
+
+    /// reply with the error `name` and `params` as its parameters
+    ///
+    /// This is meant for services, which are not generated, to send the errors of
+    /// their interfaces.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[derive(serde_derive::Serialize)]
+    /// struct NotFound {
+    ///     name: String,
+    /// }
+    ///
+    /// fn not_found(call: &mut varlink::Call, name: &str) -> varlink::Result<()> {
+    ///     use varlink::CallTrait;
+    ///     call.reply_error(
+    ///         "org.example.NotFound",
+    ///         NotFound { name: name.into() },
+    ///     )
+    /// }
+    /// ```
+    fn reply_error<S: Into<Cow<'static, str>>, T: Serialize>(
+        &mut self,
+        name: S,
+        params: T,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let name = name.into();
+        self.reply_error_value(&name, serde_json::to_value(params).map_err(map_context!())?)
+    }
+
+    /// Like [`reply_error`](#method.reply_error), but with the parameters already converted
+    /// to JSON, so it can be called on the `&mut dyn Call_*` of generated interfaces.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// fn not_found(call: &mut dyn varlink::CallTrait, name: &str) -> varlink::Result<()> {
+    ///     call.reply_error_value("org.example.NotFound", serde_json::json!({ "name": name }))
+    /// }
+    /// ```
+    fn reply_error_value(&mut self, name: &str, params: Value) -> Result<()> {
+        self.reply_struct(Reply::error(name.to_string(), Some(params)))
+    }
+
+    /// Send all replies of `replies` to a `more` call.
+    ///
+    /// `continues` is set for every reply but the last one, which terminates the call.
+    /// If the iterator is empty, an empty terminating reply is sent.
+    /// Fails with `CallContinuesMismatch`, if the client did not ask for `more`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #![allow(non_camel_case_types)]
+    /// # #![allow(non_snake_case)]
+    /// # pub trait VarlinkCallError: varlink::CallTrait {}
+    /// # impl<'a> VarlinkCallError for varlink::Call<'a> {}
+    /// # pub trait Call_TestMethod: VarlinkCallError {}
+    /// # impl<'a> Call_TestMethod for varlink::Call<'a> {}
+    /// # struct TestService;
+    /// # impl TestService {
+    /// fn test_method(&self, call: &mut dyn Call_TestMethod) -> varlink::Result<()> {
+    ///     let mut replies = (0..10).map(|progress| {
+    ///         varlink::Reply::parameters(Some(serde_json::json!({ "progress": progress * 10 })))
+    ///     });
+    ///     call.reply_stream(&mut replies)
+    /// }
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn reply_stream(&mut self, replies: &mut dyn Iterator<Item = Reply>) -> Result<()> {
+        if !self.wants_more() {
+            return Err(context!(ErrorKind::CallContinuesMismatch));
+        }
+
+        let mut replies = replies.peekable();
+
+        if replies.peek().is_none() {
+            self.set_continues(false);
+            return self.reply_struct(Reply::parameters(None));
+        }
+
+        while let Some(reply) = replies.next() {
+            self.set_continues(replies.peek().is_some());
+            self.reply_struct(reply)?;
+        }
+        Ok(())
+    }
 }
 
 impl CallTrait for Call<'_> {
@@ -726,6 +948,10 @@ impl CallTrait for Call<'_> {
         if self.continues {
             reply.continues = Some(true);
         }
+        #[cfg(feature = "tracing")]
+        if reply.error.is_some() {
+            self.reply_error = reply.error.clone();
+        }
         // serde_json::to_writer(&mut *self.writer, &reply)?;
         let b = serde_json::to_string(&reply).map_err(map_context!())? + "\0";
 
@@ -769,6 +995,10 @@ impl CallTrait for Call<'_> {
     fn get_request(&self) -> Option<&Request> {
         self.request
     }
+
+    fn peer_credentials(&self) -> Option<&PeerCredentials> {
+        self.peer_credentials.as_ref()
+    }
 }
 
 impl<'a> Call<'a> {
@@ -778,6 +1008,9 @@ impl<'a> Call<'a> {
             request: Some(request),
             continues: false,
             upgraded: false,
+            peer_credentials: None,
+            #[cfg(feature = "tracing")]
+            reply_error: None,
         }
     }
     fn new_upgraded(writer: &'a mut dyn Write) -> Self {
@@ -786,9 +1019,18 @@ impl<'a> Call<'a> {
             request: None,
             continues: false,
             upgraded: true,
+            peer_credentials: None,
+            #[cfg(feature = "tracing")]
+            reply_error: None,
         }
     }
 
+    /// Set the credentials of the client, which are returned by
+    /// [`CallTrait::peer_credentials`](trait.CallTrait.html#method.peer_credentials).
+    pub fn set_peer_credentials(&mut self, peer_credentials: Option<PeerCredentials>) {
+        self.peer_credentials = peer_credentials;
+    }
+
     pub fn reply_interface_not_found(&mut self, arg: Option<String>) -> Result<()> {
         self.reply_struct(Reply::error(
             "org.varlink.service.InterfaceNotFound",
@@ -825,9 +1067,52 @@ pub struct Connection {
     pub stream: Option<Box<dyn stream::Stream>>,
     pub child: Option<Child>,
     pub tempdir: Option<TempDir>,
+    batch: Option<Vec<u8>>,
+    auto_reconnect: bool,
+    max_message_size: Option<usize>,
+    /// the timeout set with `set_timeout()`
+    timeout: Option<Duration>,
+    /// the interfaces of the service, see `interfaces()`
+    interfaces: Option<Vec<String>>,
+    /// `false` for bridges, `ssh` and file descriptors, which have no address to connect to
+    reconnectable: bool,
+    /// a call failed while reading its reply, so the next call reconnects with `auto_reconnect`
+    broken: bool,
+    /// replies of failed calls, which the next call has to read and drop before its own reply
+    skip_replies: usize,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<rustls::ClientConfig>>,
 }
 
 impl Connection {
+    fn from_parts(
+        reader: Option<BufReader<Box<dyn Read + Send + Sync>>>,
+        writer: Option<Box<dyn Write + Send + Sync>>,
+        address: String,
+        stream: Option<Box<dyn stream::Stream>>,
+        child: Option<Child>,
+        tempdir: Option<TempDir>,
+    ) -> Self {
+        Connection {
+            reader,
+            writer,
+            address,
+            stream,
+            child,
+            tempdir,
+            batch: None,
+            auto_reconnect: false,
+            max_message_size: None,
+            timeout: None,
+            interfaces: None,
+            reconnectable: true,
+            broken: false,
+            skip_replies: 0,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
     /// Create a connection with a varlink URI
     ///
     /// see [with_address](#method.with_address)
@@ -844,8 +1129,15 @@ impl Connection {
     /// Currently supported address URIs are:
     ///
     /// - TCP `tcp:127.0.0.1:12345` hostname/IP address and port
-    /// - UNIX socket `unix:/run/org.example.ftl`
+    /// - UNIX socket `unix:/run/org.example.ftl`, on Windows 10 and newer with `AF_UNIX`
     /// - UNIX abstract namespace socket `unix:@org.example.ftl` (on Linux only)
+    /// - vsock `vsock:2:1234` context id and port (on Linux with the `vsock` feature only)
+    /// - named pipe `npipe:\\.\pipe\org.example.ftl` (on Windows only)
+    ///
+    /// TLS addresses `tcp+tls:varlink.example.org:12345` need a client configuration,
+    /// see [with_tls](#method.with_tls).
+    ///
+    /// Other schemes can be added with [register_transport](fn.register_transport.html).
     ///
     /// # Examples
     ///
@@ -858,26 +1150,26 @@ impl Connection {
         let (mut stream, address) = varlink_connect(address)?;
         let (r, w) = stream.split()?;
         let bufreader = BufReader::new(r);
-        Ok(Arc::new(RwLock::new(Connection {
-            reader: Some(bufreader),
-            writer: Some(w),
+        Ok(Arc::new(RwLock::new(Connection::from_parts(
+            Some(bufreader),
+            Some(w),
             address,
-            stream: Some(stream),
-            child: None,
-            tempdir: None,
-        })))
+            Some(stream),
+            None,
+            None,
+        ))))
     }
 
     pub fn with_address_no_rw<S: ?Sized + AsRef<str>>(address: &S) -> Result<Arc<RwLock<Self>>> {
         let (stream, address) = varlink_connect(address)?;
-        Ok(Arc::new(RwLock::new(Connection {
-            reader: None,
-            writer: None,
+        Ok(Arc::new(RwLock::new(Connection::from_parts(
+            None,
+            None,
             address,
-            stream: Some(stream),
-            child: None,
-            tempdir: None,
-        })))
+            Some(stream),
+            None,
+            None,
+        ))))
     }
 
     /// Create a connection to a service, which is executed in the background.
@@ -898,27 +1190,27 @@ impl Connection {
         let (mut stream, address) = varlink_connect(&unix_address)?;
         let (reader, writer) = stream.split()?;
         let bufreader = BufReader::new(reader);
-        Ok(Arc::new(RwLock::new(Connection {
-            reader: Some(bufreader),
-            writer: Some(writer),
+        Ok(Arc::new(RwLock::new(Connection::from_parts(
+            Some(bufreader),
+            Some(writer),
             address,
-            stream: Some(stream),
-            child: Some(child),
-            tempdir: temp_dir,
-        })))
+            Some(stream),
+            Some(child),
+            temp_dir,
+        ))))
     }
 
     pub fn with_activate_no_rw<S: ?Sized + AsRef<str>>(command: &S) -> Result<Arc<RwLock<Self>>> {
         let (child, unix_address, temp_dir) = varlink_exec(command)?;
         let (stream, address) = varlink_connect(&unix_address)?;
-        Ok(Arc::new(RwLock::new(Connection {
-            reader: None,
-            writer: None,
+        Ok(Arc::new(RwLock::new(Connection::from_parts(
+            None,
+            None,
             address,
-            stream: Some(stream),
-            child: Some(child),
-            tempdir: temp_dir,
-        })))
+            Some(stream),
+            Some(child),
+            temp_dir,
+        ))))
     }
     /// Create a connection to a service via stdin/stdout of a specified command.
     ///
@@ -940,26 +1232,133 @@ impl Connection {
         let (child, mut stream) = varlink_bridge(command)?;
         let (r, w) = stream.split()?;
         let bufreader = BufReader::new(r);
-        Ok(Arc::new(RwLock::new(Connection {
-            reader: Some(bufreader),
-            writer: Some(w),
-            address: "bridge".into(),
-            stream: Some(stream),
-            child: Some(child),
-            tempdir: None,
-        })))
+        let mut connection = Connection::from_parts(
+            Some(bufreader),
+            Some(w),
+            "bridge".into(),
+            Some(stream),
+            Some(child),
+            None,
+        );
+        connection.reconnectable = false;
+        Ok(Arc::new(RwLock::new(connection)))
     }
 
     pub fn with_bridge_no_rw<S: ?Sized + AsRef<str>>(command: &S) -> Result<Arc<RwLock<Self>>> {
         let (child, stream) = varlink_bridge(command)?;
-        Ok(Arc::new(RwLock::new(Connection {
-            reader: None,
-            writer: None,
-            address: "bridge".into(),
-            stream: Some(stream),
-            child: Some(child),
-            tempdir: None,
-        })))
+        let mut connection =
+            Connection::from_parts(None, None, "bridge".into(), Some(stream), Some(child), None);
+        connection.reconnectable = false;
+        Ok(Arc::new(RwLock::new(connection)))
+    }
+
+    /// Create a connection to a service on another host via `ssh`.
+    ///
+    /// `ssh` is started for `user_host` and runs `varlink bridge --connect=ADDRESS` with
+    /// `remote_address` on the remote side. The connection is checked with a `GetInfo` call,
+    /// so failures of `ssh` itself are returned as `ErrorKind::SshAuthenticationFailed` or
+    /// `ErrorKind::SshConnectionFailed` containing the diagnostics of `ssh`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use varlink::Connection;
+    /// let connection = Connection::with_ssh("user@my.example.org", "unix:/run/org.example.ftl");
+    /// ```
+    pub fn with_ssh(user_host: &str, remote_address: &str) -> Result<Arc<RwLock<Self>>> {
+        let (child, mut stream, stderr) = varlink_ssh(user_host, remote_address)?;
+        let (r, w) = stream.split()?;
+        let bufreader = BufReader::new(r);
+        let mut connection = Connection::from_parts(
+            Some(bufreader),
+            Some(w),
+            "bridge".into(),
+            Some(stream),
+            Some(child),
+            None,
+        );
+        connection.reconnectable = false;
+        let connection = Arc::new(RwLock::new(connection));
+
+        if let Err(e) = OrgVarlinkServiceClient::new(connection.clone()).get_info() {
+            let mut conn = connection.write().unwrap();
+            return Err(match conn.child {
+                Some(ref mut child) => ssh_error(child, stderr, e),
+                None => e,
+            });
+        }
+
+        Ok(connection)
+    }
+
+    /// Create a connection from an already connected stream socket, e.g. one end of a
+    /// `socketpair` or a descriptor passed via `LISTEN_FDS`.
+    ///
+    /// The connection takes ownership of `fd` and closes it on drop. The `address` of the
+    /// connection is set to `fd:<fd>`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::os::unix::io::{FromRawFd, OwnedFd};
+    /// # use varlink::Connection;
+    /// // SAFETY: descriptor 3 was passed to this process and is not used elsewhere
+    /// let fd = unsafe { OwnedFd::from_raw_fd(3) };
+    /// let connection = Connection::with_fd(fd);
+    /// ```
+    #[cfg(unix)]
+    pub fn with_fd(fd: std::os::unix::io::OwnedFd) -> Result<Arc<RwLock<Self>>> {
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let address = format!("fd:{}", fd.as_raw_fd());
+        let mut stream: Box<dyn Stream> = Box::new(UnixStream::from(fd));
+        let (r, w) = stream.split()?;
+        let bufreader = BufReader::new(r);
+        let mut connection =
+            Connection::from_parts(Some(bufreader), Some(w), address, Some(stream), None, None);
+        connection.reconnectable = false;
+        Ok(Arc::new(RwLock::new(connection)))
+    }
+
+    /// Create a TLS connection to a `tcp+tls:HOST:PORT` address.
+    ///
+    /// The certificate of the service is verified with `config` and has to be valid for
+    /// `HOST`. [tls_client_config](fn.tls_client_config.html) builds a configuration trusting
+    /// a set of PEM encoded certificates. Only available with the `tls` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use varlink::Connection;
+    /// # fn main() -> varlink::Result<()> {
+    /// let config = varlink::tls_client_config(&std::fs::read("ca.pem").unwrap())?;
+    /// let connection = Connection::with_tls("tcp+tls:varlink.example.org:12345", config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn with_tls<S: ?Sized + AsRef<str>>(
+        address: &S,
+        config: Arc<rustls::ClientConfig>,
+    ) -> Result<Arc<RwLock<Self>>> {
+        let address = address.as_ref();
+        let addr = address
+            .strip_prefix("tcp+tls:")
+            .ok_or(context!(ErrorKind::InvalidAddress))?;
+        let mut stream: Box<dyn Stream> = Box::new(tls::connect(addr, config.clone())?);
+        let (r, w) = stream.split()?;
+        let bufreader = BufReader::new(r);
+        let mut connection = Connection::from_parts(
+            Some(bufreader),
+            Some(w),
+            address.into(),
+            Some(stream),
+            None,
+            None,
+        );
+        connection.tls = Some(config);
+        Ok(Arc::new(RwLock::new(connection)))
     }
 
     /// Return the `address` used by the connection.
@@ -969,6 +1368,285 @@ impl Connection {
     pub fn address(&self) -> String {
         self.address.clone()
     }
+
+    /// Set the read and write timeout of the connection, `None` blocks forever.
+    ///
+    /// A [`MethodCall`](struct.MethodCall.html), which does not get a reply in time, fails
+    /// with `ErrorKind::Timeout`. The call can't be continued afterwards. The next call drops
+    /// the late reply before reading its own, or connects again with
+    /// [set_auto_reconnect](#method.set_auto_reconnect).
+    ///
+    /// Fails with `ErrorKind::OperationNotSupported` for connections without a stream, and for
+    /// streams of a [registered transport](fn.register_transport.html), which don't implement
+    /// `Stream::set_timeout()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use varlink::Connection;
+    /// # fn main() -> varlink::Result<()> {
+    /// let connection = Connection::with_address("unix:/tmp/org.example.myservice")?;
+    /// connection
+    ///     .write()
+    ///     .unwrap()
+    ///     .set_timeout(Some(Duration::from_secs(5)))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        match self.stream {
+            Some(ref mut stream) => stream.set_timeout(timeout)?,
+            None => {
+                return Err(context!(ErrorKind::OperationNotSupported(
+                    "set_timeout".into()
+                )))
+            }
+        }
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    /// Connect again to the `address` of the connection.
+    ///
+    /// This recovers a connection to a service, which was restarted, e.g. by socket activation.
+    /// A method call in progress on the old connection is abandoned and a timeout set with
+    /// [set_timeout](#method.set_timeout) has to be set again.
+    ///
+    /// Fails with `ErrorKind::OperationNotSupported` for bridges, `ssh` connections and
+    /// connections created [with_fd](#method.with_fd), which can't be reconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use varlink::{Connection, ErrorKind, OrgVarlinkServiceClient, OrgVarlinkServiceInterface};
+    /// # fn main() -> varlink::Result<()> {
+    /// let connection = Connection::with_address("unix:/tmp/org.example.myservice")?;
+    /// let mut client = OrgVarlinkServiceClient::new(connection.clone());
+    /// let info = match client.get_info() {
+    ///     Err(e) if matches!(e.kind(), ErrorKind::ConnectionClosed | ErrorKind::BrokenPipe) => {
+    ///         connection.write().unwrap().reconnect()?;
+    ///         client.get_info()?
+    ///     }
+    ///     r => r?,
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reconnect(&mut self) -> Result<()> {
+        if !self.reconnectable {
+            return Err(context!(ErrorKind::OperationNotSupported(
+                "reconnect".into()
+            )));
+        }
+        #[cfg(feature = "tls")]
+        let (mut stream, address) = match (&self.tls, self.address.strip_prefix("tcp+tls:")) {
+            (Some(config), Some(addr)) => (
+                Box::new(tls::connect(addr, config.clone())?) as Box<dyn Stream>,
+                self.address.clone(),
+            ),
+            _ => varlink_connect(&self.address)?,
+        };
+        #[cfg(not(feature = "tls"))]
+        let (mut stream, address) = varlink_connect(&self.address)?;
+        let (r, w) = stream.split()?;
+        if let Some(ref mut stream) = self.stream {
+            let _r = stream.shutdown();
+        }
+        self.reader = Some(BufReader::new(r));
+        self.writer = Some(w);
+        self.stream = Some(stream);
+        self.address = address;
+        self.timeout = None;
+        self.interfaces = None;
+        self.broken = false;
+        self.skip_replies = 0;
+        Ok(())
+    }
+
+    /// Reconnect automatically, if the service closed the connection.
+    ///
+    /// A [`MethodCall`](struct.MethodCall.html), which fails with `ErrorKind::ConnectionClosed`
+    /// or `ErrorKind::BrokenPipe` before the first reply is received, calls [reconnect](#method.reconnect) and sends the
+    /// request once more. Only enable this, if calling the methods twice is harmless, because
+    /// the service might have received the request before closing the connection.
+    ///
+    /// After a call failed while reading its reply, e.g. with `ErrorKind::Timeout`, the next call
+    /// connects again, unless the connection can't [reconnect](#method.reconnect).
+    pub fn set_auto_reconnect(&mut self, auto_reconnect: bool) {
+        self.auto_reconnect = auto_reconnect;
+    }
+
+    /// Limit the size of the replies in bytes, including the terminating NUL byte.
+    ///
+    /// A [`MethodCall`](struct.MethodCall.html) receiving a longer reply fails with
+    /// `ErrorKind::MessageTooLarge`. The call can't be continued afterwards.
+    pub fn set_max_message_size(&mut self, max_message_size: Option<usize>) {
+        self.max_message_size = max_message_size;
+    }
+
+    /// Return `true`, if a method call is in progress on the connection.
+    ///
+    /// A call holds the reader and writer of the connection, until the last reply is received,
+    /// so another call would fail with `ErrorKind::ConnectionBusy`.
+    /// Connections created by the `*_no_rw` functions are always busy.
+    pub fn is_busy(&self) -> bool {
+        self.reader.is_none() || self.writer.is_none()
+    }
+
+    /// Check, if the service still answers on the connection.
+    ///
+    /// Calls `org.varlink.service.GetInfo` and returns `true`, if a reply arrives.
+    /// A busy connection is reported as alive without sending anything, so a call in progress
+    /// is not disturbed. The reply is awaited for the timeout set with
+    /// [set_timeout](#method.set_timeout), if any, and limited like the replies of other calls
+    /// by [set_max_message_size](#method.set_max_message_size).
+    ///
+    /// After `false` is returned, the connection has to be [reconnected](#method.reconnect).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use varlink::Connection;
+    /// # fn main() -> varlink::Result<()> {
+    /// let connection = Connection::with_address("unix:/tmp/org.example.myservice")?;
+    /// if !connection.write().unwrap().ping() {
+    ///     connection.write().unwrap().reconnect()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ping(&mut self) -> bool {
+        self.is_busy() || self.get_info().is_ok()
+    }
+
+    /// Call `org.varlink.service.GetInfo` like
+    /// [`OrgVarlinkServiceClient`](struct.OrgVarlinkServiceClient.html) on this connection
+    /// and remember the interfaces for [interfaces](#method.interfaces).
+    fn get_info(&mut self) -> Result<ServiceInfo> {
+        if self.is_busy() {
+            return Err(context!(ErrorKind::ConnectionBusy));
+        }
+        self.reconnect_if_broken()?;
+
+        // A `MethodCall` needs a shared connection, so lend the reader, the writer and the
+        // batch to one for the call. It has no stream, which it could shut down.
+        let mut lent = Connection::from_parts(
+            self.reader.take(),
+            self.writer.take(),
+            self.address.clone(),
+            None,
+            None,
+            None,
+        );
+        lent.batch = self.batch.take();
+        lent.max_message_size = self.max_message_size;
+        lent.skip_replies = self.skip_replies;
+        lent.reconnectable = false;
+        let lent = Arc::new(RwLock::new(lent));
+
+        let info = OrgVarlinkServiceClient::new(lent.clone()).get_info();
+
+        let mut lent = lent
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        self.reader = lent.reader.take();
+        self.writer = lent.writer.take();
+        self.batch = lent.batch.take();
+        self.broken = lent.broken;
+        self.skip_replies = lent.skip_replies;
+        let info = info?;
+        self.interfaces = Some(info.interfaces.iter().map(|i| i.to_string()).collect());
+        Ok(info)
+    }
+
+    /// Connect again with `auto_reconnect`, after a call failed while reading its reply,
+    /// see `MethodCall::recv()`. Otherwise the next call keeps using the connection.
+    fn reconnect_if_broken(&mut self) -> Result<()> {
+        if !self.broken {
+            return Ok(());
+        }
+        let timeout = self.timeout;
+        if self.auto_reconnect && self.reconnectable {
+            self.reconnect()?;
+        } else {
+            self.broken = false;
+        }
+        if timeout.is_some() {
+            self.set_timeout(timeout)?;
+        }
+        Ok(())
+    }
+
+    /// The interfaces of the service as returned by `org.varlink.service.GetInfo`.
+    ///
+    /// The service is only asked once, later calls return the same list until
+    /// [refresh](#method.refresh) or [reconnect](#method.reconnect) is called.
+    /// Fails with `ErrorKind::ConnectionBusy`, if a method call is in progress.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use varlink::Connection;
+    /// # fn main() -> varlink::Result<()> {
+    /// let connection = Connection::with_address("unix:/tmp/org.example.myservice")?;
+    /// let interfaces = connection.write().unwrap().interfaces()?;
+    /// if interfaces.iter().any(|i| i == "org.example.myservice") {
+    ///     // ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn interfaces(&mut self) -> Result<Vec<String>> {
+        if let Some(ref interfaces) = self.interfaces {
+            return Ok(interfaces.clone());
+        }
+        self.get_info()?;
+        Ok(self.interfaces.clone().unwrap_or_default())
+    }
+
+    /// Forget the interfaces of the service returned by [interfaces](#method.interfaces),
+    /// so the next call asks the service again.
+    pub fn refresh(&mut self) {
+        self.interfaces = None;
+    }
+
+    /// Start a batch of `oneway` calls.
+    ///
+    /// Until [end_batch](#method.end_batch) is called, the requests of `oneway` calls are
+    /// collected instead of being written to the connection one by one. A call expecting a
+    /// reply sends the collected requests first, to keep the order of the requests.
+    ///
+    /// If writing the collected requests fails, they are dropped instead of being sent again
+    /// after a reconnect, because the service might have received a part of them already.
+    pub fn begin_batch(&mut self) {
+        if self.batch.is_none() {
+            self.batch = Some(Vec::new());
+        }
+    }
+
+    /// Write all `oneway` calls collected since [begin_batch](#method.begin_batch) at once
+    /// and flush the connection.
+    ///
+    /// Fails with `ErrorKind::ConnectionBusy`, if a method call is in progress, which keeps
+    /// the batch. If writing fails, the batch is lost.
+    pub fn end_batch(&mut self) -> Result<()> {
+        if self.batch.as_ref().map_or(true, Vec::is_empty) {
+            self.batch = None;
+            return Ok(());
+        }
+        let w = match self.writer {
+            Some(ref mut w) => w,
+            None => return Err(context!(ErrorKind::ConnectionBusy)),
+        };
+
+        let batch = self.batch.take().unwrap();
+        if let Err(e) = write_request(w, &batch) {
+            self.broken = true;
+            return Err(batch_lost(e));
+        }
+        Ok(())
+    }
 }
 
 impl Drop for Connection {
@@ -990,6 +1668,29 @@ impl Drop for Connection {
     }
 }
 
+fn write_request(w: &mut Box<dyn Write + Send + Sync>, b: &[u8]) -> Result<()> {
+    w.write_all(b).map_err(map_context!())?;
+    w.flush().map_err(map_context!())?;
+    Ok(())
+}
+
+/// Write the `oneway` calls collected by `Connection::begin_batch()` followed by the request.
+fn write_batch_and_request(conn: &mut Connection, b: &[u8]) -> Result<()> {
+    let w = conn.writer.as_mut().unwrap();
+    if let Some(ref mut batch) = conn.batch {
+        w.write_all(batch).map_err(map_context!())?;
+        batch.clear();
+    }
+    write_request(w, b)
+}
+
+/// Wrap the error of a failed batch write, keeping its kind.
+fn batch_lost(e: Error) -> Error {
+    let kind = e.kind().clone();
+    let msg = format!("the oneway calls of the batch were lost: {:?}", e);
+    context!(msg, kind)
+}
+
 pub struct MethodCall<MRequest, MReply, MError>
 where
     MRequest: Serialize,
@@ -1002,6 +1703,15 @@ where
     reader: Option<BufReader<Box<dyn Read + Send + Sync>>>,
     writer: Option<Box<dyn Write + Send + Sync>>,
     continues: bool,
+    raw_reply: Option<Vec<u8>>,
+    keep_raw_reply: bool,
+    /// the request to send again after a reconnect, until the first reply is received
+    resend: Option<Vec<u8>>,
+    /// more replies or an upgraded protocol might follow the first reply
+    more: bool,
+    /// replies of earlier failed calls to drop before the first reply
+    skip_replies: usize,
+    max_message_size: Option<usize>,
     phantom_reply: PhantomData<MReply>,
     phantom_error: PhantomData<MError>,
 }
@@ -1040,6 +1750,12 @@ where
             continues: false,
             reader: None,
             writer: None,
+            raw_reply: None,
+            keep_raw_reply: false,
+            resend: None,
+            more: false,
+            skip_replies: 0,
+            max_message_size: None,
             phantom_reply: PhantomData,
             phantom_error: PhantomData,
         }
@@ -1066,10 +1782,12 @@ where
                 return Err(context!(ErrorKind::ConnectionBusy).into());
             }
 
+            conn.reconnect_if_broken()?;
+
+            self.max_message_size = conn.max_message_size;
+
             if oneway {
                 req.oneway = Some(true);
-            } else {
-                self.reader = conn.reader.take();
             }
 
             if more {
@@ -1080,21 +1798,51 @@ where
                 req.upgrade = Some(true);
             }
 
-            let mut w = conn.writer.take().unwrap();
-
             let b = serde_json::to_string(&req)
                 .map_err(map_context!())
                 .map_err(Error::from)?
                 + "\0";
 
-            w.write_all(b.as_bytes())
-                .map_err(map_context!())
-                .map_err(Error::from)?;
-            w.flush().map_err(map_context!()).map_err(Error::from)?;
-            if oneway {
-                conn.writer = Some(w);
-            } else {
-                self.writer = Some(w);
+            if let Some(ref mut batch) = conn.batch {
+                if oneway {
+                    batch.extend_from_slice(b.as_bytes());
+                    return Ok(());
+                }
+            }
+
+            // the reader and writer stay with the connection until the request is written
+            let pending_batch = conn.batch.as_ref().is_some_and(|b| !b.is_empty());
+            if let Err(e) = write_batch_and_request(&mut conn, b.as_bytes()) {
+                // the request might be written partially
+                conn.broken = true;
+                // a partially written batch can't be sent again without duplicating calls
+                if pending_batch {
+                    if let Some(ref mut batch) = conn.batch {
+                        batch.clear();
+                    }
+                    return Err(batch_lost(e).into());
+                }
+                let closed = matches!(
+                    e.kind(),
+                    ErrorKind::ConnectionClosed | ErrorKind::BrokenPipe
+                );
+                if !(conn.auto_reconnect && closed) {
+                    return Err(e.into());
+                }
+                conn.reconnect()?;
+                if let Err(e) = write_request(conn.writer.as_mut().unwrap(), b.as_bytes()) {
+                    conn.broken = true;
+                    return Err(e.into());
+                }
+            }
+            if conn.auto_reconnect && !oneway {
+                self.resend = Some(b.into_bytes());
+            }
+            if !oneway {
+                self.more = more || upgrade;
+                self.skip_replies = std::mem::take(&mut conn.skip_replies);
+                self.reader = conn.reader.take();
+                self.writer = conn.writer.take();
             }
         }
         Ok(())
@@ -1105,6 +1853,46 @@ where
         self.recv()
     }
 
+    /// Like [call](#method.call), but fails with `ErrorKind::Timeout`, if the service does not
+    /// reply within `timeout`.
+    ///
+    /// The timeout of the connection is only changed for this call, see
+    /// [`Connection::set_timeout`](struct.Connection.html#method.set_timeout). The connection
+    /// can be used for further calls, whether this call succeeds or not.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use varlink::{Connection, MethodCall};
+    /// # fn main() -> varlink::Result<()> {
+    /// let connection = Connection::with_address("unix:/tmp/org.example.myservice")?;
+    /// let reply = MethodCall::<_, serde_json::Value, varlink::Error>::new(
+    ///     connection,
+    ///     "org.example.myservice.Get",
+    ///     serde_json::json!({}),
+    /// )
+    /// .call_with_timeout(Duration::from_secs(1))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_with_timeout(&mut self, timeout: Duration) -> std::result::Result<MReply, MError> {
+        let previous = {
+            let mut conn = self.connection.write().unwrap();
+            let previous = conn.timeout;
+            conn.set_timeout(Some(timeout))?;
+            previous
+        };
+        let reply = self.call();
+        let mut conn = self.connection.write().unwrap();
+        if conn.set_timeout(previous).is_err() {
+            // don't hide the reply, but connect again with the previous timeout on the next call
+            conn.timeout = previous;
+            conn.broken = true;
+        }
+        reply
+    }
+
     pub fn upgrade(&mut self) -> std::result::Result<MReply, MError> {
         self.send(false, false, true)?;
         self.recv()
@@ -1120,26 +1908,171 @@ where
         Ok(self)
     }
 
+    /// Like [more](#method.more), but returns an iterator owning the call, see
+    /// [into_replies](#method.into_replies).
+    ///
+    /// An error sending the request is the only item of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use varlink::{Connection, MethodCall};
+    /// # fn main() -> varlink::Result<()> {
+    /// let connection = Connection::with_address("unix:/tmp/org.example.myservice")?;
+    /// let replies = MethodCall::<_, serde_json::Value, varlink::Error>::new(
+    ///     connection,
+    ///     "org.example.myservice.Monitor",
+    ///     serde_json::json!({}),
+    /// )
+    /// .more_owned()
+    /// .collect::<varlink::Result<Vec<_>>>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn more_owned(mut self) -> impl Iterator<Item = std::result::Result<MReply, MError>> {
+        let error = match self.more() {
+            Ok(_) => None,
+            Err(e) => {
+                self.continues = false;
+                Some(Err(e))
+            }
+        };
+        error.into_iter().chain(self.into_replies())
+    }
+
+    /// Turn the call into an iterator over its remaining replies.
+    ///
+    /// The iterator ends after the last reply of a `more` call, when the reader and writer
+    /// are returned to the connection, or after the first error.
+    pub fn into_replies(mut self) -> impl Iterator<Item = std::result::Result<MReply, MError>> {
+        std::iter::from_fn(move || {
+            let reply = self.next()?;
+            // unlike `next()`, don't try to read again after e.g. a closed connection
+            if reply.is_err() {
+                self.continues = false;
+            }
+            Some(reply)
+        })
+    }
+
+    /// Read and parse the next reply up to the trailing NUL byte.
+    ///
+    /// The reply is parsed while it is read, so only the raw reply requested with
+    /// [`set_keep_raw_reply`](#method.set_keep_raw_reply) is buffered.
+    fn read_reply(&mut self) -> Result<Reply> {
+        fn read_error(e: std::io::Error) -> Error {
+            match e.kind() {
+                // a read timeout set with `Connection::set_timeout()`
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => {
+                    context!(e, ErrorKind::Timeout)
+                }
+                _ => context!(e, ErrorKind::from(&e)),
+            }
+        }
+
+        let reader = self.reader.as_mut().unwrap();
+        while self.skip_replies > 0 {
+            let buf = reader.fill_buf().map_err(read_error)?;
+            if buf.is_empty() {
+                return Err(context!(ErrorKind::ConnectionClosed));
+            }
+            match buf.iter().position(|b| *b == 0) {
+                Some(i) => {
+                    reader.consume(i + 1);
+                    self.skip_replies -= 1;
+                }
+                None => {
+                    let n = buf.len();
+                    reader.consume(n);
+                }
+            }
+        }
+        if reader.fill_buf().map_err(read_error)?.is_empty() {
+            return Err(context!(ErrorKind::ConnectionClosed));
+        }
+
+        let mut message = stream::MessageReader::new(
+            reader,
+            self.max_message_size.unwrap_or(usize::MAX),
+            self.keep_raw_reply,
+        );
+        let mut de = serde_json::Deserializer::from_reader(&mut message);
+        let reply = <Reply as de::Deserialize>::deserialize(&mut de)
+            .and_then(|reply| de.end().map(|_| reply));
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(e) if e.is_io() => return Err(read_error(e.into())),
+            Err(e) => {
+                message.finish().map_err(read_error)?;
+                let snippet = json_snippet(message.head());
+                return Err(context!(e, ErrorKind::SerdeJsonDe(snippet)));
+            }
+        };
+        self.raw_reply = message.into_raw();
+        Ok(reply)
+    }
+
+    /// Reconnect and send the request once more, after the service closed the connection.
+    fn send_again(&mut self) -> Result<()> {
+        let b = self.resend.take().unwrap();
+        let mut conn = self.connection.write().unwrap();
+        conn.reconnect()?;
+        self.skip_replies = 0;
+        let mut w = conn.writer.take().unwrap();
+        self.reader = conn.reader.take();
+        write_request(&mut w, &b)?;
+        self.writer = Some(w);
+        Ok(())
+    }
+
+    /// Keep the replies as sent by the service for [`raw_reply`](#method.raw_reply).
+    ///
+    /// The replies are parsed while they are read and not buffered by default.
+    pub fn set_keep_raw_reply(&mut self, keep: bool) {
+        self.keep_raw_reply = keep;
+    }
+
+    /// The last received reply as sent by the service, without the trailing NUL byte.
+    ///
+    /// Returns `None` unless enabled with [`set_keep_raw_reply`](#method.set_keep_raw_reply).
+    pub fn raw_reply(&self) -> Option<&[u8]> {
+        self.raw_reply.as_deref()
+    }
+
     pub fn recv(&mut self) -> std::result::Result<MReply, MError> {
+        self.raw_reply = None;
         if self.reader.is_none() || self.writer.is_none() {
             return Err(context!(ErrorKind::IteratorOldReply).into());
         }
 
-        let mut buf = Vec::new();
-
-        let mut reader = self.reader.take().unwrap();
-        reader
-            .read_until(0, &mut buf)
-            .map_err(map_context!())
-            .map_err(Error::from)?;
-        self.reader = Some(reader);
-        if buf.is_empty() {
-            return Err(context!(ErrorKind::ConnectionClosed).into());
-        }
-        buf.pop();
-        let reply: Reply = serde_json::from_slice(&buf)
-            .map_err(map_context!())
-            .map_err(Error::from)?;
+        let reply = match self.read_reply() {
+            Err(e) if *e.kind() == ErrorKind::ConnectionClosed && self.resend.is_some() => {
+                self.send_again().and_then(|_| self.read_reply())
+            }
+            r => r,
+        };
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.continues = false;
+                self.resend = None;
+                let mut conn = self.connection.write().unwrap();
+                conn.reader = self.reader.take();
+                conn.writer = self.writer.take();
+                match e.kind() {
+                    // the reply was read up to its NUL byte
+                    ErrorKind::SerdeJsonDe(_) if !self.more => {}
+                    // the rest of the reply might still arrive, drop it with the next call
+                    ErrorKind::Timeout | ErrorKind::MessageTooLarge if !self.more => {
+                        conn.skip_replies = self.skip_replies + 1;
+                        conn.broken = true;
+                    }
+                    _ => conn.broken = true,
+                }
+                return Err(e.into());
+            }
+        };
+        self.resend = None;
         match reply.continues {
             Some(true) => self.continues = true,
             _ => {
@@ -1158,9 +2091,12 @@ where
                 parameters: Some(p),
                 ..
             } => {
-                let mreply: MReply = serde_json::from_value(p)
-                    .map_err(map_context!())
-                    .map_err(Error::from)?;
+                let mreply = MReply::deserialize(&p).map_err(|e| {
+                    context!(
+                        e,
+                        ErrorKind::SerdeJsonDe(json_snippet(p.to_string().as_bytes()))
+                    )
+                })?;
                 Ok(mreply)
             }
             Reply {
@@ -1176,6 +2112,17 @@ where
     }
 }
 
+/// The start of the JSON message `buf` for error messages
+fn json_snippet(buf: &[u8]) -> String {
+    const MAX_LEN: usize = 256;
+
+    let s = String::from_utf8_lossy(buf);
+    match s.char_indices().nth(MAX_LEN) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.into_owned(),
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
 pub struct GetInterfaceDescriptionArgs<'a> {
     pub interface: Cow<'a, str>,
@@ -1211,12 +2158,31 @@ impl OrgVarlinkServiceClient {
     }
 }
 
+/// Client side of the `org.varlink.service` interface.
+///
+/// Every method is a separate call on the connection, so the methods can be called
+/// any number of times on the same client.
 pub trait OrgVarlinkServiceInterface {
     fn get_info(&mut self) -> Result<ServiceInfo>;
     fn get_interface_description<S: Into<Cow<'static, str>>>(
         &mut self,
         interface: S,
     ) -> Result<GetInterfaceDescriptionReply>;
+
+    /// Get the descriptions of all interfaces of the service, keyed by interface name.
+    fn get_all_interface_descriptions(&mut self) -> Result<HashMap<String, String>> {
+        let info = self.get_info()?;
+        let mut descriptions = HashMap::new();
+        for interface in info.interfaces {
+            if let Some(description) = self
+                .get_interface_description(interface.clone())?
+                .description
+            {
+                descriptions.insert(interface.into_owned(), description);
+            }
+        }
+        Ok(descriptions)
+    }
 }
 
 impl OrgVarlinkServiceInterface for OrgVarlinkServiceClient {
@@ -1395,6 +2361,51 @@ impl VarlinkService {
         }
     }
 
+    /// Add an interface to the service, e.g. for a plugin loaded at runtime.
+    ///
+    /// The interface is reported by `GetInfo` from now on. An interface with the same name
+    /// is replaced and returned.
+    pub fn add_interface(
+        &mut self,
+        interface: Box<dyn Interface + Send + Sync>,
+    ) -> Option<Box<dyn Interface + Send + Sync>> {
+        let name: Cow<'static, str> = interface.get_name().into();
+        let old = self.ifaces.insert(name.clone(), interface);
+        if old.is_none() {
+            self.info.interfaces.push(name);
+        }
+        old
+    }
+
+    /// Remove the interface `name` from the service and return it.
+    ///
+    /// Calls of its methods are answered with `org.varlink.service.InterfaceNotFound` afterwards.
+    pub fn remove_interface(&mut self, name: &str) -> Option<Box<dyn Interface + Send + Sync>> {
+        let old = self.ifaces.remove(name)?;
+        self.info.interfaces.retain(|i| i != name);
+        Some(old)
+    }
+
+    /// Return the names of the methods of every interface of the service.
+    ///
+    /// The method names are taken from the parsed description of the interface, in the order
+    /// of their definition. Interfaces with a description, which can't be parsed, map to an
+    /// empty list. Only available with the `introspection` feature.
+    #[cfg(feature = "introspection")]
+    pub fn method_map(&self) -> HashMap<String, Vec<String>> {
+        use std::convert::TryFrom;
+
+        std::iter::once(self as &dyn Interface)
+            .chain(self.ifaces.values().map(|i| i.as_ref() as &dyn Interface))
+            .map(|i| {
+                let methods = varlink_parser::IDL::try_from(i.get_description())
+                    .map(|idl| idl.method_keys.iter().map(|m| m.to_string()).collect())
+                    .unwrap_or_default();
+                (i.get_name().to_string(), methods)
+            })
+            .collect()
+    }
+
     fn call(&self, iface: &str, call: &mut Call) -> Result<()> {
         match iface {
             "org.varlink.service" => self::Interface::call(self, call),
@@ -1435,6 +2446,31 @@ pub trait ConnectionHandler {
         writer: &mut dyn Write,
         upgraded_iface: Option<String>,
     ) -> Result<(Vec<u8>, Option<String>)>;
+
+    /// Like `handle()`, but passes the credentials of the client on to the calls.
+    ///
+    /// The default implementation ignores `peer_credentials`.
+    fn handle_with_credentials(
+        &self,
+        bufreader: &mut dyn BufRead,
+        writer: &mut dyn Write,
+        upgraded_iface: Option<String>,
+        _peer_credentials: Option<PeerCredentials>,
+    ) -> Result<(Vec<u8>, Option<String>)> {
+        self.handle(bufreader, writer, upgraded_iface)
+    }
+}
+
+#[cfg(feature = "tracing")]
+fn trace_call_end(call: &Call, ret: &Result<()>, start: std::time::Instant) {
+    let duration_us = start.elapsed().as_micros() as u64;
+    match (ret, &call.reply_error) {
+        (Err(e), _) => tracing::warn!(duration_us, error = %e, "varlink call failed"),
+        (Ok(()), Some(error)) => {
+            tracing::info!(duration_us, error = %error, "varlink call replied with an error")
+        }
+        (Ok(()), None) => tracing::info!(duration_us, "varlink call finished"),
+    }
 }
 
 impl ConnectionHandler for VarlinkService {
@@ -1474,12 +2510,23 @@ impl ConnectionHandler for VarlinkService {
         bufreader: &mut dyn BufRead,
         writer: &mut dyn Write,
         upgraded_last_interface: Option<String>,
+    ) -> Result<(Vec<u8>, Option<String>)> {
+        self.handle_with_credentials(bufreader, writer, upgraded_last_interface, None)
+    }
+
+    fn handle_with_credentials(
+        &self,
+        bufreader: &mut dyn BufRead,
+        writer: &mut dyn Write,
+        upgraded_last_interface: Option<String>,
+        peer_credentials: Option<PeerCredentials>,
     ) -> Result<(Vec<u8>, Option<String>)> {
         let mut bufreader = BufReader::new(bufreader);
         let mut upgraded_iface = upgraded_last_interface;
         loop {
             if let Some(iface) = upgraded_iface {
                 let mut call = Call::new_upgraded(writer);
+                call.set_peer_credentials(peer_credentials);
                 let unread = self.call_upgraded(&iface, &mut call, &mut bufreader)?;
                 return Ok((unread, Some(iface)));
             }
@@ -1522,7 +2569,28 @@ impl ConnectionHandler for VarlinkService {
             let iface = String::from(&req.method[..n]);
 
             let mut call = Call::new(writer, &req);
-            self.call(&iface, &mut call)?;
+            call.set_peer_credentials(peer_credentials);
+
+            #[cfg(feature = "tracing")]
+            let span = tracing::info_span!(
+                "varlink_call",
+                interface = %iface,
+                method = %req.method,
+                more = req.more.unwrap_or(false),
+                oneway = req.oneway.unwrap_or(false),
+                request_id = call.request_id(),
+            );
+            #[cfg(feature = "tracing")]
+            let _enter = span.enter();
+            #[cfg(feature = "tracing")]
+            let start = std::time::Instant::now();
+
+            let ret = self.call(&iface, &mut call);
+
+            #[cfg(feature = "tracing")]
+            trace_call_end(&call, &ret, start);
+
+            ret?;
 
             if call.upgraded {
                 upgraded_iface = Some(iface);
@@ -0,0 +1,359 @@
+//! Windows named pipes for `npipe:\\.\pipe\NAME` addresses
+//!
+//! Named pipes take the role of unix sockets for local services on Windows. The pipes are
+//! opened for overlapped I/O, so a read waiting for data on one half of a split stream
+//! doesn't block a write on the other half.
+
+use std::ffi::OsStr;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle, RawHandle};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
+use winapi::shared::winerror::{
+    ERROR_BROKEN_PIPE, ERROR_IO_PENDING, ERROR_OPERATION_ABORTED, ERROR_PIPE_BUSY,
+    ERROR_PIPE_CONNECTED, ERROR_PIPE_NOT_CONNECTED, WAIT_TIMEOUT,
+};
+use winapi::um::fileapi::{CreateFileW, ReadFile, WriteFile, OPEN_EXISTING};
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+use winapi::um::ioapiset::{CancelIoEx, GetOverlappedResult};
+use winapi::um::minwinbase::OVERLAPPED;
+use winapi::um::namedpipeapi::{
+    ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, WaitNamedPipeW,
+};
+use winapi::um::synchapi::{CreateEventW, WaitForSingleObject};
+use winapi::um::winbase::{
+    FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, INFINITE, PIPE_ACCESS_DUPLEX,
+    PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES,
+    PIPE_WAIT, SECURITY_IDENTIFICATION, SECURITY_SQOS_PRESENT,
+};
+use winapi::um::winnt::{GENERIC_READ, GENERIC_WRITE, HANDLE};
+
+use crate::error::*;
+use crate::stream::Stream;
+
+const BUFFER_SIZE: DWORD = 64 * 1024;
+
+/// How long to wait for a free pipe instance of a busy service, in milliseconds
+const BUSY_TIMEOUT: DWORD = 5000;
+
+fn wide(name: &str) -> Vec<u16> {
+    OsStr::new(name).encode_wide().chain(Some(0)).collect()
+}
+
+fn cvt(ret: BOOL) -> io::Result<()> {
+    if ret == FALSE {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn is_error(e: &io::Error, code: DWORD) -> bool {
+    e.raw_os_error() == Some(code as i32)
+}
+
+/// The event to wait for the overlapped operations of one thread
+#[derive(Debug)]
+struct Event(OwnedHandle);
+
+impl Event {
+    fn new() -> io::Result<Self> {
+        let event = unsafe { CreateEventW(ptr::null_mut(), TRUE, FALSE, ptr::null()) };
+        if event.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Event(unsafe {
+            OwnedHandle::from_raw_handle(event as RawHandle)
+        }))
+    }
+
+    /// Start the overlapped operation `op` on `handle` and wait for it at most `timeout`
+    /// milliseconds.
+    fn wait<F>(&self, handle: HANDLE, timeout: DWORD, op: F) -> io::Result<usize>
+    where
+        F: FnOnce(*mut OVERLAPPED) -> BOOL,
+    {
+        let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+        overlapped.hEvent = self.0.as_raw_handle() as HANDLE;
+        let mut timed_out = false;
+        if let Err(e) = cvt(op(&mut overlapped)) {
+            if !is_error(&e, ERROR_IO_PENDING) {
+                return Err(e);
+            }
+            if unsafe { WaitForSingleObject(overlapped.hEvent, timeout) } == WAIT_TIMEOUT {
+                // the operation might complete anyway, which `GetOverlappedResult` tells
+                unsafe { CancelIoEx(handle, &mut overlapped) };
+                timed_out = true;
+            }
+        }
+        let mut n: DWORD = 0;
+        match cvt(unsafe { GetOverlappedResult(handle, &mut overlapped, &mut n, TRUE) }) {
+            Ok(()) => Ok(n as usize),
+            Err(e) if timed_out && is_error(&e, ERROR_OPERATION_ABORTED) => {
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The pipe shared by the clones of a [`NamedPipeStream`]
+#[derive(Debug)]
+struct Pipe {
+    handle: OwnedHandle,
+    /// the read and write timeout in milliseconds, `INFINITE` blocks forever
+    timeout: AtomicU32,
+    shut_down: AtomicBool,
+    /// the service side of the pipe, which can disconnect the client
+    server: bool,
+}
+
+impl Pipe {
+    fn handle(&self) -> HANDLE {
+        self.handle.as_raw_handle() as HANDLE
+    }
+}
+
+/// A connected named pipe
+#[derive(Debug)]
+pub struct NamedPipeStream {
+    pipe: Arc<Pipe>,
+    event: Event,
+}
+
+impl NamedPipeStream {
+    /// Connect to the pipe `name`, e.g. `\\.\pipe\org.example.ftl`.
+    pub fn connect(name: &str) -> io::Result<Self> {
+        let name = wide(name);
+        loop {
+            let handle = unsafe {
+                CreateFileW(
+                    name.as_ptr(),
+                    GENERIC_READ | GENERIC_WRITE,
+                    0,
+                    ptr::null_mut(),
+                    OPEN_EXISTING,
+                    // the service may not impersonate the client
+                    FILE_FLAG_OVERLAPPED | SECURITY_SQOS_PRESENT | SECURITY_IDENTIFICATION,
+                    ptr::null_mut(),
+                )
+            };
+            if handle != INVALID_HANDLE_VALUE {
+                let handle = unsafe { OwnedHandle::from_raw_handle(handle as RawHandle) };
+                return NamedPipeStream::new(handle, false);
+            }
+            // all instances are connected, wait for the service to create the next one
+            let e = io::Error::last_os_error();
+            if !is_error(&e, ERROR_PIPE_BUSY) {
+                return Err(e);
+            }
+            cvt(unsafe { WaitNamedPipeW(name.as_ptr(), BUSY_TIMEOUT) })?;
+        }
+    }
+
+    fn new(handle: OwnedHandle, server: bool) -> io::Result<Self> {
+        Ok(NamedPipeStream {
+            pipe: Arc::new(Pipe {
+                handle,
+                timeout: AtomicU32::new(INFINITE),
+                shut_down: AtomicBool::new(false),
+                server,
+            }),
+            event: Event::new()?,
+        })
+    }
+
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(NamedPipeStream {
+            pipe: self.pipe.clone(),
+            event: Event::new()?,
+        })
+    }
+
+    fn wait<F>(&self, op: F) -> io::Result<usize>
+    where
+        F: FnOnce(HANDLE, *mut OVERLAPPED) -> BOOL,
+    {
+        let handle = self.pipe.handle();
+        let timeout = self.pipe.timeout.load(Ordering::SeqCst);
+        self.event
+            .wait(handle, timeout, |overlapped| op(handle, overlapped))
+    }
+
+    fn is_shut_down(&self) -> bool {
+        self.pipe.shut_down.load(Ordering::SeqCst)
+    }
+}
+
+impl Read for NamedPipeStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.is_shut_down() {
+            return Ok(0);
+        }
+        let len = buf.len().min(DWORD::MAX as usize) as DWORD;
+        let ret = self.wait(|handle, overlapped| unsafe {
+            ReadFile(
+                handle,
+                buf.as_mut_ptr() as _,
+                len,
+                ptr::null_mut(),
+                overlapped,
+            )
+        });
+        match ret {
+            // the other side closed the pipe
+            Err(ref e)
+                if is_error(e, ERROR_BROKEN_PIPE) || is_error(e, ERROR_PIPE_NOT_CONNECTED) =>
+            {
+                Ok(0)
+            }
+            // `shutdown()` cancelled the read
+            Err(_) if self.is_shut_down() => Ok(0),
+            ret => ret,
+        }
+    }
+}
+
+impl Write for NamedPipeStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.is_shut_down() {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        let len = buf.len().min(DWORD::MAX as usize) as DWORD;
+        self.wait(|handle, overlapped| unsafe {
+            WriteFile(handle, buf.as_ptr() as _, len, ptr::null_mut(), overlapped)
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsRawHandle for NamedPipeStream {
+    fn as_raw_handle(&self) -> RawHandle {
+        self.pipe.handle.as_raw_handle()
+    }
+}
+
+impl Stream for NamedPipeStream {
+    #[inline]
+    fn split(&mut self) -> Result<(Box<dyn Read + Send + Sync>, Box<dyn Write + Send + Sync>)> {
+        Ok((
+            Box::new(NamedPipeStream::try_clone(self).map_err(map_context!())?),
+            Box::new(NamedPipeStream::try_clone(self).map_err(map_context!())?),
+        ))
+    }
+
+    #[inline]
+    fn shutdown(&mut self) -> Result<()> {
+        self.pipe.shut_down.store(true, Ordering::SeqCst);
+        // wake up the reads and writes of the other clones
+        unsafe { CancelIoEx(self.pipe.handle(), ptr::null_mut()) };
+        if self.pipe.server {
+            cvt(unsafe { DisconnectNamedPipe(self.pipe.handle()) }).map_err(map_context!())?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn try_clone(&mut self) -> ::std::io::Result<Box<dyn Stream>> {
+        Ok(Box::new(NamedPipeStream::try_clone(self)?))
+    }
+
+    #[inline]
+    fn set_nonblocking(&mut self, b: bool) -> Result<()> {
+        if b {
+            return Err(context!(ErrorKind::OperationNotSupported(
+                "set_nonblocking".into()
+            )));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        let timeout = match timeout {
+            // like `std::net::TcpStream`
+            Some(d) if d.is_zero() => {
+                return Err(context!(ErrorKind::Io(io::ErrorKind::InvalidInput)));
+            }
+            // round up below a millisecond
+            Some(d) => d.as_millis().clamp(1, (INFINITE - 1) as u128) as DWORD,
+            None => INFINITE,
+        };
+        self.pipe.timeout.store(timeout, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// A named pipe waiting for clients
+#[derive(Debug)]
+pub struct NamedPipeListener {
+    name: Vec<u16>,
+    /// the pipe instance the next client connects to
+    next: Mutex<(OwnedHandle, Event)>,
+}
+
+impl NamedPipeListener {
+    /// Create the pipe `name`, e.g. `\\.\pipe\org.example.ftl`.
+    ///
+    /// Fails, if another process already created the pipe.
+    pub fn bind(name: &str) -> io::Result<Self> {
+        let name = wide(name);
+        let instance = Self::instance(&name, true)?;
+        Ok(NamedPipeListener {
+            name,
+            next: Mutex::new((instance, Event::new()?)),
+        })
+    }
+
+    fn instance(name: &[u16], first: bool) -> io::Result<OwnedHandle> {
+        let mut mode = PIPE_ACCESS_DUPLEX | FILE_FLAG_OVERLAPPED;
+        if first {
+            mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+        }
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                BUFFER_SIZE,
+                BUFFER_SIZE,
+                0,
+                ptr::null_mut(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe { OwnedHandle::from_raw_handle(handle as RawHandle) })
+    }
+
+    /// Wait for the next client, fails with `io::ErrorKind::TimedOut` after `timeout`.
+    pub fn accept(&self, timeout: Option<Duration>) -> io::Result<NamedPipeStream> {
+        let timeout = match timeout {
+            Some(d) => d.as_millis().min((INFINITE - 1) as u128) as DWORD,
+            None => INFINITE,
+        };
+        let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+        let handle = next.0.as_raw_handle() as HANDLE;
+        match next.1.wait(handle, timeout, |overlapped| unsafe {
+            ConnectNamedPipe(handle, overlapped)
+        }) {
+            Ok(_) => {}
+            // the client connected before `ConnectNamedPipe()`
+            Err(ref e) if is_error(e, ERROR_PIPE_CONNECTED) => {}
+            Err(e) => return Err(e),
+        }
+        let connected = mem::replace(&mut next.0, Self::instance(&self.name, false)?);
+        NamedPipeStream::new(connected, true)
+    }
+}
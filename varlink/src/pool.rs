@@ -0,0 +1,148 @@
+//! A pool of client connections to one service
+
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+
+use crate::error::*;
+use crate::Connection;
+
+struct PoolState {
+    idle: Vec<Arc<RwLock<Connection>>>,
+    /// idle and acquired connections
+    open: usize,
+}
+
+/// A pool of up to `max_connections` connections to the same address.
+///
+/// A connection is busy, while a method call is in progress, so overlapping calls need
+/// their own connections. [`acquire`](#method.acquire) hands out an idle connection, opens
+/// a new one, or waits until another thread returns its connection.
+///
+/// Connections are opened lazily. A connection, which is returned while still busy, e.g. because
+/// a call failed or was not finished, is closed and replaced by a new one on demand.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// use varlink::{ConnectionPool, OrgVarlinkServiceClient, OrgVarlinkServiceInterface};
+///
+/// # fn main() -> varlink::Result<()> {
+/// let pool = Arc::new(ConnectionPool::new("unix:/run/org.example.ftl", 4));
+///
+/// let threads: Vec<_> = (0..8)
+///     .map(|_| {
+///         let pool = pool.clone();
+///         thread::spawn(move || -> varlink::Result<()> {
+///             let connection = pool.acquire()?;
+///             let info = OrgVarlinkServiceClient::new(connection.clone()).get_info()?;
+///             println!("{}", info.product);
+///             Ok(())
+///         })
+///     })
+///     .collect();
+///
+/// for t in threads {
+///     t.join().unwrap()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ConnectionPool {
+    address: String,
+    max_connections: usize,
+    state: Mutex<PoolState>,
+    returned: Condvar,
+}
+
+impl ConnectionPool {
+    /// Create a pool for `address`, without opening a connection yet.
+    ///
+    /// A `max_connections` of `0` is treated as `1`.
+    pub fn new<S: ?Sized + AsRef<str>>(address: &S, max_connections: usize) -> Self {
+        ConnectionPool {
+            address: address.as_ref().into(),
+            max_connections: max_connections.max(1),
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                open: 0,
+            }),
+            returned: Condvar::new(),
+        }
+    }
+
+    /// Get a connection, which is not used by anyone else, until the returned guard is dropped.
+    ///
+    /// Blocks, if `max_connections` connections are in use.
+    pub fn acquire(&self) -> Result<PooledConnection<'_>> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(connection) = state.idle.pop() {
+                return Ok(PooledConnection {
+                    pool: self,
+                    connection: Some(connection),
+                });
+            }
+
+            if state.open < self.max_connections {
+                state.open += 1;
+                drop(state);
+                return match Connection::with_address(&self.address) {
+                    Ok(connection) => Ok(PooledConnection {
+                        pool: self,
+                        connection: Some(connection),
+                    }),
+                    Err(e) => {
+                        self.state.lock().unwrap().open -= 1;
+                        self.returned.notify_one();
+                        Err(e)
+                    }
+                };
+            }
+
+            state = self.returned.wait(state).unwrap();
+        }
+    }
+
+    /// The number of open connections, idle or acquired.
+    pub fn open_connections(&self) -> usize {
+        self.state.lock().unwrap().open
+    }
+
+    fn release(&self, connection: Arc<RwLock<Connection>>) {
+        let busy = connection.read().unwrap().is_busy();
+        let mut state = self.state.lock().unwrap();
+        if busy {
+            state.open -= 1;
+        } else {
+            state.idle.push(connection);
+        }
+        self.returned.notify_one();
+    }
+}
+
+/// A connection of a [`ConnectionPool`](struct.ConnectionPool.html), which is returned to the
+/// pool on drop.
+///
+/// Clients created with a clone of the connection must not be used after the guard is dropped.
+pub struct PooledConnection<'a> {
+    pool: &'a ConnectionPool,
+    connection: Option<Arc<RwLock<Connection>>>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Arc<RwLock<Connection>>;
+
+    fn deref(&self) -> &Self::Target {
+        self.connection.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            self.pool.release(connection);
+        }
+    }
+}
@@ -4,7 +4,7 @@
 use std::{env, fs, thread};
 //#![feature(getpid)]
 //use std::process;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
 use std::mem;
 use std::net::{TcpListener, TcpStream};
 #[cfg(unix)]
@@ -15,20 +15,33 @@ use std::os::unix::net::{UnixListener, UnixStream};
 use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket, RawSocket};
 use std::process;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicUsize, Ordering},
     mpsc, Arc, Mutex, RwLock,
 };
+use std::time::Duration;
 
 #[cfg(windows)]
 use uds_windows::UnixListener;
 
+#[cfg(windows)]
+use crate::npipe::NamedPipeListener;
+
 use crate::error::*;
-use crate::stream::Stream;
+use crate::stream::{MaxMessageSize, Stream};
+#[cfg(feature = "tls")]
+use crate::tls::TlsStream;
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+use crate::vsock::{self, VsockListener};
+use crate::{Call, CallTrait, ConnectionHandler, PeerCredentials, Request};
 
 #[derive(Debug)]
 pub enum Listener {
     TCP(Option<TcpListener>, bool),
     UNIX(Option<UnixListener>, bool),
+    #[cfg(all(feature = "vsock", target_os = "linux"))]
+    VSOCK(Option<VsockListener>, bool),
+    #[cfg(windows)]
+    NPIPE(Option<NamedPipeListener>, bool),
 }
 
 fn activation_listener() -> Option<usize> {
@@ -62,6 +75,35 @@ fn activation_listener() -> Option<usize> {
     None
 }
 
+/// Return the varlink address for a service from the command line arguments or the
+/// environment.
+///
+/// The address is taken from a `--varlink=<address>` or `--varlink <address>` argument in
+/// `args`, or else from the `VARLINK_ADDRESS` environment variable, which is set for
+/// services started by [`Connection::with_activate`](crate::Connection::with_activate).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let args: Vec<String> = std::env::args().collect();
+/// if let Some(address) = varlink::address_from_args_or_env(&args) {
+///     println!("listening on {}", address);
+/// }
+/// ```
+pub fn address_from_args_or_env<S: AsRef<str>>(args: &[S]) -> Option<String> {
+    let mut args = args.iter().map(AsRef::as_ref);
+    while let Some(arg) = args.next() {
+        if let Some(address) = arg.strip_prefix("--varlink=") {
+            return Some(address.into());
+        }
+        if arg == "--varlink" {
+            return args.next().map(Into::into);
+        }
+    }
+
+    env::var("VARLINK_ADDRESS").ok()
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 fn get_abstract_unixlistener(addr: &str) -> Result<UnixListener> {
     use std::os::linux::net::SocketAddrExt;
@@ -73,7 +115,94 @@ fn get_abstract_unixlistener(addr: &str) -> Result<UnixListener> {
 
 #[cfg(not(any(target_os = "linux", target_os = "android")))]
 fn get_abstract_unixlistener(_addr: &str) -> Result<UnixListener> {
-    Err(context!(ErrorKind::InvalidAddress))
+    Err(context!(ErrorKind::OperationNotSupported(
+        "abstract unix sockets".into()
+    )))
+}
+
+/// The access parameters of a unix socket address,
+/// e.g. `unix:/run/org.example.ftl;mode=0660;group=wheel`.
+///
+/// `owner` and `group` take names, `uid` and `gid` numeric ids. Unknown parameters are an error.
+#[cfg(unix)]
+#[derive(Debug, Default, PartialEq)]
+struct UnixSocketAccess {
+    mode: Option<u32>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+}
+
+#[cfg(unix)]
+impl UnixSocketAccess {
+    fn parse(params: &str) -> Result<Self> {
+        let mut access = UnixSocketAccess::default();
+        for param in params.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            let invalid = || {
+                let e = format!("invalid unix socket parameter '{}'", param);
+                context!(e, ErrorKind::InvalidAddress)
+            };
+            match key {
+                "mode" => access.mode = Some(u32::from_str_radix(value, 8).map_err(|_| invalid())?),
+                "uid" => access.uid = Some(value.parse().map_err(|_| invalid())?),
+                "gid" => access.gid = Some(value.parse().map_err(|_| invalid())?),
+                "owner" => access.uid = Some(Self::lookup_user(value)?),
+                "group" => access.gid = Some(Self::lookup_group(value)?),
+                _ => {
+                    let e = format!("unknown unix socket parameter '{}'", param);
+                    return Err(context!(e, ErrorKind::InvalidAddress));
+                }
+            }
+        }
+        Ok(access)
+    }
+
+    fn lookup_user(name: &str) -> Result<u32> {
+        let cname =
+            std::ffi::CString::new(name).map_err(|e| context!(e, ErrorKind::InvalidAddress))?;
+        let pw = unsafe { libc::getpwnam(cname.as_ptr()) };
+        if pw.is_null() {
+            let e = format!("unix socket owner '{}' not found", name);
+            return Err(context!(e, ErrorKind::InvalidAddress));
+        }
+        Ok(unsafe { (*pw).pw_uid })
+    }
+
+    fn lookup_group(name: &str) -> Result<u32> {
+        let cname =
+            std::ffi::CString::new(name).map_err(|e| context!(e, ErrorKind::InvalidAddress))?;
+        let gr = unsafe { libc::getgrnam(cname.as_ptr()) };
+        if gr.is_null() {
+            let e = format!("unix socket group '{}' not found", name);
+            return Err(context!(e, ErrorKind::InvalidAddress));
+        }
+        Ok(unsafe { (*gr).gr_gid })
+    }
+
+    fn apply(&self, path: &str) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if self.uid.is_some() || self.gid.is_some() {
+            let cpath =
+                std::ffi::CString::new(path).map_err(|e| context!(e, ErrorKind::InvalidAddress))?;
+            // -1 leaves the owner or group unchanged
+            let ret = unsafe {
+                libc::chown(
+                    cpath.as_ptr(),
+                    self.uid.unwrap_or(u32::MAX),
+                    self.gid.unwrap_or(u32::MAX),
+                )
+            };
+            if ret != 0 {
+                return Err(std::io::Error::last_os_error()).map_err(map_context!());
+            }
+        }
+
+        if let Some(mode) = self.mode {
+            fs::set_permissions(path, fs::Permissions::from_mode(mode)).map_err(map_context!())?;
+        }
+        Ok(())
+    }
 }
 
 impl Listener {
@@ -83,7 +212,7 @@ impl Listener {
         if let Some(l) = activation_listener() {
             #[cfg(windows)]
             {
-                if address.starts_with("tcp:") {
+                if address.starts_with("tcp:") || address.starts_with("tcp+tls:") {
                     unsafe {
                         return Ok(Listener::TCP(
                             Some(TcpListener::from_raw_socket(l as RawSocket)),
@@ -103,7 +232,7 @@ impl Listener {
             }
             #[cfg(unix)]
             {
-                if address.starts_with("tcp:") {
+                if address.starts_with("tcp:") || address.starts_with("tcp+tls:") {
                     unsafe {
                         return Ok(Listener::TCP(
                             Some(TcpListener::from_raw_fd(l as RawFd)),
@@ -117,13 +246,41 @@ impl Listener {
                             true,
                         ));
                     }
-                } else {
-                    return Err(context!(ErrorKind::InvalidAddress));
                 }
+                #[cfg(all(feature = "vsock", target_os = "linux"))]
+                if address.starts_with("vsock:") {
+                    unsafe {
+                        return Ok(Listener::VSOCK(
+                            Some(VsockListener::from_raw_fd(l as RawFd)),
+                            true,
+                        ));
+                    }
+                }
+                return Err(context!(ErrorKind::InvalidAddress));
             }
         }
 
-        if let Some(addr) = address.strip_prefix("tcp:") {
+        #[cfg(windows)]
+        if let Some(addr) = address.strip_prefix("npipe:") {
+            return Ok(Listener::NPIPE(
+                Some(NamedPipeListener::bind(addr).map_err(map_context!())?),
+                false,
+            ));
+        }
+
+        #[cfg(all(feature = "vsock", target_os = "linux"))]
+        if let Some(addr) = address.strip_prefix("vsock:") {
+            let (cid, port) = vsock::parse_address(addr)?;
+            return Ok(Listener::VSOCK(
+                Some(VsockListener::bind(cid, port).map_err(map_context!())?),
+                false,
+            ));
+        }
+
+        if let Some(addr) = address
+            .strip_prefix("tcp:")
+            .or_else(|| address.strip_prefix("tcp+tls:"))
+        {
             Ok(Listener::TCP(
                 Some(TcpListener::bind(addr).map_err(map_context!())?),
                 false,
@@ -132,13 +289,26 @@ impl Listener {
             get_abstract_unixlistener(addr.split(';').next().unwrap_or(addr))
                 .map(|v| Listener::UNIX(Some(v), false))
         } else if let Some(addr) = address.strip_prefix("unix:") {
-            let addr = addr.split(';').next().unwrap_or(addr);
+            let (addr, params) = addr.split_once(';').unwrap_or((addr, ""));
+            #[cfg(unix)]
+            let access = UnixSocketAccess::parse(params)?;
+            #[cfg(windows)]
+            let _ = params;
             // ignore error on non-existent file
             _ = fs::remove_file(addr);
-            Ok(Listener::UNIX(
-                Some(UnixListener::bind(addr).map_err(map_context!())?),
-                false,
-            ))
+            let listener = UnixListener::bind(addr).map_err(map_context!())?;
+            #[cfg(unix)]
+            if let Err(e) = access.apply(addr) {
+                _ = fs::remove_file(addr);
+                return Err(e);
+            }
+            Ok(Listener::UNIX(Some(listener), false))
+        } else if address.starts_with("vsock:") {
+            // only reached without the `vsock` feature or on other platforms than Linux
+            Err(context!(ErrorKind::OperationNotSupported("vsock".into())))
+        } else if address.starts_with("npipe:") {
+            // only reached on other platforms than Windows
+            Err(context!(ErrorKind::OperationNotSupported("npipe".into())))
         } else {
             Err(context!(ErrorKind::InvalidAddress))
         }
@@ -149,6 +319,16 @@ impl Listener {
         use winapi::um::winsock2::WSAEINTR as EINTR;
         use winapi::um::winsock2::{fd_set, select, timeval};
 
+        // a pipe is no socket to `select()` on
+        if let Listener::NPIPE(Some(ref l), _) = self {
+            let timeout = Some(Duration::from_millis(timeout)).filter(|_| timeout > 0);
+            return match l.accept(timeout) {
+                Ok(s) => Ok(Box::new(s)),
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => Err(context!(ErrorKind::Timeout)),
+                Err(e) => Err(map_context!()(e)),
+            };
+        }
+
         if timeout > 0 {
             let socket: usize =
                 self.as_raw_socket()
@@ -251,6 +431,8 @@ impl Listener {
                 let (s, _addr) = l.accept().map_err(map_context!())?;
                 Ok(Box::new(s))
             }
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::VSOCK(Some(ref l), _) => Ok(Box::new(l.accept().map_err(map_context!())?)),
             _ => Err(context!(ErrorKind::ConnectionClosed)),
         }
     }
@@ -259,6 +441,17 @@ impl Listener {
         match *self {
             Listener::TCP(Some(ref l), _) => l.set_nonblocking(b).map_err(map_context!())?,
             Listener::UNIX(Some(ref l), _) => l.set_nonblocking(b).map_err(map_context!())?,
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::VSOCK(Some(ref l), _) => l.set_nonblocking(b).map_err(map_context!())?,
+            // `accept()` takes a timeout instead
+            #[cfg(windows)]
+            Listener::NPIPE(Some(_), _) if !b => {}
+            #[cfg(windows)]
+            Listener::NPIPE(Some(_), _) => {
+                return Err(context!(ErrorKind::OperationNotSupported(
+                    "set_nonblocking".into()
+                )))
+            }
             _ => return Err(context!(ErrorKind::ConnectionClosed)),
         }
         Ok(())
@@ -269,6 +462,8 @@ impl Listener {
         match *self {
             Listener::TCP(Some(ref l), _) => Some(l.as_raw_fd()),
             Listener::UNIX(Some(ref l), _) => Some(l.as_raw_fd()),
+            #[cfg(all(feature = "vsock", target_os = "linux"))]
+            Listener::VSOCK(Some(ref l), _) => Some(l.as_raw_fd()),
             _ => None,
         }
     }
@@ -458,6 +653,10 @@ impl Worker {
 /// assert_eq!(l.max_worker_threads, 100);
 /// assert_eq!(l.idle_timeout, 0);
 /// assert!(l.stop_listening.is_none());
+/// assert!(l.max_upgraded_connections.is_none());
+/// assert!(l.max_message_size.is_none());
+/// assert!(l.max_connections.is_none());
+/// assert!(l.connection_idle_timeout.is_none());
 /// ```
 ///
 /// [`varlink::listen`]: fn.listen.html
@@ -469,7 +668,39 @@ pub struct ListenConfig {
     /// Time in seconds for the server to quit, when it is idle
     pub idle_timeout: u64,
     /// An optional AtomicBool as a global flag, which lets the server stop accepting new connections, when set to `true`
+    ///
+    /// The flag is checked every 100ms. [`varlink::listen`] then waits for the connections
+    /// in progress to finish and returns `Ok(())`.
+    ///
+    /// [`varlink::listen`]: fn.listen.html
     pub stop_listening: Option<Arc<AtomicBool>>,
+    /// The maximum amount of simultaneously upgraded connections.
+    ///
+    /// Further `upgrade` requests are answered with `org.varlink.service.InvalidParameter`
+    /// for the parameter `upgrade`.
+    pub max_upgraded_connections: Option<usize>,
+    /// The maximum size of a message in bytes, including the terminating NUL byte.
+    ///
+    /// A connection, which sends a longer message, is closed. Messages of upgraded
+    /// connections are not limited.
+    pub max_message_size: Option<usize>,
+    /// The maximum amount of simultaneously handled connections.
+    ///
+    /// Further connections are accepted and closed immediately, instead of waiting
+    /// for a free worker thread.
+    pub max_connections: Option<usize>,
+    /// The TLS configuration of the service, needed for `tcp+tls:` addresses.
+    ///
+    /// [`tls_server_config`] builds it from a PEM encoded certificate and private key.
+    ///
+    /// [`tls_server_config`]: fn.tls_server_config.html
+    #[cfg(feature = "tls")]
+    pub tls: Option<Arc<rustls::ServerConfig>>,
+    /// Close a connection, which sends nothing for this duration.
+    ///
+    /// Unlike `idle_timeout`, this only affects the connection itself. The server keeps
+    /// accepting new connections.
+    pub connection_idle_timeout: Option<Duration>,
 }
 
 impl Default for ListenConfig {
@@ -479,6 +710,12 @@ impl Default for ListenConfig {
             max_worker_threads: 100,
             idle_timeout: 0,
             stop_listening: None,
+            max_upgraded_connections: None,
+            max_message_size: None,
+            max_connections: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+            connection_idle_timeout: None,
         }
     }
 }
@@ -489,6 +726,9 @@ impl Default for ListenConfig {
 /// amount of seconds, if no new connection is made in that time frame. It still waits for
 /// all pending connections to finish.
 ///
+/// If `listen_config.stop_listening` is set to `true`, no new connections are accepted and
+/// this function returns `Ok(())` after all pending connections are finished.
+///
 ///# Examples
 ///
 ///```
@@ -518,12 +758,23 @@ impl Default for ListenConfig {
 ///# Note
 /// You don't have to use this simple server. With the `VarlinkService::handle()` method you
 /// can implement your own server model using whatever framework you prefer.
-pub fn listen<S: ?Sized + AsRef<str>, H: crate::ConnectionHandler + Send + Sync + 'static>(
+pub fn listen<S: ?Sized + AsRef<str>, H: ConnectionHandler + Send + Sync + 'static>(
     handler: H,
     address: &S,
     listen_config: &ListenConfig,
 ) -> Result<()> {
     let handler = Arc::new(handler);
+    #[cfg(feature = "tls")]
+    let tls = match address.as_ref().starts_with("tcp+tls:") {
+        true => Some(listen_config.tls.clone().ok_or(context!(
+            ErrorKind::OperationNotSupported("tcp+tls without ListenConfig::tls".into())
+        ))?),
+        false => None,
+    };
+    #[cfg(not(feature = "tls"))]
+    if address.as_ref().starts_with("tcp+tls:") {
+        return Err(context!(ErrorKind::OperationNotSupported("tcp+tls".into())));
+    }
     let listener = Listener::new(address)?;
 
     listener.set_nonblocking(false)?;
@@ -533,6 +784,11 @@ pub fn listen<S: ?Sized + AsRef<str>, H: crate::ConnectionHandler + Send + Sync
         listen_config.max_worker_threads,
     );
 
+    let max_upgraded = listen_config.max_upgraded_connections;
+    let max_message_size = listen_config.max_message_size.unwrap_or(usize::MAX);
+    let connections = Arc::new(AtomicUsize::new(0));
+    let upgraded = Arc::new(AtomicUsize::new(0));
+
     loop {
         let mut to_wait = listen_config.idle_timeout * 1000;
         let wait_time = listen_config
@@ -571,14 +827,67 @@ pub fn listen<S: ?Sized + AsRef<str>, H: crate::ConnectionHandler + Send + Sync
                 r => break r?,
             }
         };
+
+        if let Some(max) = listen_config.max_connections {
+            if connections.load(Ordering::SeqCst) >= max {
+                let _ = stream.shutdown();
+                continue;
+            }
+        }
+
+        // the handshake happens with the first read in the worker thread
+        #[cfg(feature = "tls")]
+        if let Some(ref config) = tls {
+            stream = match TlsStream::accept(stream, config.clone()) {
+                Ok(stream) => Box::new(stream),
+                // `accept` shut down the stream, keep serving the other connections
+                Err(err) => {
+                    eprintln!("Worker error: {:?}", err);
+                    continue;
+                }
+            };
+        }
+
+        if let Some(timeout) = listen_config.connection_idle_timeout {
+            if let Err(err) = stream.set_timeout(Some(timeout)) {
+                eprintln!("Worker error: {:?}", err);
+                let _ = stream.shutdown();
+                continue;
+            }
+        }
+
         let handler = handler.clone();
+        let upgraded = upgraded.clone();
+        let connections = connections.clone();
 
+        // only counted once the connection is handled, so the `continue`s above don't leak it
+        connections.fetch_add(1, Ordering::SeqCst);
         pool.execute(move || {
+            let peer_credentials = stream.peer_credentials();
             let (r, mut w) = stream.split().unwrap();
-            let mut br = BufReader::new(r);
+            let mut br = MaxMessageSize::new(BufReader::new(r), max_message_size);
             let mut iface: Option<String> = None;
             loop {
-                match handler.handle(&mut br, &mut w, iface.clone()) {
+                let ret = match max_upgraded {
+                    _ if iface.is_some() => handler.handle_with_credentials(
+                        br.get_mut(),
+                        &mut w,
+                        iface.clone(),
+                        peer_credentials,
+                    ),
+                    Some(max) => handle_limit_upgraded(
+                        &*handler,
+                        &mut br,
+                        &mut w,
+                        max,
+                        &upgraded,
+                        peer_credentials,
+                    ),
+                    None => {
+                        handler.handle_with_credentials(&mut br, &mut w, None, peer_credentials)
+                    }
+                };
+                match ret {
                     Ok((_, i)) => {
                         iface = i;
                         match br.fill_buf() {
@@ -589,7 +898,11 @@ pub fn listen<S: ?Sized + AsRef<str>, H: crate::ConnectionHandler + Send + Sync
                     }
                     Err(err) => {
                         match err.kind() {
-                            ErrorKind::ConnectionClosed | ErrorKind::SerdeJsonDe(_) => {}
+                            ErrorKind::ConnectionClosed
+                            | ErrorKind::BrokenPipe
+                            | ErrorKind::SerdeJsonDe(_) => {}
+                            // the connection was idle for `connection_idle_timeout`
+                            ErrorKind::Io(io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
                             _ => {
                                 eprintln!("Worker error: {:?}", err);
                             }
@@ -599,6 +912,61 @@ pub fn listen<S: ?Sized + AsRef<str>, H: crate::ConnectionHandler + Send + Sync
                     }
                 }
             }
+            if max_upgraded.is_some() && iface.is_some() {
+                upgraded.fetch_sub(1, Ordering::SeqCst);
+            }
+            connections.fetch_sub(1, Ordering::SeqCst);
         });
     }
 }
+
+/// Handle the next message of a connection, which is not upgraded,
+/// while at most `max` connections may be `upgraded`.
+///
+/// Messages are handled one by one, so that an `upgrade` request can be rejected,
+/// before it reaches the interface.
+fn handle_limit_upgraded<H: ConnectionHandler + ?Sized>(
+    handler: &H,
+    bufreader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+    max: usize,
+    upgraded: &AtomicUsize,
+    peer_credentials: Option<PeerCredentials>,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let mut buf = Vec::new();
+    bufreader
+        .read_until(b'\0', &mut buf)
+        .map_err(map_context!())?;
+
+    if buf.last() != Some(&b'\0') {
+        // EOF or incomplete message
+        return Ok((buf, None));
+    }
+
+    let upgrade = match serde_json::from_slice::<Request>(&buf[..buf.len() - 1]) {
+        Ok(ref req) if req.upgrade == Some(true) => {
+            let reserved = upgraded
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                    if n < max {
+                        Some(n + 1)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok();
+            if !reserved {
+                Call::new(writer, req).reply_invalid_parameter("upgrade".into())?;
+                return Ok((Vec::new(), None));
+            }
+            true
+        }
+        _ => false,
+    };
+
+    let ret = handler.handle_with_credentials(&mut buf.as_slice(), writer, None, peer_credentials);
+
+    if upgrade && !matches!(ret, Ok((_, Some(_)))) {
+        upgraded.fetch_sub(1, Ordering::SeqCst);
+    }
+    ret
+}
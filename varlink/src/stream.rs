@@ -1,31 +1,214 @@
-use std::io::{Read, Write};
+use std::io::{self, BufRead, Read, Write};
 use std::net::{Shutdown, TcpStream};
 #[cfg(unix)]
 use std::os::unix::io::AsRawFd;
 #[cfg(unix)]
 use std::os::unix::net::UnixStream;
-#[cfg(windows)]
-use std::os::windows::io::AsRawSocket;
+use std::time::Duration;
 
 #[cfg(windows)]
 use uds_windows::UnixStream;
 
 use crate::error::*;
 
+/// A `BufRead`, which fails with `ErrorKind::MessageTooLarge`, if a message
+/// is longer than `max` bytes including the terminating NUL byte.
+pub(crate) struct MaxMessageSize<R> {
+    inner: R,
+    max: usize,
+    /// bytes consumed of the current message
+    len: usize,
+    /// length of the last slice returned by `fill_buf()`, if it ends with the NUL byte
+    message_end: Option<usize>,
+}
+
+impl<R: BufRead> MaxMessageSize<R> {
+    pub(crate) fn new(inner: R, max: usize) -> Self {
+        MaxMessageSize {
+            inner,
+            max,
+            len: 0,
+            message_end: None,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+}
+
+impl<R: BufRead> Read for MaxMessageSize<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for MaxMessageSize<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.len >= self.max {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                context!(ErrorKind::MessageTooLarge),
+            ));
+        }
+        let left = self.max - self.len;
+        let buf = self.inner.fill_buf()?;
+        // never return more than the rest of the current message
+        let (buf, message_end) = match buf.iter().position(|b| *b == 0) {
+            Some(i) if i < left => (&buf[..=i], Some(i + 1)),
+            _ => (&buf[..buf.len().min(left)], None),
+        };
+        self.message_end = message_end;
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if self.message_end == Some(amt) {
+            self.len = 0;
+        } else {
+            self.len += amt;
+        }
+        self.message_end = None;
+        self.inner.consume(amt);
+    }
+}
+
+/// A `Read` of a single message from `inner`, which ends before the terminating NUL byte.
+///
+/// The NUL byte is consumed, so `inner` is positioned at the next message afterwards.
+/// Fails with `ErrorKind::MessageTooLarge` like [`MaxMessageSize`], if the message is longer
+/// than `max` bytes including the NUL byte.
+pub(crate) struct MessageReader<R> {
+    inner: R,
+    max: usize,
+    /// bytes consumed including the NUL byte
+    len: usize,
+    done: bool,
+    /// the start of the message for error messages
+    head: Vec<u8>,
+    /// the whole message, if requested
+    raw: Option<Vec<u8>>,
+}
+
+impl<R: BufRead> MessageReader<R> {
+    const HEAD_LEN: usize = 1024;
+
+    pub(crate) fn new(inner: R, max: usize, keep_raw: bool) -> Self {
+        MessageReader {
+            inner,
+            max,
+            len: 0,
+            done: false,
+            head: Vec::new(),
+            raw: if keep_raw { Some(Vec::new()) } else { None },
+        }
+    }
+
+    /// Skip the rest of the message.
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        io::copy(self, &mut io::sink()).map(|_| ())
+    }
+
+    pub(crate) fn head(&self) -> &[u8] {
+        &self.head
+    }
+
+    pub(crate) fn into_raw(self) -> Option<Vec<u8>> {
+        self.raw
+    }
+}
+
+impl<R: BufRead> Read for MessageReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done {
+            return Ok(0);
+        }
+        if self.len >= self.max {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                context!(ErrorKind::MessageTooLarge),
+            ));
+        }
+        let left = self.max - self.len;
+        let available = self.inner.fill_buf()?;
+        let end = available.len().min(left);
+        // look one byte further for a NUL byte directly after a full `buf`
+        let (n, consumed) = match available[..end.min(buf.len() + 1)]
+            .iter()
+            .position(|b| *b == 0)
+        {
+            Some(i) => {
+                self.done = true;
+                (i, i + 1)
+            }
+            None => (end.min(buf.len()), end.min(buf.len())),
+        };
+        buf[..n].copy_from_slice(&available[..n]);
+        self.inner.consume(consumed);
+        self.len += consumed;
+
+        if self.head.len() < Self::HEAD_LEN {
+            let m = n.min(Self::HEAD_LEN - self.head.len());
+            self.head.extend_from_slice(&buf[..m]);
+        }
+        if let Some(raw) = self.raw.as_mut() {
+            raw.extend_from_slice(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// The credentials of the process on the other side of a unix socket connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCredentials {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: i32,
+}
+
 #[cfg(unix)]
 pub trait Stream: Read + Write + Send + Sync + AsRawFd {
     fn split(&mut self) -> Result<(Box<dyn Read + Send + Sync>, Box<dyn Write + Send + Sync>)>;
     fn shutdown(&mut self) -> Result<()>;
     fn try_clone(&mut self) -> ::std::io::Result<Box<dyn Stream>>;
     fn set_nonblocking(&mut self, b: bool) -> Result<()>;
+
+    /// Set the read and write timeout of the stream, `None` blocks forever.
+    fn set_timeout(&mut self, _timeout: Option<Duration>) -> Result<()> {
+        Err(context!(ErrorKind::OperationNotSupported(
+            "set_timeout".into()
+        )))
+    }
+
+    /// The credentials of the peer, only available for unix sockets on Linux.
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        None
+    }
 }
 
+/// On Windows, a stream is not necessarily a socket, e.g. a named pipe.
 #[cfg(windows)]
-pub trait Stream: Read + Write + Send + Sync + AsRawSocket {
+pub trait Stream: Read + Write + Send + Sync {
     fn split(&mut self) -> Result<(Box<dyn Read + Send + Sync>, Box<dyn Write + Send + Sync>)>;
     fn shutdown(&mut self) -> Result<()>;
     fn try_clone(&mut self) -> ::std::io::Result<Box<dyn Stream>>;
     fn set_nonblocking(&mut self, b: bool) -> Result<()>;
+
+    /// Set the read and write timeout of the stream, `None` blocks forever.
+    fn set_timeout(&mut self, _timeout: Option<Duration>) -> Result<()> {
+        Err(context!(ErrorKind::OperationNotSupported(
+            "set_timeout".into()
+        )))
+    }
+
+    /// The credentials of the peer, only available for unix sockets on Linux.
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        None
+    }
 }
 
 impl Stream for TcpStream {
@@ -53,6 +236,12 @@ impl Stream for TcpStream {
         TcpStream::set_nonblocking(self, b).map_err(map_context!())?;
         Ok(())
     }
+    #[inline]
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        TcpStream::set_read_timeout(self, timeout).map_err(map_context!())?;
+        TcpStream::set_write_timeout(self, timeout).map_err(map_context!())?;
+        Ok(())
+    }
 }
 
 impl Stream for UnixStream {
@@ -80,4 +269,37 @@ impl Stream for UnixStream {
         UnixStream::set_nonblocking(self, b).map_err(map_context!())?;
         Ok(())
     }
+    #[inline]
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        UnixStream::set_read_timeout(self, timeout).map_err(map_context!())?;
+        UnixStream::set_write_timeout(self, timeout).map_err(map_context!())?;
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        let mut cred = libc::ucred {
+            pid: 0,
+            uid: 0,
+            gid: 0,
+        };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                self.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut cred as *mut libc::ucred as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if ret != 0 {
+            return None;
+        }
+        Some(PeerCredentials {
+            uid: cred.uid,
+            gid: cred.gid,
+            pid: cred.pid,
+        })
+    }
 }
@@ -209,3 +209,2438 @@ fn test_handle() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn test_reply_stream() -> Result<()> {
+    let request = Request {
+        more: Some(true),
+        ..Request::create("org.example.more.Ping", None)
+    };
+    let mut w = vec![];
+    {
+        let mut call = Call::new(&mut w, &request);
+        let mut replies =
+            (0..3).map(|i| Reply::parameters(Some(serde_json::json!({ "progress": i }))));
+        call.reply_stream(&mut replies)?;
+    }
+
+    let replies = w
+        .split(|b| *b == 0)
+        .filter(|m| !m.is_empty())
+        .map(|m| from_slice::<Reply>(m).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(replies.len(), 3);
+    assert_eq!(replies[0].continues, Some(true));
+    assert_eq!(replies[1].continues, Some(true));
+    assert_eq!(replies[2].continues, None);
+    assert_eq!(
+        replies[2].parameters,
+        Some(serde_json::json!({ "progress": 2 }))
+    );
+
+    let request = Request::create("org.example.more.Ping", None);
+    let mut w = vec![];
+    let mut call = Call::new(&mut w, &request);
+    let e = call
+        .reply_stream(&mut std::iter::once(Reply::parameters(None)))
+        .unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::CallContinuesMismatch);
+
+    Ok(())
+}
+
+#[test]
+fn test_varlink_int() {
+    use serde_json::json;
+
+    assert_eq!(from_value::<VarlinkInt>(json!(42)).unwrap(), VarlinkInt(42));
+    assert_eq!(from_value::<VarlinkInt>(json!(-7)).unwrap(), VarlinkInt(-7));
+    assert_eq!(from_slice::<VarlinkInt>(b"1e3").unwrap(), VarlinkInt(1000));
+    assert_eq!(serde_json::to_string(&VarlinkInt(42)).unwrap(), "42");
+
+    for invalid in [&b"3.5"[..], b"1e19", b"9223372036854775808", b"\"42\""] {
+        assert!(from_slice::<VarlinkInt>(invalid).is_err());
+    }
+    let e = from_slice::<VarlinkInt>(b"3.5").unwrap_err();
+    assert!(e.to_string().contains("3.5"), "{}", e);
+}
+
+#[test]
+fn test_framing() -> Result<()> {
+    let frames: [&[u8]; 4] = [b"", b"\0\0\0\0", b"\x01\x02\xff\n\0binary", &[0xaa; 70000]];
+
+    let mut wire = Vec::new();
+    for frame in frames.iter() {
+        write_framed(&mut wire, frame)?;
+    }
+
+    let mut reader = std::io::Cursor::new(&wire[..]);
+    let mut buf = Vec::new();
+    for frame in frames.iter() {
+        assert_eq!(read_framed(&mut reader, &mut buf)?, frame.len());
+        assert_eq!(&buf[..], *frame);
+    }
+
+    let e = read_framed(&mut reader, &mut buf).unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::ConnectionClosed);
+
+    // truncated payload
+    let mut reader = std::io::Cursor::new(&wire[..wire.len() - 1]);
+    for _ in 0..3 {
+        read_framed(&mut reader, &mut buf)?;
+    }
+    let e = read_framed(&mut reader, &mut buf).unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::ConnectionClosed);
+
+    // the length is checked before allocating the payload
+    let mut reader = std::io::Cursor::new(&wire[..]);
+    assert_eq!(read_framed_with_limit(&mut reader, &mut buf, 0)?, 0);
+    assert_eq!(read_framed_with_limit(&mut reader, &mut buf, 4)?, 4);
+    let e = read_framed_with_limit(&mut reader, &mut buf, 4).unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::MessageTooLarge);
+    let mut reader = std::io::Cursor::new(&b"\xff\xff\xff\xff"[..]);
+    let e = read_framed(&mut reader, &mut buf).unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::MessageTooLarge);
+
+    Ok(())
+}
+
+#[test]
+fn test_request_id() -> Result<()> {
+    struct RequestIdInterface;
+
+    impl Interface for RequestIdInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.requestid\nmethod Get() -> (id: ?string)"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.requestid"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            let id = call.request_id().map(String::from);
+            call.reply_struct(Reply::parameters(Some(serde_json::json!({ "id": id }))))
+        }
+    }
+
+    let service = VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(RequestIdInterface)],
+    );
+
+    let mut w = vec![];
+    let mut br = concat!(
+        r#"{"method":"org.example.requestid.Get","x-request-id":"4711","x-other":1}"#,
+        "\0",
+        r#"{"method":"org.example.requestid.Get"}"#,
+        "\0"
+    )
+    .as_bytes();
+    service.handle(&mut br, &mut w, None)?;
+
+    assert_eq!(
+        String::from_utf8_lossy(&w),
+        concat!(
+            r#"{"parameters":{"id":"4711"}}"#,
+            "\0",
+            r#"{"parameters":{"id":null}}"#,
+            "\0"
+        )
+    );
+
+    let request: Request = serde_json::from_str(
+        r#"{"method":"org.example.requestid.Get","x-request-id":"4711","x-other":1}"#,
+    )
+    .map_err(map_context!())?;
+    assert_eq!(request.extra.len(), 2);
+    assert_eq!(
+        serde_json::to_value(&request).map_err(map_context!())?,
+        serde_json::json!({"method":"org.example.requestid.Get","x-request-id":"4711","x-other":1})
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_get_all_interface_descriptions() -> Result<()> {
+    struct TestInterface(&'static str, &'static str);
+
+    impl Interface for TestInterface {
+        fn get_description(&self) -> &'static str {
+            self.1
+        }
+
+        fn get_name(&self) -> &'static str {
+            self.0
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            call.reply_method_not_implemented(call.request.unwrap().method.to_string())
+        }
+    }
+
+    let address = "unix:test_get_all_interface_descriptions";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![
+                Box::new(TestInterface(
+                    "org.example.foo",
+                    "interface org.example.foo\nmethod Foo() -> ()\n",
+                )),
+                Box::new(TestInterface(
+                    "org.example.bar",
+                    "interface org.example.bar\nmethod Bar() -> ()\n",
+                )),
+            ],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 2,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_secs(1));
+
+    let mut client = OrgVarlinkServiceClient::new(Connection::with_address(address)?);
+    let descriptions = client.get_all_interface_descriptions()?;
+
+    assert_eq!(descriptions.len(), 3);
+    assert!(descriptions["org.varlink.service"].contains("interface org.varlink.service"));
+    assert_eq!(
+        descriptions["org.example.foo"],
+        "interface org.example.foo\nmethod Foo() -> ()\n"
+    );
+    assert_eq!(
+        descriptions["org.example.bar"],
+        "interface org.example.bar\nmethod Bar() -> ()\n"
+    );
+
+    // the client can be used again
+    assert_eq!(client.get_info()?.interfaces.len(), 3);
+    drop(client);
+
+    assert!(child.join().is_ok());
+    Ok(())
+}
+
+/// `true`, if `ssh` to localhost works without a password and `varlink` is installed there
+#[cfg(unix)]
+fn ssh_localhost_usable() -> bool {
+    std::process::Command::new("ssh")
+        .args(["-o", "BatchMode=yes", "localhost", "varlink", "--version"])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ssh() -> Result<()> {
+    if !ssh_localhost_usable() {
+        eprintln!("skipping test_ssh: ssh to localhost is not usable");
+        return Ok(());
+    }
+
+    let address = format!(
+        "unix:{}",
+        std::env::current_dir().unwrap().join("test_ssh").display()
+    );
+    let client_address = address.clone();
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 2,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_secs(1));
+
+    let connection = Connection::with_ssh("localhost", &client_address)?;
+    let info = OrgVarlinkServiceClient::new(connection.clone()).get_info()?;
+    assert_eq!(info.vendor, "org.varlink");
+    drop(connection);
+
+    assert!(child.join().is_ok());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ssh_unknown_host() -> Result<()> {
+    if std::process::Command::new("ssh")
+        .arg("-V")
+        .output()
+        .is_err()
+    {
+        eprintln!("skipping test_ssh_unknown_host: ssh is not installed");
+        return Ok(());
+    }
+
+    let e = Connection::with_ssh("unknown-host.invalid", "unix:/run/org.example.ftl")
+        .err()
+        .unwrap();
+    match e.kind() {
+        ErrorKind::SshConnectionFailed(message) => {
+            assert!(message.contains("unknown-host.invalid"), "{}", message)
+        }
+        kind => panic!("unexpected error: {:?}", kind),
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ssh_error_hanging() {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    // an ssh, which neither exits nor closes its stderr after closing the connection
+    let mut child = Command::new("sleep")
+        .arg("60")
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr = thread::spawn(move || {
+        let mut message = String::new();
+        let _r = stderr.read_to_string(&mut message);
+        message
+    });
+
+    let start = Instant::now();
+    let e = client::ssh_error(&mut child, stderr, context!(ErrorKind::ConnectionClosed));
+    assert_eq!(*e.kind(), ErrorKind::ConnectionClosed);
+    assert!(start.elapsed() < Duration::from_secs(30));
+    assert!(child.try_wait().unwrap().is_some());
+}
+
+#[test]
+fn test_max_upgraded_connections() -> Result<()> {
+    struct UpgradeInterface;
+
+    impl Interface for UpgradeInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.upgrade\nmethod Upgrade() -> ()"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.upgrade"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            // wait for the client to close the connection
+            let mut buf = Vec::new();
+            bufreader.read_to_end(&mut buf).map_err(map_context!())?;
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            call.to_upgraded();
+            call.reply_struct(Reply::parameters(None))
+        }
+    }
+
+    fn upgrade(connection: Arc<RwLock<Connection>>) -> Result<Value> {
+        MethodCall::<Value, Value, Error>::new(
+            connection,
+            "org.example.upgrade.Upgrade",
+            serde_json::json!({}),
+        )
+        .upgrade()
+    }
+
+    let address = "unix:test_max_upgraded_connections";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![Box::new(UpgradeInterface)],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 2,
+                max_upgraded_connections: Some(1),
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_secs(1));
+
+    let upgraded = Connection::with_address(&address)?;
+    upgrade(upgraded.clone())?;
+
+    let rejected = Connection::with_address(&address)?;
+    let e = upgrade(rejected.clone()).err().unwrap();
+    assert_eq!(*e.kind(), ErrorKind::InvalidParameter("upgrade".into()));
+
+    // not upgrading still works
+    let info = OrgVarlinkServiceClient::new(Connection::with_address(&address)?).get_info()?;
+    assert_eq!(info.vendor, "org.varlink");
+
+    // closing the upgraded connection frees the slot
+    drop(upgraded);
+    thread::sleep(time::Duration::from_millis(200));
+    let upgraded = Connection::with_address(&address)?;
+    upgrade(upgraded.clone())?;
+
+    drop(upgraded);
+    drop(rejected);
+
+    assert!(child.join().is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_is_busy() -> Result<()> {
+    struct CountInterface;
+
+    impl Interface for CountInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.count\nmethod Count() -> (i: int)"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.count"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            let mut replies =
+                (0..3).map(|i| Reply::parameters(Some(serde_json::json!({ "i": i }))));
+            call.reply_stream(&mut replies)
+        }
+    }
+
+    let address = "unix:test_is_busy";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![Box::new(CountInterface)],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 2,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_secs(1));
+
+    let connection = Connection::with_address(&address)?;
+    assert!(!connection.read().unwrap().is_busy());
+
+    let mut call = MethodCall::<Value, Value, Error>::new(
+        connection.clone(),
+        "org.example.count.Count",
+        serde_json::json!({}),
+    );
+    let mut replies = 0;
+    for reply in call.more()? {
+        reply?;
+        replies += 1;
+        assert_eq!(connection.read().unwrap().is_busy(), replies < 3);
+    }
+    assert_eq!(replies, 3);
+    assert!(!connection.read().unwrap().is_busy());
+
+    drop(call);
+    drop(connection);
+
+    assert!(child.join().is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_stop_listening() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
+    let address = "unix:test_stop_listening";
+    let stop = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+
+    let stop_listening = stop.clone();
+    thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        let ret = listen(
+            service,
+            &address,
+            &ListenConfig {
+                // would not return for a long time, if the flag is ignored
+                idle_timeout: 60,
+                stop_listening: Some(stop_listening),
+                ..Default::default()
+            },
+        );
+        tx.send(ret).unwrap();
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    {
+        let conn = Connection::with_address(&address)?;
+        let info = OrgVarlinkServiceClient::new(conn).get_info()?;
+        assert_eq!(&info.product, "test service");
+    }
+
+    stop.store(true, Ordering::SeqCst);
+
+    match rx.recv_timeout(time::Duration::from_secs(2)) {
+        Ok(ret) => assert!(ret.is_ok(), "listen failed: {:?}", ret),
+        Err(_) => panic!("listen did not return after stop_listening was set"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_connection_idle_timeout() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let address = "unix:test_connection_idle_timeout";
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let stop_listening = stop.clone();
+    let server = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        listen(
+            service,
+            &address,
+            &ListenConfig {
+                initial_worker_threads: 2,
+                stop_listening: Some(stop_listening),
+                connection_idle_timeout: Some(Duration::from_millis(300)),
+                ..Default::default()
+            },
+        )
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let mut idle = OrgVarlinkServiceClient::new(Connection::with_address(&address)?);
+    let mut active = OrgVarlinkServiceClient::new(Connection::with_address(&address)?);
+    idle.get_info()?;
+
+    for _ in 0..8 {
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(active.get_info()?.product, "test service");
+    }
+
+    assert!(idle.get_info().is_err());
+    assert_eq!(active.get_info()?.product, "test service");
+
+    drop(active);
+    stop.store(true, Ordering::SeqCst);
+    server.join().expect("Error joining thread")
+}
+
+#[test]
+fn test_reply_error() -> Result<()> {
+    #[derive(Serialize)]
+    struct NotFound {
+        name: String,
+    }
+
+    struct ErrorInterface;
+
+    impl Interface for ErrorInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.errors\nmethod Get() -> ()\nmethod GetDyn() -> ()\nerror NotFound (name: string)"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.errors"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            match call.request.map(|r| r.method.as_ref()) {
+                Some("org.example.errors.Get") => call.reply_error(
+                    "org.example.errors.NotFound",
+                    NotFound { name: "foo".into() },
+                ),
+                _ => reply_not_found(call),
+            }
+        }
+    }
+
+    // like the methods of generated interfaces, which get a `&mut dyn Call_*`
+    fn reply_not_found(call: &mut dyn CallTrait) -> Result<()> {
+        call.reply_error_value(
+            "org.example.errors.NotFound",
+            serde_json::json!({ "name": "bar" }),
+        )
+    }
+
+    let address = "unix:test_reply_error";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![Box::new(ErrorInterface)],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 1,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let connection = Connection::with_address(&address)?;
+    for (method, name) in [("Get", "foo"), ("GetDyn", "bar")] {
+        let e = MethodCall::<Value, Value, Error>::new(
+            connection.clone(),
+            format!("org.example.errors.{}", method),
+            serde_json::json!({}),
+        )
+        .call()
+        .unwrap_err();
+
+        match e.kind() {
+            ErrorKind::VarlinkErrorReply(reply) => {
+                assert_eq!(reply.error.as_deref(), Some("org.example.errors.NotFound"));
+                assert_eq!(reply.parameters, Some(serde_json::json!({ "name": name })));
+            }
+            kind => panic!("Unknown error {:?}", kind),
+        }
+    }
+    drop(connection);
+
+    assert!(child.join().is_ok());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_peer_credentials() -> Result<()> {
+    struct WhoAmIInterface;
+
+    impl Interface for WhoAmIInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.whoami\nmethod WhoAmI() -> (uid: ?int, pid: ?int)"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.whoami"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            let cred = call.peer_credentials().cloned();
+            call.reply_struct(Reply::parameters(Some(serde_json::json!({
+                "uid": cred.map(|c| c.uid),
+                "pid": cred.map(|c| c.pid),
+            }))))
+        }
+    }
+
+    let address = "unix:test_peer_credentials";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![Box::new(WhoAmIInterface)],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 1,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let connection = Connection::with_address(&address)?;
+    let reply = MethodCall::<Value, Value, Error>::new(
+        connection,
+        "org.example.whoami.WhoAmI",
+        serde_json::json!({}),
+    )
+    .call()?;
+
+    assert_eq!(reply["uid"], serde_json::json!(unsafe { libc::getuid() }));
+    assert_eq!(reply["pid"], serde_json::json!(std::process::id()));
+
+    // without a socket, there are no credentials
+    let mut w = vec![];
+    let mut br = concat!(r#"{"method":"org.example.whoami.WhoAmI"}"#, "\0").as_bytes();
+    VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(WhoAmIInterface)],
+    )
+    .handle(&mut br, &mut w, None)?;
+    assert_eq!(
+        String::from_utf8_lossy(&w),
+        concat!(r#"{"parameters":{"pid":null,"uid":null}}"#, "\0")
+    );
+
+    assert!(child.join().is_ok());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reconnect() -> Result<()> {
+    use std::os::unix::net::UnixListener;
+    use std::sync::mpsc;
+
+    let path = "test_reconnect";
+    let (tx, rx) = mpsc::channel();
+
+    // a service, which is restarted after every request
+    let server = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        for _ in 0..3 {
+            let _ = std::fs::remove_file(path);
+            let listener = UnixListener::bind(path).unwrap();
+            tx.send(()).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut br = BufReader::new(stream.try_clone().unwrap());
+            let mut buf = Vec::new();
+            br.read_until(0, &mut buf).unwrap();
+            service
+                .handle(&mut buf.as_slice(), &mut stream, None)
+                .unwrap();
+        }
+        let _ = std::fs::remove_file(path);
+    });
+
+    rx.recv().unwrap();
+    let connection = Connection::with_address(&format!("unix:{}", path))?;
+    let mut client = OrgVarlinkServiceClient::new(connection.clone());
+    assert_eq!(&client.get_info()?.product, "test service");
+
+    // the restarted service does not know the old connection, so writing the request fails
+    rx.recv().unwrap();
+    let e = client.get_info().unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::BrokenPipe);
+    connection.write().unwrap().reconnect()?;
+    assert_eq!(&client.get_info()?.product, "test service");
+
+    rx.recv().unwrap();
+    connection.write().unwrap().set_auto_reconnect(true);
+    assert_eq!(&client.get_info()?.product, "test service");
+
+    server.join().unwrap();
+
+    let mut bridge = Connection::default();
+    bridge.address = "bridge".into();
+    assert_eq!(
+        *bridge.reconnect().unwrap_err().kind(),
+        ErrorKind::OperationNotSupported("reconnect".into())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_max_message_size_reader() {
+    use crate::stream::MaxMessageSize;
+
+    let input = concat!("abc\0", "defg\0", "hijklm\0");
+    let mut reader = MaxMessageSize::new(input.as_bytes(), 5);
+    let mut buf = Vec::new();
+
+    reader.read_until(0, &mut buf).unwrap();
+    assert_eq!(buf, b"abc\0");
+    buf.clear();
+    reader.read_until(0, &mut buf).unwrap();
+    assert_eq!(buf, b"defg\0");
+    buf.clear();
+    let e = reader.read_until(0, &mut buf).unwrap_err();
+    assert_eq!(ErrorKind::from(&e), ErrorKind::MessageTooLarge);
+}
+
+#[test]
+fn test_message_reader() {
+    use crate::stream::MessageReader;
+    use std::io::Read;
+
+    let input = concat!("abc\0", "defg\0", "hi");
+    let mut input = input.as_bytes();
+
+    let mut message = MessageReader::new(&mut input, 5, true);
+    let mut buf = Vec::new();
+    message.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"abc");
+    assert_eq!(message.into_raw().unwrap(), b"abc");
+
+    // reads in small pieces do not cross the NUL byte
+    let mut message = MessageReader::new(&mut input, 5, false);
+    let mut buf = [0u8; 4];
+    assert_eq!(message.read(&mut buf).unwrap(), 4);
+    assert_eq!(message.read(&mut buf).unwrap(), 0);
+    assert_eq!(message.head(), b"defg");
+    assert!(message.into_raw().is_none());
+
+    let mut message = MessageReader::new(&mut input, 2, false);
+    let e = message.finish().unwrap_err();
+    assert_eq!(ErrorKind::from(&e), ErrorKind::MessageTooLarge);
+}
+
+/// Counts the bytes allocated by each thread, to check the memory used to receive a reply.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+    static PEAK: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+}
+
+impl CountingAllocator {
+    fn add(n: isize) {
+        let _ = ALLOCATED.try_with(|allocated| {
+            allocated.set(allocated.get() + n);
+            let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+        });
+    }
+
+    /// Run `f` and return its result and the most bytes allocated by it at once.
+    #[cfg(unix)]
+    fn peak<T>(f: impl FnOnce() -> T) -> (T, usize) {
+        let start = ALLOCATED.with(|a| a.get());
+        PEAK.with(|p| p.set(start));
+        let t = f();
+        (t, (PEAK.with(|p| p.get()) - start) as usize)
+    }
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let p = std::alloc::System.alloc(layout);
+        if !p.is_null() {
+            Self::add(layout.size() as isize);
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, p: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(p, layout);
+        Self::add(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, p: *mut u8, layout: std::alloc::Layout, size: usize) -> *mut u8 {
+        let new = std::alloc::System.realloc(p, layout, size);
+        if !new.is_null() {
+            Self::add(size as isize - layout.size() as isize);
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[cfg(unix)]
+#[test]
+fn test_large_reply() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::io::OwnedFd;
+    use std::os::unix::net::UnixStream;
+
+    const LEN: usize = 8 * 1024 * 1024;
+
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+
+    // reply to two requests with a long string, and to the last one with a long padding
+    let child = thread::spawn(move || -> Result<()> {
+        let mut reader = BufReader::new(&server);
+        let mut writer = &server;
+        let data = "x".repeat(LEN);
+        let large = serde_json::to_vec(&Reply::parameters(Some(json!({ "data": data }))))
+            .map_err(map_context!())?;
+        let padded = format!("{{\"parameters\":{{\"data\":\"x\"}}{}}}", " ".repeat(LEN));
+        for reply in [&large, &large, padded.as_bytes()] {
+            let mut request = Vec::new();
+            reader.read_until(0, &mut request).map_err(map_context!())?;
+            writer.write_all(reply).map_err(map_context!())?;
+            writer.write_all(b"\0").map_err(map_context!())?;
+        }
+        Ok(())
+    });
+
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+
+    let mut call =
+        MethodCall::<Value, Value, Error>::new(connection.clone(), "org.example.Large", json!({}));
+    let reply = call.call()?;
+    assert_eq!(reply["data"].as_str().map(str::len), Some(LEN));
+    assert!(reply["data"].as_str().unwrap().bytes().all(|b| b == b'x'));
+    // the reply was parsed without buffering it
+    assert!(call.raw_reply().is_none());
+
+    let mut call =
+        MethodCall::<Value, Value, Error>::new(connection.clone(), "org.example.Large", json!({}));
+    call.set_keep_raw_reply(true);
+    let reply = call.call()?;
+    assert_eq!(reply["data"].as_str().map(str::len), Some(LEN));
+    assert_eq!(
+        call.raw_reply().map(<[u8]>::len),
+        Some(LEN + r#"{"parameters":{"data":""}}"#.len())
+    );
+
+    // only the parsed reply takes memory, the frame is not copied into a buffer
+    let mut call =
+        MethodCall::<Value, Value, Error>::new(connection, "org.example.Large", json!({}));
+    let (reply, peak) = CountingAllocator::peak(|| call.call());
+    assert_eq!(reply?, json!({ "data": "x" }));
+    assert!(peak < 64 * 1024, "{} bytes allocated", peak);
+
+    child.join().expect("Error joining thread")?;
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_max_message_size() -> Result<()> {
+    use std::io::{Read, Write};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    // server side
+    let address = "unix:test_max_message_size";
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 1,
+                max_message_size: Some(1024),
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let mut stream = UnixStream::connect("test_max_message_size").map_err(map_context!())?;
+    // the server may close the connection before everything is written
+    let _ = stream.write_all(&[b' '; 4096]);
+    let mut reply = Vec::new();
+    let _ = stream.read_to_end(&mut reply);
+    assert!(reply.is_empty());
+
+    let info = OrgVarlinkServiceClient::new(Connection::with_address(&address)?).get_info()?;
+    assert_eq!(&info.product, "test service");
+    child.join().unwrap();
+
+    // client side
+    let path = "test_max_message_size_client";
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path).map_err(map_context!())?;
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut br = BufReader::new(stream.try_clone().unwrap());
+        let mut buf = Vec::new();
+        br.read_until(0, &mut buf).unwrap();
+        let _ = stream.write_all(&[b' '; 4096]);
+        // wait for the client to give up
+        let _ = br.read_to_end(&mut buf);
+    });
+
+    let connection = Connection::with_address(&format!("unix:{}", path))?;
+    connection.write().unwrap().set_max_message_size(Some(1024));
+    let e = OrgVarlinkServiceClient::new(connection.clone())
+        .get_info()
+        .unwrap_err();
+    assert_eq!(*e.kind(), ErrorKind::MessageTooLarge);
+
+    drop(connection);
+    server.join().unwrap();
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[test]
+fn test_connection_pool() -> Result<()> {
+    use std::sync::Arc;
+
+    let address = "unix:test_connection_pool";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 1,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let pool = Arc::new(ConnectionPool::new(address, 2));
+
+    let threads: Vec<_> = (0..6)
+        .map(|_| {
+            let pool = pool.clone();
+            thread::spawn(move || -> Result<()> {
+                for _ in 0..5 {
+                    let connection = pool.acquire()?;
+                    let info = OrgVarlinkServiceClient::new(connection.clone()).get_info()?;
+                    assert_eq!(&info.product, "test service");
+                }
+                Ok(())
+            })
+        })
+        .collect();
+
+    for t in threads {
+        t.join().unwrap()?;
+    }
+    assert!(pool.open_connections() <= 2);
+
+    // a connection returned in the middle of a call is replaced
+    {
+        let connection = pool.acquire()?;
+        connection.write().unwrap().reader = None;
+    }
+    {
+        let a = pool.acquire()?;
+        let b = pool.acquire()?;
+        assert!(!a.read().unwrap().is_busy());
+        assert!(!b.read().unwrap().is_busy());
+        assert_eq!(pool.open_connections(), 2);
+    }
+
+    drop(pool);
+    child.join().unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_max_connections() -> Result<()> {
+    let address = "unix:test_max_connections";
+
+    let child = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        if let Err(e) = listen(
+            service,
+            &address,
+            &ListenConfig {
+                idle_timeout: 1,
+                max_connections: Some(2),
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let mut clients = (0..2)
+        .map(|_| {
+            Ok(OrgVarlinkServiceClient::new(Connection::with_address(
+                &address,
+            )?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    for client in clients.iter_mut() {
+        client.get_info()?;
+    }
+
+    let mut refused = OrgVarlinkServiceClient::new(Connection::with_address(&address)?);
+    assert_eq!(
+        *refused.get_info().unwrap_err().kind(),
+        ErrorKind::ConnectionClosed
+    );
+
+    // a closed connection makes room for a new one
+    clients.pop();
+    thread::sleep(time::Duration::from_millis(200));
+    OrgVarlinkServiceClient::new(Connection::with_address(&address)?).get_info()?;
+
+    drop(clients);
+    child.join().unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_add_remove_interface() -> Result<()> {
+    struct PluginInterface;
+
+    impl Interface for PluginInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.plugin\nmethod Hello() -> (greeting: string)"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.plugin"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, call: &mut Call) -> Result<()> {
+            call.reply_struct(Reply::parameters(Some(
+                serde_json::json!({ "greeting": "hello" }),
+            )))
+        }
+    }
+
+    fn request(service: &VarlinkService, method: &str) -> Result<Value> {
+        let mut w = vec![];
+        let req = format!("{{\"method\":\"{}\"}}\0", method);
+        service.handle(&mut req.as_bytes(), &mut w, None)?;
+        w.pop();
+        from_slice(&w).map_err(map_context!())
+    }
+
+    let mut service = VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![],
+    );
+
+    assert!(service.add_interface(Box::new(PluginInterface)).is_none());
+    let info = request(&service, "org.varlink.service.GetInfo")?;
+    assert_eq!(
+        info["parameters"]["interfaces"],
+        serde_json::json!(["org.varlink.service", "org.example.plugin"])
+    );
+    let reply = request(&service, "org.example.plugin.Hello")?;
+    assert_eq!(reply["parameters"]["greeting"], "hello");
+
+    // adding it again replaces it
+    assert!(service.add_interface(Box::new(PluginInterface)).is_some());
+    let info = request(&service, "org.varlink.service.GetInfo")?;
+    assert_eq!(
+        info["parameters"]["interfaces"].as_array().unwrap().len(),
+        2
+    );
+
+    assert!(service.remove_interface("org.example.plugin").is_some());
+    assert!(service.remove_interface("org.example.plugin").is_none());
+    let info = request(&service, "org.varlink.service.GetInfo")?;
+    assert_eq!(
+        info["parameters"]["interfaces"],
+        serde_json::json!(["org.varlink.service"])
+    );
+    let reply = request(&service, "org.example.plugin.Hello")?;
+    assert_eq!(reply["error"], "org.varlink.service.InterfaceNotFound");
+    Ok(())
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_span() -> Result<()> {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    #[derive(Default)]
+    struct Fields(HashMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().into(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().into(), value.into());
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<(&'static str, Fields)>>>,
+        events: Arc<Mutex<Vec<HashMap<String, String>>>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = Fields::default();
+            span.record(&mut fields);
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata().name(), fields));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.events.lock().unwrap().push(fields.0);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    let service = VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![],
+    );
+
+    let recorder = Recorder::default();
+    let mut w = vec![];
+    tracing::subscriber::with_default(recorder.clone(), || {
+        service.handle(
+            &mut concat!(
+                r#"{"method":"org.varlink.service.GetInfo"}"#,
+                "\0",
+                r#"{"method":"org.varlink.service.GetInfo","x-request-id":"4711"}"#,
+                "\0"
+            )
+            .as_bytes(),
+            &mut w,
+            None,
+        )
+    })?;
+
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(spans.len(), 2);
+    let (name, fields) = &spans[0];
+    assert_eq!(*name, "varlink_call");
+    let fields = &fields.0;
+    assert_eq!(fields["interface"], "org.varlink.service");
+    assert_eq!(fields["method"], "org.varlink.service.GetInfo");
+    assert_eq!(fields["oneway"], "false");
+    assert!(!fields.contains_key("request_id"));
+    assert_eq!(spans[1].1 .0["request_id"], "4711");
+
+    let events = recorder.events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events[0].contains_key("duration_us"));
+    assert!(!events[0].contains_key("error"));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_with_fd() -> Result<()> {
+    use std::io::BufReader;
+    use std::os::unix::io::{AsRawFd, OwnedFd};
+    use std::os::unix::net::UnixStream;
+
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+
+    let child = thread::spawn(move || -> Result<()> {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        let mut writer = &server;
+        service.handle(&mut BufReader::new(&server), &mut writer, None)?;
+        Ok(())
+    });
+
+    let fd = client.as_raw_fd();
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    assert_eq!(connection.read().unwrap().address(), format!("fd:{}", fd));
+
+    let info = OrgVarlinkServiceClient::new(connection.clone()).get_info()?;
+    assert_eq!(info.product, "test service");
+    assert_eq!(
+        *connection.write().unwrap().reconnect().unwrap_err().kind(),
+        ErrorKind::OperationNotSupported("reconnect".into())
+    );
+
+    // closing the connection ends `handle()` on the other side
+    drop(connection);
+    child.join().expect("Error joining thread")
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ping() -> Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::io::OwnedFd;
+    use std::os::unix::net::UnixStream;
+
+    // answer a single request, then close the connection
+    fn serve(server: UnixStream) -> thread::JoinHandle<Result<()>> {
+        thread::spawn(move || -> Result<()> {
+            let service = VarlinkService::new(
+                "org.varlink",
+                "test service",
+                "0.1",
+                "http://varlink.org",
+                vec![],
+            );
+            let mut request = Vec::new();
+            BufReader::new(&server)
+                .read_until(0, &mut request)
+                .map_err(map_context!())?;
+            let mut writer = &server;
+            service.handle(&mut request.as_slice(), &mut writer, None)?;
+            Ok(())
+        })
+    }
+
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+    let child = serve(server);
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    assert!(connection.write().unwrap().ping());
+    child.join().expect("Error joining thread")?;
+    assert!(!connection.write().unwrap().ping());
+
+    // the reply is limited like any other
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+    let child = serve(server);
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    connection.write().unwrap().set_max_message_size(Some(16));
+    assert!(!connection.write().unwrap().ping());
+    child.join().expect("Error joining thread")?;
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interfaces() -> Result<()> {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::io::OwnedFd;
+    use std::os::unix::net::UnixStream;
+
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+
+    // answer requests until the connection is closed, counting them
+    let child = thread::spawn(move || -> Result<usize> {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        let mut reader = BufReader::new(&server);
+        let mut writer = &server;
+        let mut requests = 0;
+        loop {
+            let mut request = Vec::new();
+            reader.read_until(0, &mut request).map_err(map_context!())?;
+            if request.is_empty() {
+                return Ok(requests);
+            }
+            requests += 1;
+            service.handle(&mut request.as_slice(), &mut writer, None)?;
+        }
+    });
+
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    {
+        let mut conn = connection.write().unwrap();
+        assert_eq!(conn.interfaces()?, vec!["org.varlink.service"]);
+        assert_eq!(conn.interfaces()?, vec!["org.varlink.service"]);
+        conn.refresh();
+        assert_eq!(conn.interfaces()?, vec!["org.varlink.service"]);
+
+        // the reply is limited like any other
+        conn.refresh();
+        conn.set_max_message_size(Some(16));
+        assert_eq!(
+            *conn.interfaces().unwrap_err().kind(),
+            ErrorKind::MessageTooLarge
+        );
+
+        // the connection is kept and the next call drops the rest of the failed reply
+        assert!(!conn.is_busy());
+        conn.set_max_message_size(None);
+        assert_eq!(conn.interfaces()?, vec!["org.varlink.service"]);
+    }
+    drop(connection);
+
+    assert_eq!(child.join().expect("Error joining thread")?, 4);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_into_replies() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::io::OwnedFd;
+    use std::os::unix::net::UnixStream;
+
+    // reply to the request with `count` replies, the last one without `continues`,
+    // then close the connection
+    fn serve(stream: UnixStream, count: usize, last: bool) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let mut request = Vec::new();
+            BufReader::new(&stream).read_until(0, &mut request).unwrap();
+            let mut writer = &stream;
+            for i in 0..count {
+                let continues = !last || i + 1 < count;
+                let reply = json!({ "continues": continues, "parameters": { "i": i } });
+                writer.write_all(format!("{}\0", reply).as_bytes()).unwrap();
+            }
+        })
+    }
+
+    let call = |connection: &Arc<RwLock<Connection>>| {
+        MethodCall::<Value, Value, Error>::new(
+            connection.clone(),
+            "org.example.more.Count",
+            json!({}),
+        )
+    };
+
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+    let server = serve(server, 3, true);
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    let replies = call(&connection).more_owned().collect::<Result<Vec<_>>>()?;
+    assert_eq!(
+        replies,
+        vec![json!({"i": 0}), json!({"i": 1}), json!({"i": 2})]
+    );
+    assert!(!connection.read().unwrap().is_busy());
+    server.join().unwrap();
+
+    // the service closes the connection in the middle of the replies
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+    let server = serve(server, 2, false);
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    let mut replies = call(&connection);
+    replies.more()?;
+    assert_eq!(replies.next().unwrap()?, json!({"i": 0}));
+    let rest = replies.into_replies().collect::<Vec<_>>();
+    assert_eq!(rest.len(), 2);
+    assert_eq!(*rest[0].as_ref().unwrap(), json!({"i": 1}));
+    assert_eq!(
+        *rest[1].as_ref().unwrap_err().kind(),
+        ErrorKind::ConnectionClosed
+    );
+    server.join().unwrap();
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_unix_socket_access() -> Result<()> {
+    use std::fs;
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let dir = tempfile::tempdir().map_err(map_context!())?;
+    let path = dir.path().join("test_unix_socket_access");
+    let path = path.to_str().unwrap();
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+
+    let _listener = Listener::new(&format!("unix:{};mode=0640;uid={};gid={}", path, uid, gid))?;
+    let metadata = fs::metadata(path).map_err(map_context!())?;
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+    assert_eq!(metadata.uid(), uid);
+    assert_eq!(metadata.gid(), gid);
+
+    for params in &[
+        "mode=rw",
+        "uid=nobody",
+        "group=no-such-group-for-varlink",
+        "gorup=wheel",
+        "mode",
+    ] {
+        let e = Listener::new(&format!("unix:{};{}", path, params)).unwrap_err();
+        assert_eq!(*e.kind(), ErrorKind::InvalidAddress, "{}", params);
+    }
+
+    // changing the group to another one needs privileges
+    if uid == 0 {
+        let _listener = Listener::new(&format!("unix:{};gid=1", path))?;
+        assert_eq!(fs::metadata(path).map_err(map_context!())?.gid(), 1);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_transports() {
+    #[allow(dead_code)]
+    fn assert_unsupported(address: &str, operation: &str) {
+        let kind = ErrorKind::OperationNotSupported(operation.into());
+        match Connection::with_address(address) {
+            Err(e) => assert_eq!(*e.kind(), kind),
+            Ok(_) => panic!("connected to {}", address),
+        }
+        assert_eq!(*Listener::new(address).unwrap_err().kind(), kind);
+    }
+
+    #[cfg(not(all(feature = "vsock", target_os = "linux")))]
+    assert_unsupported("vsock:2:1234", "vsock");
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    assert_unsupported("unix:@org.example.unsupported", "abstract unix sockets");
+    #[cfg(not(windows))]
+    assert_unsupported(r"npipe:\\.\pipe\org.example.unsupported", "npipe");
+}
+
+#[cfg(windows)]
+#[test]
+fn test_npipe() -> Result<()> {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let address = format!(
+        r"npipe:\\.\pipe\org.varlink.test_npipe.{}",
+        std::process::id()
+    );
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_listening = stop.clone();
+    let server_address = address.clone();
+    let server = thread::spawn(move || -> Result<()> {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        listen(
+            service,
+            &server_address,
+            &ListenConfig {
+                stop_listening: Some(stop_listening),
+                ..Default::default()
+            },
+        )
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let connection = Connection::with_address(&address)?;
+    let mut client = OrgVarlinkServiceClient::new(connection.clone());
+    assert_eq!(client.get_info()?.product, "test service");
+
+    // another client, while the first one is still connected
+    let other = Connection::with_address(&address)?;
+    assert_eq!(
+        OrgVarlinkServiceClient::new(other).get_info()?.product,
+        "test service"
+    );
+    assert_eq!(client.get_info()?.product, "test service");
+
+    // the pipe belongs to the running service
+    assert!(Listener::new(&address).is_err());
+    drop(client);
+    drop(connection);
+
+    stop.store(true, Ordering::SeqCst);
+    server.join().expect("Error joining thread")?;
+
+    assert_eq!(
+        *Connection::with_address(&address).err().unwrap().kind(),
+        ErrorKind::AddressNotFound
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reply_deserialization_error() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::io::OwnedFd;
+    use std::os::unix::net::UnixStream;
+
+    let (server, client) = UnixStream::pair().map_err(map_context!())?;
+
+    // a peer replying to the first GetInfo with the wrong fields
+    let child = thread::spawn(move || -> Result<()> {
+        let mut reader = BufReader::new(&server);
+        for reply in [
+            &b"{\"parameters\":{\"vendor\":\"org.varlink\",\"products\":[]}}\0"[..],
+            b"{\"parameters\":{\"vendor\":\"org.varlink\",\"product\":\"test\",\
+              \"version\":\"1\",\"url\":\"\",\"interfaces\":[]}}\0",
+        ] {
+            let mut request = Vec::new();
+            reader
+                .read_until(b'\0', &mut request)
+                .map_err(map_context!())?;
+            (&server).write_all(reply).map_err(map_context!())?;
+        }
+        Ok(())
+    });
+
+    let connection = Connection::with_fd(OwnedFd::from(client))?;
+    let mut client = OrgVarlinkServiceClient::new(connection);
+    let e = client.get_info().unwrap_err();
+    match e.kind() {
+        ErrorKind::SerdeJsonDe(json) => assert!(json.contains(r#""products":[]"#), "{}", json),
+        kind => panic!("unexpected error {:?}", kind),
+    }
+    assert!(e.to_string().contains(r#""products":[]"#));
+
+    // the invalid reply was read completely, so the connection can still be used
+    assert_eq!(&client.get_info()?.product, "test");
+    child.join().expect("Error joining thread")
+}
+
+#[test]
+fn test_batch_oneway() -> Result<()> {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counts {
+        bytes: AtomicUsize,
+        writes: AtomicUsize,
+        flushes: AtomicUsize,
+    }
+
+    struct CountingWriter(Arc<Counts>);
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.writes.fetch_add(1, Ordering::SeqCst);
+            self.0.bytes.fetch_add(buf.len(), Ordering::SeqCst);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flushes.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    let counts = Arc::new(Counts::default());
+    let mut conn = Connection::default();
+    conn.reader = Some(BufReader::new(Box::new(io::empty())));
+    conn.writer = Some(Box::new(CountingWriter(counts.clone())));
+    let connection = Arc::new(RwLock::new(conn));
+
+    connection.write().unwrap().begin_batch();
+
+    for i in 0..1000 {
+        MethodCall::<_, serde_json::Value, Error>::new(
+            connection.clone(),
+            "org.example.batch.Send",
+            serde_json::json!({ "i": i }),
+        )
+        .oneway()?;
+    }
+
+    assert_eq!(counts.writes.load(Ordering::SeqCst), 0);
+    assert_eq!(counts.flushes.load(Ordering::SeqCst), 0);
+
+    connection.write().unwrap().end_batch()?;
+
+    assert_eq!(counts.writes.load(Ordering::SeqCst), 1);
+    assert_eq!(counts.flushes.load(Ordering::SeqCst), 1);
+    assert!(counts.bytes.load(Ordering::SeqCst) > 1000 * "{\"oneway\":true}".len());
+
+    // without a batch, every call is flushed
+    MethodCall::<_, serde_json::Value, Error>::new(
+        connection.clone(),
+        "org.example.batch.Send",
+        serde_json::json!({ "i": 1000 }),
+    )
+    .oneway()?;
+    assert_eq!(counts.flushes.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_batch_reconnect() -> Result<()> {
+    use std::os::unix::net::UnixListener;
+    use std::sync::mpsc;
+
+    let path = "test_batch_reconnect";
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path).map_err(map_context!())?;
+    let (tx, rx) = mpsc::channel();
+
+    // a service, which drops the first connection and records the requests of the second
+    let server = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        drop(listener.accept().unwrap());
+        tx.send(()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut br = BufReader::new(stream.try_clone().unwrap());
+        let mut requests = Vec::new();
+        loop {
+            let mut buf = Vec::new();
+            br.read_until(0, &mut buf).unwrap();
+            if buf.is_empty() {
+                break;
+            }
+            service
+                .handle(&mut buf.as_slice(), &mut stream, None)
+                .unwrap();
+            requests.push(String::from_utf8(buf).unwrap());
+        }
+        requests
+    });
+
+    let connection = Connection::with_address(&format!("unix:{}", path))?;
+    connection.write().unwrap().set_auto_reconnect(true);
+    let mut client = OrgVarlinkServiceClient::new(connection.clone());
+    rx.recv().unwrap();
+
+    connection.write().unwrap().begin_batch();
+    for i in 0..2 {
+        MethodCall::<_, serde_json::Value, Error>::new(
+            connection.clone(),
+            "org.example.batch.Send",
+            serde_json::json!({ "i": i }),
+        )
+        .oneway()?;
+    }
+
+    // the batch is not sent again on the new connection, and the call fails
+    let e = client.get_info().unwrap_err();
+    assert!(matches!(
+        e.kind(),
+        ErrorKind::BrokenPipe | ErrorKind::ConnectionClosed
+    ));
+    assert!(format!("{:?}", e).contains("batch were lost"));
+    assert!(connection
+        .read()
+        .unwrap()
+        .batch
+        .as_ref()
+        .is_some_and(Vec::is_empty));
+
+    assert_eq!(&client.get_info()?.product, "test service");
+    connection.write().unwrap().end_batch()?;
+    drop(client);
+    drop(connection);
+
+    let requests = server.join().unwrap();
+    let _ = std::fs::remove_file(path);
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].contains("org.varlink.service.GetInfo"));
+    Ok(())
+}
+
+#[test]
+fn test_batch_errors() -> Result<()> {
+    use std::io;
+
+    struct BrokenWriter;
+
+    impl Write for BrokenWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut conn = Connection::default();
+    conn.reader = Some(BufReader::new(Box::new(io::empty())));
+    conn.writer = Some(Box::new(Vec::new()));
+    let connection = Arc::new(RwLock::new(conn));
+    let send = |i| {
+        MethodCall::<_, serde_json::Value, Error>::new(
+            connection.clone(),
+            "org.example.batch.Send",
+            serde_json::json!({ "i": i }),
+        )
+    };
+
+    // a busy connection keeps the batch
+    connection.write().unwrap().begin_batch();
+    send(0).oneway()?;
+    let writer = connection.write().unwrap().writer.take();
+    assert_eq!(
+        *connection.write().unwrap().end_batch().unwrap_err().kind(),
+        ErrorKind::ConnectionBusy
+    );
+    assert!(connection.read().unwrap().batch.is_some());
+    connection.write().unwrap().writer = writer;
+    connection.write().unwrap().end_batch()?;
+    assert!(connection.read().unwrap().batch.is_none());
+
+    // a failed write leaves the reader and writer with the connection
+    connection.write().unwrap().writer = Some(Box::new(BrokenWriter));
+    connection.write().unwrap().begin_batch();
+    send(1).oneway()?;
+    assert!(send(2).call().is_err());
+    {
+        let conn = connection.read().unwrap();
+        assert!(conn.reader.is_some() && conn.writer.is_some());
+        assert!(conn.batch.as_ref().is_some_and(Vec::is_empty));
+    }
+    // the connection can't be reconnected, so the next call fails writing again
+    assert_eq!(*send(3).call().unwrap_err().kind(), ErrorKind::BrokenPipe);
+    Ok(())
+}
+
+#[cfg(feature = "introspection")]
+#[test]
+fn test_method_map() {
+    struct PingInterface;
+
+    impl Interface for PingInterface {
+        fn get_description(&self) -> &'static str {
+            "interface org.example.ping
+method Ping(ping: string) -> (pong: string)
+method Stop() -> ()
+"
+        }
+
+        fn get_name(&self) -> &'static str {
+            "org.example.ping"
+        }
+
+        fn call_upgraded(&self, _call: &mut Call, _bufreader: &mut dyn BufRead) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+
+        fn call(&self, _call: &mut Call) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    let service = VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(PingInterface)],
+    );
+
+    let map = service.method_map();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["org.example.ping"], vec!["Ping", "Stop"]);
+    assert_eq!(
+        map["org.varlink.service"],
+        vec!["GetInfo", "GetInterfaceDescription"]
+    );
+}
+
+#[test]
+fn test_address_from_args_or_env() {
+    std::env::remove_var("VARLINK_ADDRESS");
+    assert_eq!(address_from_args_or_env(&["prog", "--client"]), None);
+
+    assert_eq!(
+        address_from_args_or_env(&["prog", "--varlink=unix:/run/org.example.ftl"]),
+        Some("unix:/run/org.example.ftl".into())
+    );
+    assert_eq!(
+        address_from_args_or_env(&["prog", "--varlink", "tcp:127.0.0.1:12345"]),
+        Some("tcp:127.0.0.1:12345".into())
+    );
+
+    std::env::set_var("VARLINK_ADDRESS", "unix:@org.example.env");
+    assert_eq!(
+        address_from_args_or_env(&["prog"]),
+        Some("unix:@org.example.env".into())
+    );
+    // the command line wins
+    assert_eq!(
+        address_from_args_or_env(&["prog", "--varlink=unix:@org.example.arg"]),
+        Some("unix:@org.example.arg".into())
+    );
+    std::env::remove_var("VARLINK_ADDRESS");
+}
+
+/// The serde attributes generated with `GeneratorOptions::skip_empty_collections`
+#[test]
+fn test_skip_empty_collections() {
+    #[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug, PartialEq)]
+    struct Tags {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        names: Vec<String>,
+        #[serde(default, skip_serializing_if = "crate::StringHashSet::is_empty")]
+        set: StringHashSet,
+        #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+        map: StringHashMap<i64>,
+    }
+
+    let empty = Tags {
+        names: Vec::new(),
+        set: StringHashSet::new(),
+        map: StringHashMap::new(),
+    };
+    let wire = serde_json::to_string(&empty).unwrap();
+    assert_eq!(wire, "{}");
+    assert_eq!(serde_json::from_str::<Tags>(&wire).unwrap(), empty);
+
+    let mut tags = empty;
+    tags.names.push("a".into());
+    tags.set.insert("b".into());
+    let wire = serde_json::to_string(&tags).unwrap();
+    assert_eq!(wire, r#"{"names":["a"],"set":{"b":{}}}"#);
+    assert_eq!(serde_json::from_str::<Tags>(&wire).unwrap(), tags);
+}
+
+#[test]
+fn test_strict_string_hash_set() {
+    let set = from_slice::<StrictStringHashSet>(br#"{"a":{},"b":null}"#).unwrap();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains("a") && set.contains("b"));
+    assert_eq!(
+        serde_json::to_string(&set).unwrap().len(),
+        r#"{"a":{},"b":{}}"#.len()
+    );
+
+    let e = from_slice::<StrictStringHashSet>(br#"{"a":1}"#).unwrap_err();
+    assert!(e.to_string().contains("set element `a`"), "{}", e);
+    assert!(from_slice::<StrictStringHashSet>(br#"{"a":{"b":{}}}"#).is_err());
+
+    // the lenient set ignores the values
+    assert!(from_slice::<StringHashSet>(br#"{"a":1}"#)
+        .unwrap()
+        .contains("a"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_register_transport() -> Result<()> {
+    use std::os::unix::net::UnixStream;
+
+    // every connection to `mem:` gets its own service thread on the other end of a socket pair
+    register_transport("mem", |_addr: &str| -> Result<Box<dyn Stream>> {
+        let (client, server) = UnixStream::pair().map_err(map_context!())?;
+        thread::spawn(move || {
+            let service = VarlinkService::new(
+                "org.varlink",
+                "mem service",
+                "0.1",
+                "http://varlink.org",
+                vec![],
+            );
+            let mut reader = BufReader::new(server.try_clone().unwrap());
+            let mut writer = server;
+            let _ = service.handle(&mut reader, &mut writer, None);
+        });
+        Ok(Box::new(client))
+    });
+
+    let connection = Connection::with_address("mem:test")?;
+    assert_eq!(connection.read().unwrap().address(), "mem:test");
+
+    let info = OrgVarlinkServiceClient::new(connection).get_info()?;
+    assert_eq!(info.product, "mem service");
+
+    assert_eq!(
+        Connection::with_address("unknown:test")
+            .err()
+            .unwrap()
+            .kind(),
+        &ErrorKind::InvalidAddress
+    );
+    Ok(())
+}
+
+#[test]
+fn test_connect_errors() -> Result<()> {
+    use std::net::TcpListener;
+
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map_err(map_context!())?
+        .port();
+    let e = Connection::with_address(&format!("tcp:127.0.0.1:{}", port))
+        .err()
+        .unwrap();
+    assert_eq!(*e.kind(), ErrorKind::ConnectionRefused);
+    assert_eq!(e.to_string(), "Connection refused");
+
+    let e = Connection::with_address("unix:/nonexistent/org.example.missing")
+        .err()
+        .unwrap();
+    assert_eq!(*e.kind(), ErrorKind::AddressNotFound);
+    Ok(())
+}
+
+#[test]
+fn test_set_timeout() -> Result<()> {
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    // a server, which never replies on the first connection, but on the second
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(map_context!())?;
+    let address = format!("tcp:{}", listener.local_addr().map_err(map_context!())?);
+    let server = thread::spawn(move || -> std::io::Result<_> {
+        use std::io::{BufRead, BufReader, Write};
+
+        let (silent, _) = listener.accept()?;
+        let (stream, _) = listener.accept()?;
+        let mut request = Vec::new();
+        BufReader::new(&stream).read_until(0, &mut request)?;
+        (&stream).write_all(b"{\"parameters\":{}}\0")?;
+        Ok((silent, stream))
+    });
+
+    let connection = Connection::with_address(&address)?;
+    connection
+        .write()
+        .unwrap()
+        .set_timeout(Some(Duration::from_millis(200)))?;
+
+    let start = Instant::now();
+    let err = MethodCall::<Value, Value, Error>::new(
+        connection.clone(),
+        "org.example.silent.Wait",
+        json!({}),
+    )
+    .call()
+    .unwrap_err();
+    assert_eq!(*err.kind(), ErrorKind::Timeout);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // with auto reconnect, the next call on the same connection connects again and keeps
+    // the timeout
+    connection.write().unwrap().set_auto_reconnect(true);
+    assert_eq!(
+        MethodCall::<Value, Value, Error>::new(
+            connection.clone(),
+            "org.example.silent.Wait",
+            json!({}),
+        )
+        .call()?,
+        json!({})
+    );
+    assert_eq!(
+        connection.read().unwrap().timeout,
+        Some(Duration::from_millis(200))
+    );
+
+    let _streams = server.join().unwrap().map_err(map_context!())?;
+
+    let mut no_socket = Connection::default();
+    assert_eq!(
+        *no_socket.set_timeout(None).unwrap_err().kind(),
+        ErrorKind::OperationNotSupported("set_timeout".into())
+    );
+    Ok(())
+}
+
+#[test]
+fn test_call_with_timeout() -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    // a server, which replies to every request after 500ms with the number of the connection
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(map_context!())?;
+    let address = format!("tcp:{}", listener.local_addr().map_err(map_context!())?);
+    let server = thread::spawn(move || {
+        for n in 0..3 {
+            let (stream, _) = listener.accept().unwrap();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(&stream);
+                let mut writer = &stream;
+                let mut request = Vec::new();
+                let reply = format!("{{\"parameters\":{{\"connection\":{}}}}}\0", n);
+                while reader.read_until(0, &mut request).unwrap_or(0) > 0 {
+                    thread::sleep(Duration::from_millis(500));
+                    if writer.write_all(reply.as_bytes()).is_err() {
+                        break;
+                    }
+                    request.clear();
+                }
+            });
+        }
+    });
+
+    let call = |connection: &Arc<RwLock<Connection>>| {
+        MethodCall::<Value, Value, Error>::new(
+            connection.clone(),
+            "org.example.slow.Wait",
+            json!({}),
+        )
+    };
+
+    let connection = Connection::with_address(&address)?;
+    let start = Instant::now();
+    let err = call(&connection)
+        .call_with_timeout(Duration::from_millis(100))
+        .unwrap_err();
+    assert_eq!(*err.kind(), ErrorKind::Timeout);
+    assert!(start.elapsed() < Duration::from_millis(500));
+    assert_eq!(connection.read().unwrap().timeout, None);
+
+    // the same connection can be used again, without getting the late reply of the first call
+    assert_eq!(call(&connection).call()?, json!({"connection": 0}));
+    assert_eq!(connection.read().unwrap().timeout, None);
+
+    // with auto reconnect, the next call connects again instead
+    connection.write().unwrap().set_auto_reconnect(true);
+    call(&connection)
+        .call_with_timeout(Duration::from_millis(100))
+        .unwrap_err();
+    assert_eq!(call(&connection).call()?, json!({"connection": 1}));
+
+    let connection = Connection::with_address(&address)?;
+    connection
+        .write()
+        .unwrap()
+        .set_timeout(Some(Duration::from_secs(30)))?;
+    assert_eq!(
+        call(&connection).call_with_timeout(Duration::from_secs(5))?,
+        json!({"connection": 2})
+    );
+    assert_eq!(
+        connection.read().unwrap().timeout,
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(call(&connection).call()?, json!({"connection": 2}));
+
+    server.join().unwrap();
+    Ok(())
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls() -> Result<()> {
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let cert = include_bytes!("../testdata/tls-cert.pem");
+    let key = include_bytes!("../testdata/tls-key.pem");
+
+    // find a free port
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map_err(map_context!())?
+        .port();
+    let address = format!("tcp+tls:127.0.0.1:{}", port);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_listening = stop.clone();
+    let server_address = address.clone();
+    let server = thread::spawn(move || -> Result<()> {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        listen(
+            service,
+            &server_address,
+            &ListenConfig {
+                stop_listening: Some(stop_listening),
+                tls: Some(tls_server_config(cert, key)?),
+                ..Default::default()
+            },
+        )
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let connection = Connection::with_tls(&address, tls_client_config(cert)?)?;
+    let mut client = OrgVarlinkServiceClient::new(connection.clone());
+    assert_eq!(client.get_info()?.product, "test service");
+    connection.write().unwrap().reconnect()?;
+    assert_eq!(client.get_info()?.product, "test service");
+    drop(client);
+    drop(connection);
+
+    // a certificate, which is not trusted, fails the handshake
+    let connection = Connection::with_tls(&address, tls_client_config(b"")?)?;
+    assert!(OrgVarlinkServiceClient::new(connection).get_info().is_err());
+
+    assert_eq!(
+        *Connection::with_address(&address).err().unwrap().kind(),
+        ErrorKind::OperationNotSupported("tcp+tls".into())
+    );
+
+    stop.store(true, Ordering::SeqCst);
+    server.join().expect("Error joining thread")
+}
+
+/// A read waiting for data on one half of a split `TlsStream` doesn't block the other half.
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_split() -> Result<()> {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    let cert = include_bytes!("../testdata/tls-cert.pem");
+    let key = include_bytes!("../testdata/tls-key.pem");
+
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(map_context!())?;
+    let addr = listener.local_addr().map_err(map_context!())?;
+    let config = tls_server_config(cert, key)?;
+
+    // echo everything back, until the client closes the session
+    let server = thread::spawn(move || -> Result<()> {
+        let (stream, _) = listener.accept().map_err(map_context!())?;
+        let mut stream = TlsStream::accept(Box::new(stream), config)?;
+        let mut buf = [0; 64];
+        loop {
+            let n = stream.read(&mut buf).map_err(map_context!())?;
+            if n == 0 {
+                return Ok(());
+            }
+            stream.write_all(&buf[..n]).map_err(map_context!())?;
+        }
+    });
+
+    let stream = TcpStream::connect(addr).map_err(map_context!())?;
+    let mut stream = TlsStream::connect(Box::new(stream), "127.0.0.1", tls_client_config(cert)?)?;
+    stream.set_timeout(Some(time::Duration::from_secs(10)))?;
+    let (mut reader, mut writer) = stream.split()?;
+
+    let child = thread::spawn(move || -> Result<Vec<u8>> {
+        let mut buf = vec![0; 4];
+        reader.read_exact(&mut buf).map_err(map_context!())?;
+        Ok(buf)
+    });
+
+    // give the reader time to wait for data
+    thread::sleep(time::Duration::from_millis(200));
+    writer.write_all(b"ping").map_err(map_context!())?;
+    writer.flush().map_err(map_context!())?;
+    assert_eq!(child.join().expect("Error joining thread")?, b"ping");
+
+    stream.shutdown()?;
+    server.join().expect("Error joining thread")
+}
+
+/// Like with the other transports, a zero timeout is an error.
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+#[test]
+fn test_vsock_timeout() -> Result<()> {
+    use std::os::unix::io::FromRawFd;
+
+    let fd = unsafe { libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        eprintln!("skipping test_vsock_timeout, no vsock support");
+        return Ok(());
+    }
+    let mut stream = unsafe { VsockStream::from_raw_fd(fd) };
+    assert_eq!(
+        *Stream::set_timeout(&mut stream, Some(time::Duration::ZERO))
+            .unwrap_err()
+            .kind(),
+        ErrorKind::Io(std::io::ErrorKind::InvalidInput)
+    );
+    Stream::set_timeout(&mut stream, Some(time::Duration::from_nanos(1)))?;
+    Stream::set_timeout(&mut stream, None)?;
+    Ok(())
+}
+
+/// Loops back over vsock, if the kernel supports local vsock connections.
+#[cfg(all(feature = "vsock", target_os = "linux"))]
+#[test]
+fn test_vsock() -> Result<()> {
+    for invalid in ["vsock:1", "vsock:local:23457", "vsock:1:port"] {
+        match Connection::with_address(invalid) {
+            Err(e) => assert_eq!(*e.kind(), ErrorKind::InvalidAddress),
+            Ok(_) => panic!("`{}` should be invalid", invalid),
+        }
+    }
+
+    // VMADDR_CID_LOCAL
+    let address = "vsock:1:23457";
+
+    if let Err(e) = Listener::new(address) {
+        eprintln!("skipping test_vsock, no local vsock: {}", e);
+        return Ok(());
+    }
+
+    let server = thread::spawn(move || {
+        let service = VarlinkService::new(
+            "org.varlink",
+            "test service",
+            "0.1",
+            "http://varlink.org",
+            vec![],
+        );
+        if let Err(e) = listen(
+            service,
+            address,
+            &ListenConfig {
+                idle_timeout: 1,
+                ..Default::default()
+            },
+        ) {
+            if *e.kind() != ErrorKind::Timeout {
+                panic!("Error listen: {:#?}", e);
+            }
+        }
+    });
+
+    // give server time to start
+    thread::sleep(time::Duration::from_millis(500));
+
+    let conn = Connection::with_address(address)?;
+    let info = OrgVarlinkServiceClient::new(conn).get_info()?;
+    assert_eq!(&info.product, "test service");
+
+    server.join().unwrap();
+    Ok(())
+}
@@ -0,0 +1,375 @@
+//! TLS sessions for `tcp+tls:HOST:PORT` addresses
+//!
+//! The sessions are provided by `rustls` with the `ring` crypto provider. The client needs
+//! a [`ClientConfig`] trusting the certificate of the service, see [`tls_client_config`],
+//! and the service a [`ServerConfig`] with its certificate and private key,
+//! see [`tls_server_config`].
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{
+    ClientConfig, ClientConnection, Connection, RootCertStore, ServerConfig, ServerConnection,
+};
+
+use crate::error::*;
+use crate::stream::{PeerCredentials, Stream};
+
+/// The TLS session shared by both halves of a split [`TlsStream`]
+///
+/// The socket is only read and written without holding the `session` lock, except during
+/// the handshake, so a read waiting for data doesn't stop the other half from writing.
+struct Session {
+    session: Mutex<Connection>,
+    /// held while writing records to the transport, which keeps them in order
+    send: Mutex<()>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Session {
+    fn new(session: Connection) -> Arc<Self> {
+        Arc::new(Session {
+            session: Mutex::new(session),
+            send: Mutex::new(()),
+        })
+    }
+
+    /// Lock the session, after doing the handshake with the first caller.
+    fn lock(&self, transport: &mut Box<dyn Stream>) -> io::Result<MutexGuard<'_, Connection>> {
+        let mut session = lock(&self.session);
+        if session.is_handshaking() {
+            session.complete_io(transport)?;
+        }
+        Ok(session)
+    }
+
+    /// Write the records queued in the session to `transport`.
+    fn send(&self, transport: &mut Box<dyn Stream>) -> io::Result<()> {
+        let _send = lock(&self.send);
+        let mut records = Vec::new();
+        {
+            let mut session = lock(&self.session);
+            while session.wants_write() {
+                session.write_tls(&mut records)?;
+            }
+        }
+        transport.write_all(&records)?;
+        transport.flush()
+    }
+}
+
+fn process_new_packets(session: &mut Connection) -> io::Result<()> {
+    session
+        .process_new_packets()
+        .map(|_| ())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// The reading half of a split [`TlsStream`]
+struct ReadHalf {
+    session: Arc<Session>,
+    transport: Box<dyn Stream>,
+    /// records read from the transport, which the session didn't take yet
+    records: Vec<u8>,
+    eof: bool,
+}
+
+/// Hand the `records` read so far to the session, as long as it has no plaintext left.
+fn feed(session: &mut Connection, records: &mut Vec<u8>, eof: bool) -> io::Result<()> {
+    while session.wants_read() && !records.is_empty() {
+        let n = session.read_tls(&mut records.as_slice())?;
+        records.drain(..n);
+        process_new_packets(session)?;
+    }
+    if eof && records.is_empty() && session.wants_read() {
+        session.read_tls(&mut io::empty())?;
+        process_new_packets(session)?;
+    }
+    Ok(())
+}
+
+impl Read for ReadHalf {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let (read, wants_write) = {
+                let mut session = self.session.lock(&mut self.transport)?;
+                let fed = feed(&mut session, &mut self.records, self.eof);
+                let read = fed.and_then(|_| session.reader().read(buf));
+                (read, session.wants_write())
+            };
+            // e.g. an alert or a key update
+            if wants_write {
+                self.session.send(&mut self.transport)?;
+            }
+            match read {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                read => return read,
+            }
+
+            // wait for more records without holding the session
+            let mut records = [0; 16 * 1024];
+            match self.transport.read(&mut records)? {
+                0 => self.eof = true,
+                n => self.records.extend_from_slice(&records[..n]),
+            }
+        }
+    }
+}
+
+/// The writing half of a split [`TlsStream`]
+struct WriteHalf {
+    session: Arc<Session>,
+    transport: Box<dyn Stream>,
+}
+
+impl Write for WriteHalf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self
+            .session
+            .lock(&mut self.transport)?
+            .writer()
+            .write(buf)?;
+        self.session.send(&mut self.transport)?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.session.lock(&mut self.transport)?.writer().flush()?;
+        self.session.send(&mut self.transport)
+    }
+}
+
+/// A TLS session on top of another [`Stream`], usually a `TcpStream`
+///
+/// The handshake is done with the first read or write. Both halves returned by
+/// [`Stream::split`] share the session, but a read waiting for data doesn't delay
+/// a write from another thread.
+pub struct TlsStream {
+    reader: ReadHalf,
+    writer: WriteHalf,
+    /// a clone of the transport for the socket options and `shutdown()`
+    transport: Box<dyn Stream>,
+}
+
+impl TlsStream {
+    /// Start the client side of a TLS session on `stream` to the service `server_name`.
+    pub fn connect(
+        stream: Box<dyn Stream>,
+        server_name: &str,
+        config: Arc<ClientConfig>,
+    ) -> Result<Self> {
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|e| context!(e, ErrorKind::InvalidAddress))?;
+        let conn = ClientConnection::new(config, server_name)
+            .map_err(|e| context!(e, ErrorKind::Generic))?;
+        TlsStream::new(conn.into(), stream).map_err(map_context!())
+    }
+
+    /// Start the server side of a TLS session on `stream`.
+    ///
+    /// `stream` is shut down, if the session can't be set up.
+    pub fn accept(mut stream: Box<dyn Stream>, config: Arc<ServerConfig>) -> Result<Self> {
+        let accepted = stream
+            .try_clone()
+            .map_err(map_context!())
+            .and_then(|clone| {
+                let conn =
+                    ServerConnection::new(config).map_err(|e| context!(e, ErrorKind::Generic))?;
+                TlsStream::new(conn.into(), clone).map_err(map_context!())
+            });
+        if accepted.is_err() {
+            let _ = stream.shutdown();
+        }
+        accepted
+    }
+
+    fn new(session: Connection, mut stream: Box<dyn Stream>) -> io::Result<Self> {
+        let session = Session::new(session);
+        Ok(TlsStream {
+            writer: WriteHalf {
+                session: session.clone(),
+                transport: stream.try_clone()?,
+            },
+            transport: stream.try_clone()?,
+            reader: ReadHalf {
+                session,
+                transport: stream,
+                records: Vec::new(),
+                eof: false,
+            },
+        })
+    }
+}
+
+impl Read for TlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for TlsStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.transport.as_raw_fd()
+    }
+}
+
+impl Stream for TlsStream {
+    #[inline]
+    fn split(&mut self) -> Result<(Box<dyn Read + Send + Sync>, Box<dyn Write + Send + Sync>)> {
+        Ok((
+            Box::new(ReadHalf {
+                session: self.reader.session.clone(),
+                transport: self.reader.transport.try_clone().map_err(map_context!())?,
+                records: std::mem::take(&mut self.reader.records),
+                eof: self.reader.eof,
+            }),
+            Box::new(WriteHalf {
+                session: self.writer.session.clone(),
+                transport: self.writer.transport.try_clone().map_err(map_context!())?,
+            }),
+        ))
+    }
+
+    #[inline]
+    fn shutdown(&mut self) -> Result<()> {
+        // a reader blocked in the handshake holds the lock, the shutdown of the
+        // transport wakes it up without a `close_notify`
+        if let Ok(mut session) = self.reader.session.session.try_lock() {
+            session.send_close_notify();
+            drop(session);
+            let _ = self.reader.session.send(&mut self.transport);
+        }
+        self.transport.shutdown()
+    }
+
+    #[inline]
+    fn try_clone(&mut self) -> ::std::io::Result<Box<dyn Stream>> {
+        Ok(Box::new(TlsStream {
+            reader: ReadHalf {
+                session: self.reader.session.clone(),
+                transport: self.reader.transport.try_clone()?,
+                records: Vec::new(),
+                eof: false,
+            },
+            writer: WriteHalf {
+                session: self.writer.session.clone(),
+                transport: self.writer.transport.try_clone()?,
+            },
+            transport: self.transport.try_clone()?,
+        }))
+    }
+
+    #[inline]
+    fn set_nonblocking(&mut self, b: bool) -> Result<()> {
+        self.transport.set_nonblocking(b)
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.transport.set_timeout(timeout)
+    }
+
+    fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.transport.peer_credentials()
+    }
+}
+
+/// Connect to the `HOST:PORT` part of a `tcp+tls:` address.
+///
+/// The certificate of the service has to be valid for `HOST`.
+pub(crate) fn connect(addr: &str, config: Arc<ClientConfig>) -> Result<TlsStream> {
+    let (host, _port) = addr
+        .rsplit_once(':')
+        .ok_or(context!(ErrorKind::InvalidAddress))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let stream = TcpStream::connect(addr).map_err(crate::client::connect_error)?;
+    TlsStream::connect(Box::new(stream), host, config)
+}
+
+fn pem_error(e: rustls::pki_types::pem::Error) -> Error {
+    context!(e, ErrorKind::Io(io::ErrorKind::InvalidData))
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+/// Build a client configuration, which trusts the PEM encoded certificates in `root_certs`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # fn main() -> varlink::Result<()> {
+/// let config = varlink::tls_client_config(&std::fs::read("ca.pem").unwrap())?;
+/// let connection = varlink::Connection::with_tls("tcp+tls:varlink.example.org:12345", config)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn tls_client_config(root_certs: &[u8]) -> Result<Arc<ClientConfig>> {
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_slice_iter(root_certs) {
+        roots
+            .add(cert.map_err(pem_error)?)
+            .map_err(|e| context!(e, ErrorKind::Generic))?;
+    }
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| context!(e, ErrorKind::Generic))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Build a server configuration from the PEM encoded `cert_chain` and `private_key`.
+///
+/// The certificate of the service comes first in `cert_chain`.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # fn main() -> varlink::Result<()> {
+/// let config = varlink::ListenConfig {
+///     tls: Some(varlink::tls_server_config(
+///         &std::fs::read("cert.pem").unwrap(),
+///         &std::fs::read("key.pem").unwrap(),
+///     )?),
+///     ..Default::default()
+/// };
+/// # Ok(())
+/// # }
+/// ```
+pub fn tls_server_config(cert_chain: &[u8], private_key: &[u8]) -> Result<Arc<ServerConfig>> {
+    let certs = CertificateDer::pem_slice_iter(cert_chain)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(pem_error)?;
+    let key = PrivateKeyDer::from_pem_slice(private_key).map_err(pem_error)?;
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| context!(e, ErrorKind::Generic))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| context!(e, ErrorKind::Generic))?;
+    Ok(Arc::new(config))
+}
@@ -0,0 +1,239 @@
+//! `AF_VSOCK` sockets for `vsock:CID:PORT` addresses
+//!
+//! vsock connects virtual machines with their host. The CID addresses the machine,
+//! `2` is the host and `1` the local machine.
+
+use std::io::{self, Read, Write};
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::time::Duration;
+
+use crate::error::*;
+use crate::stream::Stream;
+
+/// Parse the `CID:PORT` part of a `vsock:` address.
+pub(crate) fn parse_address(addr: &str) -> Result<(u32, u32)> {
+    let addr = addr.split(';').next().unwrap_or(addr);
+    let (cid, port) = addr
+        .split_once(':')
+        .ok_or(context!(ErrorKind::InvalidAddress))?;
+    match (cid.parse(), port.parse()) {
+        (Ok(cid), Ok(port)) => Ok((cid, port)),
+        _ => Err(context!(ErrorKind::InvalidAddress)),
+    }
+}
+
+fn sockaddr(cid: u32, port: u32) -> libc::sockaddr_vm {
+    let mut addr: libc::sockaddr_vm = unsafe { mem::zeroed() };
+    addr.svm_family = libc::AF_VSOCK as libc::sa_family_t;
+    addr.svm_cid = cid;
+    addr.svm_port = port;
+    addr
+}
+
+fn socket() -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::socket(libc::AF_VSOCK, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+fn set_nonblocking(fd: RawFd, b: bool) -> io::Result<()> {
+    let flags = cvt(unsafe { libc::fcntl(fd, libc::F_GETFL) })?;
+    let flags = if b {
+        flags | libc::O_NONBLOCK
+    } else {
+        flags & !libc::O_NONBLOCK
+    };
+    cvt(unsafe { libc::fcntl(fd, libc::F_SETFL, flags) })?;
+    Ok(())
+}
+
+/// A connected vsock stream socket
+#[derive(Debug)]
+pub struct VsockStream(OwnedFd);
+
+impl VsockStream {
+    pub fn connect(cid: u32, port: u32) -> io::Result<Self> {
+        let fd = socket()?;
+        let addr = sockaddr(cid, port);
+        cvt(unsafe {
+            libc::connect(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_vm as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_vm>() as libc::socklen_t,
+            )
+        })?;
+        Ok(VsockStream(fd))
+    }
+
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(VsockStream(self.0.try_clone()?))
+    }
+
+    fn set_timeout(&self, option: libc::c_int, timeout: Option<Duration>) -> io::Result<()> {
+        let tv = match timeout {
+            // like `std::net::TcpStream`, a zero timeval would mean no timeout
+            Some(d) if d.is_zero() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "cannot set a 0 duration timeout",
+                ));
+            }
+            Some(d) => libc::timeval {
+                tv_sec: d.as_secs() as _,
+                // round up below a microsecond, which would be no timeout, too
+                tv_usec: d.subsec_micros().max(u32::from(d.as_secs() == 0)) as _,
+            },
+            None => libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+        };
+        cvt(unsafe {
+            libc::setsockopt(
+                self.0.as_raw_fd(),
+                libc::SOL_SOCKET,
+                option,
+                &tv as *const libc::timeval as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        })?;
+        Ok(())
+    }
+}
+
+impl Read for VsockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let ret = unsafe { libc::read(self.0.as_raw_fd(), buf.as_mut_ptr() as _, buf.len()) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ret as usize)
+    }
+}
+
+impl Write for VsockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let ret = unsafe { libc::write(self.0.as_raw_fd(), buf.as_ptr() as _, buf.len()) };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ret as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsRawFd for VsockStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for VsockStream {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        VsockStream(OwnedFd::from_raw_fd(fd))
+    }
+}
+
+impl IntoRawFd for VsockStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.0.into_raw_fd()
+    }
+}
+
+impl Stream for VsockStream {
+    #[inline]
+    fn split(&mut self) -> Result<(Box<dyn Read + Send + Sync>, Box<dyn Write + Send + Sync>)> {
+        Ok((
+            Box::new(VsockStream::try_clone(self).map_err(map_context!())?),
+            Box::new(VsockStream::try_clone(self).map_err(map_context!())?),
+        ))
+    }
+
+    #[inline]
+    fn shutdown(&mut self) -> Result<()> {
+        cvt(unsafe { libc::shutdown(self.0.as_raw_fd(), libc::SHUT_RDWR) })
+            .map_err(map_context!())?;
+        Ok(())
+    }
+
+    #[inline]
+    fn try_clone(&mut self) -> ::std::io::Result<Box<dyn Stream>> {
+        Ok(Box::new(VsockStream::try_clone(self)?))
+    }
+
+    #[inline]
+    fn set_nonblocking(&mut self, b: bool) -> Result<()> {
+        set_nonblocking(self.0.as_raw_fd(), b).map_err(map_context!())?;
+        Ok(())
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        VsockStream::set_timeout(self, libc::SO_RCVTIMEO, timeout).map_err(map_context!())?;
+        VsockStream::set_timeout(self, libc::SO_SNDTIMEO, timeout).map_err(map_context!())?;
+        Ok(())
+    }
+}
+
+/// A vsock socket listening for connections
+#[derive(Debug)]
+pub struct VsockListener(OwnedFd);
+
+impl VsockListener {
+    /// Listen on `port`, use `libc::VMADDR_CID_ANY` as `cid` for all addresses of the machine.
+    pub fn bind(cid: u32, port: u32) -> io::Result<Self> {
+        let fd = socket()?;
+        let addr = sockaddr(cid, port);
+        cvt(unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_vm as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_vm>() as libc::socklen_t,
+            )
+        })?;
+        cvt(unsafe { libc::listen(fd.as_raw_fd(), 128) })?;
+        Ok(VsockListener(fd))
+    }
+
+    pub fn accept(&self) -> io::Result<VsockStream> {
+        let fd = cvt(unsafe {
+            libc::accept4(
+                self.0.as_raw_fd(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                libc::SOCK_CLOEXEC,
+            )
+        })?;
+        Ok(unsafe { VsockStream::from_raw_fd(fd) })
+    }
+
+    pub fn set_nonblocking(&self, b: bool) -> io::Result<()> {
+        set_nonblocking(self.0.as_raw_fd(), b)
+    }
+}
+
+impl AsRawFd for VsockListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl FromRawFd for VsockListener {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        VsockListener(OwnedFd::from_raw_fd(fd))
+    }
+}
@@ -1,11 +1,13 @@
 //! Macros for generating modules from a varlink interface definition
 //!
-//! This crate provides two procedural macros for generating a Rust module out of an interface
-//! definition:
+//! This crate provides procedural macros for generating Rust modules out of interface
+//! definitions:
 //!
 //! - The [`varlink!`] macro takes as argument the interface as a string literal.
 //! - The [`varlink_file!`] macro takes as argument the path to the varlink interface definition
 //!   _relative_ to the directory containing the manifest of your package.
+//! - The [`varlink_dir!`] macro generates a module for every `*.varlink` file in a directory
+//!   _relative_ to the directory containing the manifest of your package.
 //!
 //! They have the drawback that most IDEs don't execute this and thus
 //! offer no code completion.
@@ -40,12 +42,38 @@
 //! use org_example_network::VarlinkClientInterface;
 //! /* ... */
 //! ```
+//!
+//! # Generator options
+//!
+//! All macros accept optional `key = value` arguments after the string literal, which set the
+//! fields of the same name in [`varlink_generator::GeneratorOptions`]:
+//!
+//! - `bool_type`, `int_type`, `float_type`, `string_type`, `dict_type` and `set_type` take the
+//!   type as a string literal, e.g. `int_type = "i128"`
+//! - `derive_hash`, `skip_empty_collections`, `generate_builders`, `emit_doc_comments`,
+//!   `non_exhaustive_errors`, `generate_return_handler`, `deny_unknown_fields` and
+//!   `snake_case_fields` take `true` or `false`
+//! - `allow_more` takes a list of method names, e.g. `allow_more = ["Monitor"]`
+//! - `target` takes `"both"`, `"client"` or `"server"`
+//!
+//! ```rust,no_run
+//! use varlink_derive;
+//! extern crate serde_derive;
+//!
+//! varlink_derive::varlink_file!(
+//!    org_example_network,
+//!    "../examples/example/src/org.example.network.varlink",
+//!    int_type = "i128",
+//!    target = "client",
+//! );
+//! ```
 
 extern crate proc_macro;
 extern crate varlink_generator;
 
-use proc_macro::{Span, TokenStream, TokenTree};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 use std::io::Read;
+use varlink_generator::{GeneratorOptions, Target};
 
 /// Generates a module from a varlink interface definition
 ///
@@ -54,7 +82,10 @@ use std::io::Read;
 /// The macro takes two arguments:
 ///
 /// 1. The module name that will be generated. It must be a valid Rust identifier.
-/// 2. A string literal containing the the varlink interface definition.
+/// 2. A string literal containing the the varlink interface definition, either raw
+///    (`r#"..."#`) or with escapes like `\n` (`"..."`).
+///
+/// It can be followed by [generator options](index.html#generator-options).
 ///
 /// # Examples
 ///
@@ -75,8 +106,12 @@ use std::io::Read;
 /// ```
 #[proc_macro]
 pub fn varlink(input: TokenStream) -> TokenStream {
-    let (name, source, _) = parse_varlink_args(input);
-    expand_varlink(name, source)
+    let (name, source, span, options) = parse_varlink_args(input);
+    let options = match parse_generator_options(&options) {
+        Ok(options) => options,
+        Err(error) => return error,
+    };
+    expand_varlink(name, source, span, &options)
 }
 
 /// Generates a module from a varlink interface definition file
@@ -89,6 +124,8 @@ pub fn varlink(input: TokenStream) -> TokenStream {
 /// 2. A string literal containing the file path of the varlink interface definition. The path
 ///    **must** be relative to the directory containing the manifest of your package.
 ///
+/// It can be followed by [generator options](index.html#generator-options).
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -105,14 +142,14 @@ pub fn varlink(input: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn varlink_file(input: TokenStream) -> TokenStream {
-    let (name, filename, _) = parse_varlink_filename_args(input);
+    let (name, filename, span, options) = parse_varlink_filename_args(input);
+    let options = match parse_generator_options(&options) {
+        Ok(options) => options,
+        Err(error) => return error,
+    };
     let mut source = Vec::<u8>::new();
 
-    let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
-        std::borrow::Cow::Owned(std::path::Path::new(&manifest_dir).join(filename))
-    } else {
-        std::borrow::Cow::Borrowed(std::path::Path::new(&filename))
-    };
+    let path = manifest_path(&filename);
 
     std::fs::File::open(&path)
         .unwrap_or_else(|err| {
@@ -131,14 +168,115 @@ pub fn varlink_file(input: TokenStream) -> TokenStream {
             )
         });
 
-    expand_varlink(name, String::from_utf8_lossy(&source).to_string())
+    expand_varlink(
+        name,
+        String::from_utf8_lossy(&source).to_string(),
+        span,
+        &options,
+    )
 }
 
-// Parse a TokenStream of the form `name r#""#`
-fn parse_varlink_filename_args(input: TokenStream) -> (String, String, Span) {
+/// Generates a module with a submodule for every varlink interface definition file in a directory
+///
+/// # Usage
+///
+/// The macro takes two arguments:
+///
+/// 1. The module name that will be generated. It must be a valid Rust identifier.
+/// 2. A string literal containing the path of the directory. The path **must** be relative to
+///    the directory containing the manifest of your package.
+///
+/// It can be followed by [generator options](index.html#generator-options), which apply to all
+/// files.
+///
+/// Every `*.varlink` file in the directory becomes a submodule named after the file with the
+/// dots replaced by underscores, e.g. `org.example.ping.varlink` becomes `org_example_ping`.
+///
+/// Changes to the files trigger a rebuild, added or removed files are only picked up, when the
+/// invoking crate is rebuilt for another reason.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use varlink_derive;
+/// extern crate serde_derive;
+///
+/// varlink_derive::varlink_dir!(interfaces, "../examples/example/src");
+///
+/// use interfaces::org_example_network::VarlinkClientInterface;
+/// /* ... */
+/// ```
+#[proc_macro]
+pub fn varlink_dir(input: TokenStream) -> TokenStream {
+    let (name, dirname, span, options) = parse_varlink_filename_args(input);
+    let options = match parse_generator_options(&options) {
+        Ok(options) => options,
+        Err(error) => return error,
+    };
+    if let Some(error) = invalid_module_name(&name.to_string(), name.span()) {
+        return error;
+    }
+    let dir = manifest_path(&dirname);
+
+    let mut paths: Vec<std::path::PathBuf> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|err| {
+            panic!(
+                "varlink_dir! expansion failed. Could not read directory {}: {}",
+                dir.display(),
+                err
+            )
+        })
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "varlink") && path.is_file())
+        .collect();
+    paths.sort();
+
+    let mut modules = String::new();
+    for path in paths {
+        let source = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+            panic!(
+                "varlink_dir! expansion failed. Could not read file {}: {}",
+                path.display(),
+                err
+            )
+        });
+        let code = match varlink_generator::compile_with_options(source, &options) {
+            Ok(code) => code,
+            Err(e) => return compile_error(&format!("varlink: {}: {}", path.display(), e), span),
+        };
+        let module = path
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .replace('.', "_");
+        if let Some(error) = invalid_module_name(&module, span) {
+            return error;
+        }
+        // include the file, so that cargo rebuilds the invoking crate on changes
+        modules.push_str(&format!(
+            "pub mod {} {{ {} const _: &[u8] = include_bytes!({:?}); }}",
+            module,
+            code,
+            path.to_string_lossy()
+        ));
+    }
+
+    format!("mod {} {{ {} }}", name, modules).parse().unwrap()
+}
+
+// `filename` relative to the directory containing the manifest of the package
+fn manifest_path(filename: &str) -> std::path::PathBuf {
+    match std::env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => std::path::Path::new(&manifest_dir).join(filename),
+        None => std::path::PathBuf::from(filename),
+    }
+}
+
+// Parse a TokenStream of the form `name, "filename"` followed by the generator options
+fn parse_varlink_filename_args(input: TokenStream) -> (Ident, String, Span, Vec<OptionToken>) {
     let mut iter = input.into_iter();
     let name = match iter.next() {
-        Some(TokenTree::Ident(i)) => i.to_string(),
+        Some(TokenTree::Ident(i)) => i,
         Some(other) => panic!("Expected module name, found {}", other),
         None => panic!("Unexpected end of macro input"),
     };
@@ -149,25 +287,24 @@ fn parse_varlink_filename_args(input: TokenStream) -> (String, String, Span) {
     };
     let (body_literal, span) = match iter.next() {
         Some(TokenTree::Literal(l)) => (l.to_string(), l.span()),
-        Some(other) => panic!("Expected raw string literal, found {}", other),
+        Some(other) => panic!("Expected string literal, found {}", other),
         None => panic!("Unexpected end of macro input"),
     };
-    if !body_literal.starts_with('\"') || !body_literal.ends_with('\"') {
-        panic!("Expected raw string literal (`r#\"...\"#`)");
-    }
-    let body_string = body_literal[1..body_literal.len() - 1].to_string();
-    match iter.next() {
-        None => {}
-        Some(_) => panic!("Unexpected trailing tokens in macro"),
-    }
-    (name, body_string, span)
+    let body_string = string_literal_value(&body_literal)
+        .unwrap_or_else(|| panic!("Expected string literal, found {}", body_literal));
+    (
+        name,
+        body_string,
+        span,
+        iter.map(OptionToken::new).collect(),
+    )
 }
 
-// Parse a TokenStream of the form `name r#""#`
-fn parse_varlink_args(input: TokenStream) -> (String, String, Span) {
+// Parse a TokenStream of the form `name, r#""#` or `name, ""` followed by the generator options
+fn parse_varlink_args(input: TokenStream) -> (Ident, String, Span, Vec<OptionToken>) {
     let mut iter = input.into_iter();
     let name = match iter.next() {
-        Some(TokenTree::Ident(i)) => i.to_string(),
+        Some(TokenTree::Ident(i)) => i,
         Some(other) => panic!("Expected module name, found {}", other),
         None => panic!("Unexpected end of macro input"),
     };
@@ -178,22 +315,304 @@ fn parse_varlink_args(input: TokenStream) -> (String, String, Span) {
     };
     let (body_literal, span) = match iter.next() {
         Some(TokenTree::Literal(l)) => (l.to_string(), l.span()),
-        Some(other) => panic!("Expected raw string literal, found {}", other),
+        Some(other) => panic!("Expected string literal, found {}", other),
         None => panic!("Unexpected end of macro input"),
     };
-    if !body_literal.starts_with("r#\"") || !body_literal.ends_with("\"#") {
-        panic!("Expected raw string literal (`r#\"...\"#`)");
+    let body_string = string_literal_value(&body_literal)
+        .unwrap_or_else(|| panic!("Expected string literal, found {}", body_literal));
+    (
+        name,
+        body_string,
+        span,
+        iter.map(OptionToken::new).collect(),
+    )
+}
+
+// A token of the generator options with the value, if it is a string literal.
+//
+// The type options borrow the value, which lives as long as the options are used.
+struct OptionToken {
+    token: TokenTree,
+    string: Option<String>,
+}
+
+impl OptionToken {
+    fn new(token: TokenTree) -> Self {
+        let string = match token {
+            TokenTree::Literal(ref l) => string_literal_value(&l.to_string()),
+            _ => None,
+        };
+        OptionToken { token, string }
     }
-    let body_string = body_literal[3..body_literal.len() - 2].to_string();
-    match iter.next() {
-        None => {}
-        Some(_) => panic!("Unexpected trailing tokens in macro"),
+}
+
+// Parse the optional `, key = value, ...` generator options following the string literal
+//
+// Returns a `compile_error!` pointing at the invalid token.
+fn parse_generator_options(tokens: &[OptionToken]) -> Result<GeneratorOptions<'_>, TokenStream> {
+    let mut options = GeneratorOptions::default();
+    let mut iter = tokens.iter();
+    let end_of_input =
+        || compile_error("varlink: unexpected end of macro input", Span::call_site());
+
+    match iter.next().map(|t| &t.token) {
+        None => return Ok(options),
+        Some(TokenTree::Punct(ref p)) if p.as_char() == ',' => {}
+        Some(other) => {
+            return Err(option_error(
+                format!("expected `,`, found `{}`", other),
+                other,
+            ))
+        }
+    }
+
+    loop {
+        let key = match iter.next().map(|t| &t.token) {
+            None => break,
+            Some(TokenTree::Ident(i)) => i,
+            Some(other) => {
+                return Err(option_error(
+                    format!("expected generator option name, found `{}`", other),
+                    other,
+                ))
+            }
+        };
+        let key_name = key.to_string();
+        match iter.next().map(|t| &t.token) {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == '=' => {}
+            Some(other) => {
+                return Err(option_error(
+                    format!("expected `=` after `{}`, found `{}`", key_name, other),
+                    other,
+                ))
+            }
+            None => return Err(end_of_input()),
+        }
+        let value = iter.next().ok_or_else(end_of_input)?;
+
+        match key_name.as_str() {
+            "bool_type" => options.bool_type = Some(option_string(&key_name, value)?),
+            "int_type" => options.int_type = Some(option_string(&key_name, value)?),
+            "float_type" => options.float_type = Some(option_string(&key_name, value)?),
+            "string_type" => options.string_type = Some(option_string(&key_name, value)?),
+            "dict_type" => options.dict_type = Some(option_string(&key_name, value)?),
+            "set_type" => options.set_type = Some(option_string(&key_name, value)?),
+            "derive_hash" => options.derive_hash = option_bool(&key_name, value)?,
+            "skip_empty_collections" => {
+                options.skip_empty_collections = option_bool(&key_name, value)?
+            }
+            "generate_builders" => options.generate_builders = option_bool(&key_name, value)?,
+            "emit_doc_comments" => options.emit_doc_comments = option_bool(&key_name, value)?,
+            "non_exhaustive_errors" => {
+                options.non_exhaustive_errors = option_bool(&key_name, value)?
+            }
+            "generate_return_handler" => {
+                options.generate_return_handler = option_bool(&key_name, value)?
+            }
+            "deny_unknown_fields" => options.deny_unknown_fields = option_bool(&key_name, value)?,
+            "snake_case_fields" => options.snake_case_fields = option_bool(&key_name, value)?,
+            "allow_more" => options.allow_more = Some(option_list(&key_name, &value.token)?),
+            "target" => {
+                options.target = match option_string(&key_name, value)? {
+                    "both" => Target::Both,
+                    "client" => Target::ClientOnly,
+                    "server" => Target::ServerOnly,
+                    other => {
+                        return Err(option_error(
+                            format!(
+                        "expected \"both\", \"client\" or \"server\" for `target`, found \"{}\"",
+                        other
+                    ),
+                            &value.token,
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(compile_error(
+                    &format!("varlink: unknown generator option `{}`", key_name),
+                    key.span(),
+                ))
+            }
+        }
+
+        match iter.next().map(|t| &t.token) {
+            None => break,
+            Some(TokenTree::Punct(ref p)) if p.as_char() == ',' => {}
+            Some(other) => {
+                return Err(option_error(
+                    format!("expected `,`, found `{}`", other),
+                    other,
+                ))
+            }
+        }
     }
-    (name, body_string, span)
+    Ok(options)
 }
 
-fn expand_varlink(name: String, source: String) -> TokenStream {
-    let code = varlink_generator::compile(source).unwrap();
+// A `compile_error!` for the generator options pointing at `token`
+fn option_error(message: String, token: &TokenTree) -> TokenStream {
+    compile_error(&format!("varlink: {}", message), token.span())
+}
+
+fn option_string<'a>(key: &str, value: &'a OptionToken) -> Result<&'a str, TokenStream> {
+    value.string.as_deref().ok_or_else(|| {
+        option_error(
+            format!(
+                "expected string literal for `{}`, found `{}`",
+                key, value.token
+            ),
+            &value.token,
+        )
+    })
+}
 
-    format!("mod {} {{ {} }}", name, code).parse().unwrap()
+fn option_bool(key: &str, value: &OptionToken) -> Result<bool, TokenStream> {
+    match value.token {
+        TokenTree::Ident(ref i) if i.to_string() == "true" => Ok(true),
+        TokenTree::Ident(ref i) if i.to_string() == "false" => Ok(false),
+        ref other => Err(option_error(
+            format!(
+                "expected `true` or `false` for `{}`, found `{}`",
+                key, other
+            ),
+            other,
+        )),
+    }
+}
+
+fn option_list(key: &str, value: &TokenTree) -> Result<Vec<String>, TokenStream> {
+    match value {
+        TokenTree::Group(g) if g.delimiter() == Delimiter::Bracket => g
+            .stream()
+            .into_iter()
+            .filter(|t| !matches!(t, TokenTree::Punct(p) if p.as_char() == ','))
+            .map(|t| {
+                let t = OptionToken::new(t);
+                option_string(key, &t).map(str::to_string)
+            })
+            .collect(),
+        _ => Err(option_error(
+            format!(
+                "expected a list of string literals for `{}`, found `{}`",
+                key, value
+            ),
+            value,
+        )),
+    }
+}
+
+// The value of a raw (`r#"..."#`) or normal (`"..."`) string literal
+fn string_literal_value(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = raw[hashes..].strip_prefix('"')?;
+        let body = body.strip_suffix(&"#".repeat(hashes))?.strip_suffix('"')?;
+        return Some(body.to_string());
+    }
+
+    let body = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            '\\' => value.push('\\'),
+            '"' => value.push('"'),
+            '\'' => value.push('\''),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                value.push(char::from(u8::from_str_radix(&hex, 16).ok()?));
+            }
+            'u' => {
+                chars.next().filter(|c| *c == '{')?;
+                let hex: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                value.push(char::from_u32(
+                    u32::from_str_radix(&hex.replace('_', ""), 16).ok()?,
+                )?);
+            }
+            // a line continuation skips the newline and the leading whitespace of the next line
+            '\n' => while chars.next_if(|c| c.is_whitespace()).is_some() {},
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
+fn expand_varlink(
+    name: Ident,
+    source: String,
+    span: Span,
+    options: &GeneratorOptions,
+) -> TokenStream {
+    if let Some(error) = invalid_module_name(&name.to_string(), name.span()) {
+        return error;
+    }
+    match varlink_generator::compile_with_options(source, options) {
+        Ok(code) => format!("mod {} {{ {} }}", name, code).parse().unwrap(),
+        Err(e) => compile_error(&format!("varlink: {}", e), span),
+    }
+}
+
+// Keywords of all editions, which can't be used as identifiers
+const KEYWORDS: &[&str] = &[
+    "_", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// A `compile_error!`, if `name` can't be used as the name of the generated module
+fn invalid_module_name(name: &str, span: Span) -> Option<TokenStream> {
+    let message = if let Some(raw) = name.strip_prefix("r#") {
+        if !["crate", "self", "super", "Self"].contains(&raw) {
+            return None;
+        }
+        format!(
+            "varlink: `{}` can't be used as module name, even as raw identifier",
+            raw
+        )
+    } else if KEYWORDS.contains(&name) {
+        format!(
+            "varlink: `{}` is a Rust keyword and can't be used as module name, use `r#{}` or another name",
+            name, name
+        )
+    } else if name.starts_with(|c: char| c.is_ascii_digit())
+        || !name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    {
+        format!("varlink: `{}` is not a valid module name", name)
+    } else {
+        return None;
+    };
+    Some(compile_error(&message, span))
+}
+
+// `compile_error!("message");` pointing at `span`
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut literal = Literal::string(message);
+    literal.set_span(span);
+    let mut args = Group::new(Delimiter::Parenthesis, TokenTree::Literal(literal).into());
+    args.set_span(span);
+
+    let tokens: Vec<TokenTree> = vec![
+        Ident::new("compile_error", span).into(),
+        Punct::new('!', Spacing::Alone).into(),
+        args.into(),
+        Punct::new(';', Spacing::Alone).into(),
+    ];
+    tokens
+        .into_iter()
+        .map(|mut t| {
+            t.set_span(span);
+            t
+        })
+        .collect()
 }
@@ -0,0 +1,66 @@
+use serde_json::Value;
+use varlink::ConnectionHandler;
+
+varlink_derive::varlink!(
+    org_example_ping,
+    r#"
+interface org.example.ping
+
+method Ping(ping: string) -> (pong: string)
+
+method Reset() -> ()
+"#,
+    deny_unknown_fields = true,
+);
+
+use org_example_ping::{Call_Ping, Call_Reset, VarlinkInterface};
+
+struct Ping;
+
+impl VarlinkInterface for Ping {
+    fn ping(&self, call: &mut dyn Call_Ping, ping: String) -> varlink::Result<()> {
+        call.reply(ping)
+    }
+
+    fn reset(&self, call: &mut dyn Call_Reset) -> varlink::Result<()> {
+        call.reply()
+    }
+}
+
+fn call(method: &str, parameters: &str) -> Value {
+    let service = varlink::VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(org_example_ping::new(Box::new(Ping)))],
+    );
+    let request = format!(
+        r#"{{"method":"org.example.ping.{}","parameters":{}}}"#,
+        method, parameters
+    ) + "\0";
+    let mut reply = Vec::new();
+    let _ = service.handle(&mut request.as_bytes(), &mut reply, None);
+    reply.pop();
+    serde_json::from_slice(&reply).unwrap()
+}
+
+#[test]
+fn known_fields() {
+    assert_eq!(call("Ping", r#"{"ping":"a"}"#)["parameters"]["pong"], "a");
+    assert!(call("Reset", "{}")["error"].is_null());
+}
+
+#[test]
+fn unknown_fields() {
+    let reply = call("Ping", r#"{"ping":"a","extra":1}"#);
+    assert_eq!(reply["error"], "org.varlink.service.InvalidParameter");
+    assert!(reply["parameters"]["parameter"]
+        .as_str()
+        .unwrap()
+        .contains("extra"));
+
+    let reply = call("Reset", r#"{"extra":1}"#);
+    assert_eq!(reply["error"], "org.varlink.service.InvalidParameter");
+    assert_eq!(reply["parameters"]["parameter"], "extra");
+}
@@ -0,0 +1,38 @@
+use varlink::Interface;
+
+varlink_derive::varlink_dir!(interfaces, "tests/interfaces");
+
+use interfaces::{org_example_echo, org_example_time};
+
+struct Service;
+
+impl org_example_echo::VarlinkInterface for Service {
+    fn echo(
+        &self,
+        call: &mut dyn org_example_echo::Call_Echo,
+        message: String,
+    ) -> varlink::Result<()> {
+        call.reply(message)
+    }
+}
+
+impl org_example_time::VarlinkInterface for Service {
+    fn now(&self, call: &mut dyn org_example_time::Call_Now) -> varlink::Result<()> {
+        call.reply(org_example_time::Time {
+            seconds: 0,
+            nanoseconds: 0,
+        })
+    }
+}
+
+#[test]
+fn module_per_file() {
+    assert_eq!(
+        org_example_echo::new(Box::new(Service)).get_name(),
+        "org.example.echo"
+    );
+    assert_eq!(
+        org_example_time::new(Box::new(Service)).get_name(),
+        "org.example.time"
+    );
+}
@@ -0,0 +1,40 @@
+use std::collections::{BTreeSet, HashSet};
+
+varlink_derive::varlink!(
+    org_example_enums,
+    r#"
+interface org.example.enums
+
+type Color (red, green, blue)
+
+method Paint(color: Color, shade: (light, dark)) -> ()
+"#,
+    target = "client",
+);
+
+use org_example_enums::{Color, Paint_Args_shade};
+
+#[test]
+fn enums_as_keys() {
+    let colors: HashSet<Color> = vec![Color::blue, Color::red, Color::blue]
+        .into_iter()
+        .collect();
+    assert_eq!(colors.len(), 2);
+    assert!(colors.contains(&Color::red));
+    assert!(!colors.contains(&Color::green));
+
+    let shades: HashSet<Paint_Args_shade> = vec![Paint_Args_shade::dark].into_iter().collect();
+    assert!(shades.contains(&Paint_Args_shade::dark));
+}
+
+#[test]
+fn enums_ordered_like_the_interface() {
+    let colors: BTreeSet<Color> = vec![Color::blue, Color::red, Color::green]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        colors.into_iter().collect::<Vec<_>>(),
+        vec![Color::red, Color::green, Color::blue]
+    );
+    assert!(Paint_Args_shade::light < Paint_Args_shade::dark);
+}
@@ -0,0 +1,52 @@
+varlink_derive::varlink!(
+    org_example_errors,
+    r#"
+interface org.example.errors
+
+method Find(name: string) -> (id: int)
+
+error NotFound (name: string, tries: int)
+
+error Unavailable ()
+"#
+);
+
+use org_example_errors::{ErrorKind, NotFound_Args};
+
+#[test]
+fn display_includes_parameters() {
+    let reply = varlink::Reply::error(
+        "org.example.errors.NotFound",
+        Some(serde_json::json!({ "name": "foo", "tries": 3 })),
+    );
+    let kind = ErrorKind::from(&reply);
+    // the `ErrorKind` keeps its debug output of the parameters
+    assert!(kind
+        .to_string()
+        .starts_with("org.example.errors.NotFound: Some(\n    NotFound_Args {"));
+
+    let err = kind.into_args_error().unwrap();
+    assert_eq!(
+        err.to_string(),
+        r#"org.example.errors.NotFound (name: "foo", tries: 3)"#
+    );
+    let args = err.downcast_ref::<NotFound_Args>().unwrap();
+    assert_eq!(args.name, "foo");
+    assert_eq!(args.tries, 3);
+}
+
+#[test]
+fn display_without_parameters() {
+    assert_eq!(
+        ErrorKind::NotFound(None).to_string(),
+        "org.example.errors.NotFound: None"
+    );
+    assert!(ErrorKind::NotFound(None).into_args_error().is_none());
+    assert!(ErrorKind::Varlink_Error.into_args_error().is_none());
+
+    let reply = varlink::Reply::error("org.example.errors.Unavailable", None);
+    assert_eq!(
+        ErrorKind::from(&reply).to_string(),
+        "org.example.errors.Unavailable: None"
+    );
+}
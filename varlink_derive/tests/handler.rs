@@ -0,0 +1,78 @@
+use serde_json::Value;
+use varlink::ConnectionHandler;
+
+varlink_derive::varlink!(
+    org_example_handler,
+    r#"
+interface org.example.handler
+
+method Divide(dividend: int, divisor: int) -> (quotient: int)
+
+error DivisionByZero ()
+"#,
+    generate_return_handler = true,
+);
+
+use org_example_handler::{Divide_Reply, Error, ErrorKind, VarlinkHandler, VarlinkHandlerAdapter};
+
+struct Calculator;
+
+impl VarlinkHandler for Calculator {
+    fn divide(&self, dividend: i64, divisor: i64) -> Result<Divide_Reply, Error> {
+        match divisor {
+            0 => Err(ErrorKind::DivisionByZero(None).into()),
+            // not an error of the interface
+            -1 => Err(ErrorKind::Varlink_Error.into()),
+            _ => Ok(Divide_Reply {
+                quotient: dividend / divisor,
+            }),
+        }
+    }
+}
+
+#[test]
+fn call_handler_directly() {
+    assert_eq!(Calculator.divide(7, 2).unwrap().quotient, 3);
+    assert_eq!(
+        *Calculator.divide(7, 0).unwrap_err().kind(),
+        ErrorKind::DivisionByZero(None)
+    );
+}
+
+#[test]
+fn serve_handler() {
+    let service = varlink::VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(org_example_handler::new(Box::new(
+            VarlinkHandlerAdapter(Calculator),
+        )))],
+    );
+
+    let request = |divisor: i64| {
+        format!(
+            r#"{{"method":"org.example.handler.Divide","parameters":{{"dividend":7,"divisor":{}}}}}"#,
+            divisor
+        ) + "\0"
+    };
+    let call = |divisor: i64| -> Value {
+        let mut reply = Vec::new();
+        service
+            .handle(&mut request(divisor).as_bytes(), &mut reply, None)
+            .unwrap();
+        reply.pop();
+        serde_json::from_slice(&reply).unwrap()
+    };
+
+    assert_eq!(call(2)["parameters"]["quotient"], 3);
+    assert_eq!(call(0)["error"], "org.example.handler.DivisionByZero");
+
+    let mut reply = Vec::new();
+    let e = service
+        .handle(&mut request(-1).as_bytes(), &mut reply, None)
+        .unwrap_err();
+    assert_eq!(*e.kind(), varlink::ErrorKind::Server);
+    assert!(reply.is_empty());
+}
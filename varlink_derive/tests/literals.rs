@@ -0,0 +1,47 @@
+use varlink::Interface;
+
+varlink_derive::varlink!(
+    org_example_raw,
+    r#"
+# Example service
+interface org.example.literal
+
+# Returns the same string
+method Ping(ping: string) -> (pong: string)
+"#
+);
+
+varlink_derive::varlink!(
+    org_example_plain,
+    "\n# Example service\ninterface org.example.literal\n\n\
+     # Returns the same string\n\
+     method Ping(ping: string) -> (pong: string)\n"
+);
+
+struct Ping;
+
+impl org_example_raw::VarlinkInterface for Ping {
+    fn ping(&self, call: &mut dyn org_example_raw::Call_Ping, ping: String) -> varlink::Result<()> {
+        call.reply(ping)
+    }
+}
+
+impl org_example_plain::VarlinkInterface for Ping {
+    fn ping(
+        &self,
+        call: &mut dyn org_example_plain::Call_Ping,
+        ping: String,
+    ) -> varlink::Result<()> {
+        call.reply(ping)
+    }
+}
+
+#[test]
+fn raw_and_plain_literals() {
+    let raw = org_example_raw::new(Box::new(Ping));
+    let plain = org_example_plain::new(Box::new(Ping));
+
+    assert_eq!(raw.get_name(), "org.example.literal");
+    assert_eq!(raw.get_name(), plain.get_name());
+    assert_eq!(raw.get_description(), plain.get_description());
+}
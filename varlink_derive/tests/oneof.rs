@@ -0,0 +1,38 @@
+use serde_json::json;
+
+varlink_derive::varlink!(
+    org_example_oneof,
+    r#"
+interface org.example.oneof
+
+# @oneof
+type State (start: ?bool, inProgress: ?int, end: ?[]string)
+
+method Get() -> (state: State)
+"#,
+    target = "client",
+    snake_case_fields = true,
+    skip_empty_collections = true,
+);
+
+use org_example_oneof::{State, State_Fields};
+
+#[test]
+fn only_the_set_field_on_the_wire() {
+    let state = State::inProgress(42);
+    let value = serde_json::to_value(&state).unwrap();
+    assert_eq!(value, json!({ "inProgress": 42 }));
+    assert_eq!(serde_json::from_value::<State>(value).unwrap(), state);
+
+    let state = State::end(Vec::new());
+    let value = serde_json::to_value(&state).unwrap();
+    assert_eq!(value, json!({ "end": [] }));
+    assert_eq!(serde_json::from_value::<State>(value).unwrap(), state);
+
+    let fields: State_Fields = serde_json::from_value(json!({ "start": true })).unwrap();
+    assert_eq!(fields.start, Some(true));
+    assert_eq!(fields.in_progress, None);
+
+    assert!(serde_json::from_value::<State>(json!({ "start": true, "end": [] })).is_err());
+    assert!(serde_json::from_value::<State>(json!({})).is_err());
+}
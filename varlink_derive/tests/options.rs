@@ -0,0 +1,19 @@
+varlink_derive::varlink!(
+    org_example_options,
+    r#"
+interface org.example.options
+
+type Big (value: int)
+
+method Get() -> (big: Big)
+"#,
+    int_type = "i128",
+    target = "client",
+);
+
+#[test]
+fn int_type() {
+    let big = org_example_options::Big { value: i128::MAX };
+    let value: i128 = big.value;
+    assert_eq!(value, i128::MAX);
+}
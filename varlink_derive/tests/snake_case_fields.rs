@@ -0,0 +1,66 @@
+use serde_json::{json, Value};
+use varlink::ConnectionHandler;
+
+varlink_derive::varlink!(
+    org_example_dhcp,
+    r#"
+interface org.example.dhcp
+
+type Lease (clientId: string, ifIndex: int)
+
+method Renew(clientId: string) -> (lease: Lease)
+"#,
+    snake_case_fields = true,
+);
+
+use org_example_dhcp::{Call_Renew, Lease, Renew_Args, VarlinkInterface};
+
+struct Dhcp;
+
+impl VarlinkInterface for Dhcp {
+    fn renew(&self, call: &mut dyn Call_Renew, client_id: String) -> varlink::Result<()> {
+        call.reply(Lease {
+            client_id,
+            if_index: 2,
+        })
+    }
+}
+
+#[test]
+fn renamed_on_the_wire() {
+    let args = Renew_Args {
+        client_id: "01:02".into(),
+    };
+    assert_eq!(
+        serde_json::to_value(&args).unwrap(),
+        json!({ "clientId": "01:02" })
+    );
+
+    let service = varlink::VarlinkService::new(
+        "org.varlink",
+        "test service",
+        "0.1",
+        "http://varlink.org",
+        vec![Box::new(org_example_dhcp::new(Box::new(Dhcp)))],
+    );
+    let request = json!({
+        "method": "org.example.dhcp.Renew",
+        "parameters": serde_json::to_value(&args).unwrap(),
+    })
+    .to_string()
+        + "\0";
+    let mut reply = Vec::new();
+    service
+        .handle(&mut request.as_bytes(), &mut reply, None)
+        .unwrap();
+    reply.pop();
+    let reply: Value = serde_json::from_slice(&reply).unwrap();
+    assert_eq!(
+        reply["parameters"]["lease"],
+        json!({ "clientId": "01:02", "ifIndex": 2 })
+    );
+
+    let lease: Lease = serde_json::from_value(reply["parameters"]["lease"].clone()).unwrap();
+    assert_eq!(lease.client_id, "01:02");
+    assert_eq!(lease.if_index, 2);
+}
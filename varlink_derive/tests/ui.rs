@@ -0,0 +1,9 @@
+/// The compiler output differs between toolchains, so the CI only compares it on stable with
+/// `cargo test -p varlink_derive --test ui -- --ignored`. Run with `TRYBUILD=overwrite` to
+/// update the `.stderr` files.
+#[test]
+#[ignore = "compiler output differs between toolchains"]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
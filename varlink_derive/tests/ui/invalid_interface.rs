@@ -0,0 +1,7 @@
+varlink_derive::varlink!(org_example_invalid, r#"
+interface org.example.invalid
+
+method Ping(ping: string) -> (pong: strin
+"#);
+
+fn main() {}
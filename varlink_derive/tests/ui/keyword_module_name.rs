@@ -0,0 +1,7 @@
+varlink_derive::varlink!(fn, r#"
+interface org.example.keyword
+
+method Ping(ping: string) -> (pong: string)
+"#);
+
+fn main() {}
@@ -0,0 +1,7 @@
+varlink_derive::varlink!(org_example_option, r#"
+interface org.example.option
+
+method Ping(ping: string) -> (pong: string)
+"#, int_typ = "i128");
+
+fn main() {}
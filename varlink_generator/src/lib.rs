@@ -24,6 +24,21 @@
 //! }
 //! ```
 //! [`varlink_generator::cargo_build_tosource`]: fn.cargo_build_tosource.html
+//!
+//! A struct type with only optional fields, of which exactly one is set, can be annotated
+//! with a `# @oneof` doc comment line:
+//!
+//! ```varlink
+//! # @oneof
+//! type State (start: ?bool, progress: ?int, end: ?bool)
+//! ```
+//!
+//! It is generated as an enum `State` with one variant per field (`State::progress(i64)`)
+//! and accessors like `State::as_progress()`, while staying a struct on the wire.
+//!
+//! The methods of the interface are listed in an enum `Method`, e.g. `Method::Ping`, which
+//! converts from and to the fully qualified method name. If the interface declares a type
+//! `Method`, the enum is called `VarlinkMethod` instead.
 
 #![recursion_limit = "512"]
 #![doc(
@@ -32,6 +47,7 @@
 )]
 
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::convert::TryFrom;
 use std::env;
 use std::fs::File;
@@ -43,7 +59,9 @@ use std::str::FromStr;
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::{format_ident, quote};
 
-use varlink_parser::{Typedef, VEnum, VError, VStruct, VStructOrEnum, VType, VTypeExt, IDL};
+use varlink_parser::{
+    Method, Typedef, VEnum, VError, VStruct, VStructOrEnum, VType, VTypeExt, IDL,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -51,6 +69,8 @@ pub enum Error {
     Parse(varlink_parser::Error),
     #[error("I/O error: {0}")]
     Io(std::io::Error),
+    #[error("{0}")]
+    Annotation(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -60,7 +80,7 @@ trait ToRustString<'short, 'long: 'short> {
         &'long self,
         name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     ) -> Cow<'long, str>;
 }
 
@@ -69,17 +89,286 @@ trait ToTokenStream<'short, 'long: 'short> {
         &'long self,
         name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     );
 }
 
+/// The parts of the interface code to generate
+///
+/// The types of the interface and its `Error` are generated for all targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Target {
+    /// The client and the server code
+    #[default]
+    Both,
+    /// Only `VarlinkClient` and `VarlinkClientInterface`
+    ClientOnly,
+    /// Only `VarlinkInterface`, the `Call_*` traits and the proxy returned by `new()`
+    ServerOnly,
+}
+
+impl Target {
+    fn client(self) -> bool {
+        self != Target::ServerOnly
+    }
+
+    fn server(self) -> bool {
+        self != Target::ClientOnly
+    }
+}
+
 #[derive(Default)]
-pub struct GeneratorOptions {
-    pub bool_type: Option<&'static str>,
-    pub int_type: Option<&'static str>,
-    pub float_type: Option<&'static str>,
-    pub string_type: Option<&'static str>,
+pub struct GeneratorOptions<'a> {
+    pub bool_type: Option<&'a str>,
+    /// The type of varlink `int`. Defaults to `i64`, `varlink::VarlinkInt` rejects fractions
+    /// and numbers out of the range of `i64` with a clear error.
+    pub int_type: Option<&'a str>,
+    pub float_type: Option<&'a str>,
+    pub string_type: Option<&'a str>,
+    /// The type of varlink maps like `[string]int`, with `_` standing for the value type,
+    /// e.g. `"std::collections::BTreeMap<String, _>"`. Defaults to `varlink::StringHashMap<_>`.
+    pub dict_type: Option<&'a str>,
+    /// The type of varlink sets `[string]()`. Defaults to `varlink::StringHashSet`.
+    ///
+    /// The type has to serialize as a JSON object with an empty object for every key.
+    /// `varlink::StrictStringHashSet` also rejects other values than `{}` when deserializing.
+    pub set_type: Option<&'a str>,
     pub preamble: Option<TokenStream>,
+    /// Derive `Eq` and `Hash` for all generated types, which can implement them.
+    ///
+    /// Types transitively containing a `float`, an `object` or a map are skipped,
+    /// and the `cargo_build*` functions emit a `cargo:warning` for skipped typedefs.
+    /// Enums derive `Eq`, `Hash`, `PartialOrd` and `Ord` regardless of this option.
+    pub derive_hash: bool,
+    /// Passed as `--edition` to `rustfmt`, when the generator formats the generated code.
+    pub rustfmt_edition: Option<String>,
+    /// Passed as `--config-path` to `rustfmt`, when the generator formats the generated code.
+    pub rustfmt_config_path: Option<PathBuf>,
+    /// Omit empty arrays and maps from the serialized messages.
+    ///
+    /// Such fields are filled with an empty collection, if they are missing while deserializing.
+    pub skip_empty_collections: bool,
+    /// Generate a `<Method>ArgsBuilder` for methods with more than three input parameters.
+    ///
+    /// The builder is returned by `VarlinkClient::<method>_builder()` and has a setter for
+    /// every parameter. Optional parameters default to `None`.
+    pub generate_builders: bool,
+    /// Turn the doc comments of the interface, its methods, types and errors into `#[doc]`
+    /// attributes of the generated code.
+    pub emit_doc_comments: bool,
+    /// Mark the generated `ErrorKind` as `#[non_exhaustive]`, so other crates matching on it
+    /// keep compiling, when the interface gains new errors.
+    pub non_exhaustive_errors: bool,
+    /// The names of the methods, which accept `more` calls, e.g. `vec!["Monitor".into()]`.
+    ///
+    /// If set, the generated server replies with `InvalidParameter("more")` to `more` calls
+    /// of all other methods, instead of passing them to the implementation.
+    pub allow_more: Option<Vec<String>>,
+    /// Generate only the client or the server side of the interface.
+    pub target: Target,
+    /// Generate a `VarlinkHandler` trait, whose methods return their reply or error instead of
+    /// writing it to a `Call`, and the `VarlinkHandlerAdapter` implementing `VarlinkInterface`
+    /// for it.
+    pub generate_return_handler: bool,
+    /// Reject requests with parameters, which the method does not declare.
+    ///
+    /// The `<Method>_Args` structs get `#[serde(deny_unknown_fields)]` and the server replies
+    /// with `InvalidParameter` instead of ignoring the additional parameters.
+    pub deny_unknown_fields: bool,
+    /// Name the fields of the generated structs and the parameters of the generated methods
+    /// in snake case, e.g. `client_id` for `clientId`.
+    ///
+    /// Renamed fields get `#[serde(rename = "clientId")]`, so the messages keep the names of
+    /// the interface. The variants of `@oneof` enums keep their names.
+    pub snake_case_fields: bool,
+}
+
+/// State of the code generation for one interface
+struct Context<'a> {
+    options: &'a GeneratorOptions<'a>,
+    /// typedefs, which can't derive `Eq` and `Hash`
+    unhashable: HashSet<&'a str>,
+}
+
+impl<'a> Context<'a> {
+    fn new(idl: &IDL<'a>, options: &'a GeneratorOptions<'a>) -> Self {
+        Context {
+            options,
+            unhashable: if options.derive_hash {
+                unhashable_typedefs(idl).into_keys().collect()
+            } else {
+                HashSet::new()
+            },
+        }
+    }
+
+    fn is_hashable(&self, vtype: &VTypeExt) -> bool {
+        vtype_is_hashable(vtype, &mut |name| !self.unhashable.contains(name))
+    }
+
+    /// The Rust name of the field `name`, see `GeneratorOptions::snake_case_fields`
+    fn field_ident(&self, name: &str) -> Ident {
+        if self.options.snake_case_fields {
+            syn::parse_str(&format!("r#{}", to_snake_case(name))).unwrap()
+        } else {
+            syn::parse_str(&format!("r#{}", name)).unwrap()
+        }
+    }
+
+    /// The `#[serde(rename)]` attribute for a field renamed by `field_ident()`
+    fn field_rename(&self, name: &str) -> TokenStream {
+        if self.options.snake_case_fields && to_snake_case(name) != name {
+            quote!(#[serde(rename = #name)])
+        } else {
+            quote!()
+        }
+    }
+
+    /// serde attributes for a field of type `vtype`, which is not optional
+    fn skip_empty(&self, vtype: &VTypeExt) -> TokenStream {
+        if !self.options.skip_empty_collections {
+            return quote!();
+        }
+        match vtype {
+            VTypeExt::Array(_) => quote!(#[serde(default, skip_serializing_if = "Vec::is_empty")]),
+            VTypeExt::Dict(v) => {
+                let is_empty = match v.as_ref() {
+                    VTypeExt::Plain(VType::Struct(s)) if s.elts.is_empty() => {
+                        match self.options.set_type {
+                            Some(t) => format!("{}::is_empty", type_path(t)),
+                            None => "varlink::StringHashSet::is_empty".into(),
+                        }
+                    }
+                    _ => match self.options.dict_type {
+                        Some(t) => format!("{}::is_empty", type_path(t)),
+                        None => "std::collections::HashMap::is_empty".into(),
+                    },
+                };
+                quote!(#[serde(default, skip_serializing_if = #is_empty)])
+            }
+            _ => quote!(),
+        }
+    }
+
+    /// The `#[doc]` attribute for the doc comment of a declaration, without the leading `#`
+    fn doc(&self, doc: &str) -> TokenStream {
+        if !self.options.emit_doc_comments {
+            return TokenStream::new();
+        }
+        let doc = doc
+            .lines()
+            .map(|line| {
+                let line = line.trim_start().trim_start_matches('#');
+                line.strip_prefix(' ').unwrap_or(line).trim_end()
+            })
+            .filter(|line| *line != "@oneof")
+            .collect::<Vec<_>>()
+            .join("\n");
+        let doc = doc.trim_matches('\n');
+        if doc.is_empty() {
+            return TokenStream::new();
+        }
+        quote!(#[doc = #doc])
+    }
+
+    fn derive(&self, hashable: bool) -> TokenStream {
+        if self.options.derive_hash && hashable {
+            quote!(#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, Default)])
+        } else {
+            quote!(#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)])
+        }
+    }
+
+    /// Like `derive()`, for enums, which can't derive `Default`
+    fn derive_without_default(&self, hashable: bool) -> TokenStream {
+        if self.options.derive_hash && hashable {
+            quote!(#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash)])
+        } else {
+            quote!(#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)])
+        }
+    }
+}
+
+/// Replaces the `_` placeholder in a type like `BTreeMap<String, _>` with `value`.
+fn replace_placeholder(template: &str, value: &str) -> String {
+    let is_ident_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut result = String::new();
+    let mut prev = None;
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '_' && !is_ident_char(prev) && !is_ident_char(chars.peek().copied()) {
+            result += value;
+        } else {
+            result.push(c);
+        }
+        prev = Some(c);
+    }
+    result
+}
+
+/// The path of a type without its generic arguments, e.g. `BTreeMap` for `BTreeMap<String, _>`
+fn type_path(t: &str) -> &str {
+    t.split('<').next().unwrap_or(t).trim()
+}
+
+fn vtype_is_hashable(vtype: &VTypeExt, typename_is_hashable: &mut dyn FnMut(&str) -> bool) -> bool {
+    match vtype {
+        VTypeExt::Array(v) | VTypeExt::Option(v) => vtype_is_hashable(v, typename_is_hashable),
+        // neither `StringHashMap` nor `StringHashSet` implement `Hash`
+        VTypeExt::Dict(_) => false,
+        VTypeExt::Plain(VType::Float) | VTypeExt::Plain(VType::Object) => false,
+        VTypeExt::Plain(VType::Bool)
+        | VTypeExt::Plain(VType::Int)
+        | VTypeExt::Plain(VType::String)
+        | VTypeExt::Plain(VType::Enum(_)) => true,
+        VTypeExt::Plain(VType::Typename(name)) => typename_is_hashable(name),
+        VTypeExt::Plain(VType::Struct(v)) => v
+            .elts
+            .iter()
+            .all(|e| vtype_is_hashable(&e.vtype, typename_is_hashable)),
+    }
+}
+
+/// Returns all typedefs, which transitively contain a type without `Eq` and `Hash`,
+/// mapped to the reason.
+fn unhashable_typedefs<'a>(idl: &IDL<'a>) -> BTreeMap<&'a str, String> {
+    fn typedef_is_hashable<'a>(idl: &IDL<'a>, name: &str, visiting: &mut Vec<&'a str>) -> bool {
+        let (name, typedef) = match idl.typedefs.get_key_value(name) {
+            Some((name, typedef)) => (*name, typedef),
+            None => return false,
+        };
+
+        // a recursive type is hashable, if the rest of it is
+        if visiting.contains(&name) {
+            return true;
+        }
+
+        match typedef.elt {
+            VStructOrEnum::VEnum(_) => true,
+            VStructOrEnum::VStruct(ref v) => {
+                visiting.push(name);
+                let hashable = v.elts.iter().all(|e| {
+                    vtype_is_hashable(&e.vtype, &mut |n| typedef_is_hashable(idl, n, visiting))
+                });
+                visiting.pop();
+                hashable
+            }
+        }
+    }
+
+    idl.typedefs
+        .keys()
+        .filter(|name| !typedef_is_hashable(idl, name, &mut Vec::new()))
+        .map(|name| {
+            (
+                *name,
+                format!(
+                    "{}.{}: not deriving Eq and Hash, because it contains a float, object or map",
+                    idl.name, name
+                ),
+            )
+        })
+        .collect()
 }
 
 impl<'short, 'long: 'short> ToRustString<'short, 'long> for VType<'long> {
@@ -87,21 +376,21 @@ impl<'short, 'long: 'short> ToRustString<'short, 'long> for VType<'long> {
         &'long self,
         name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     ) -> Cow<'long, str> {
         match *self {
-            VType::Bool => options.bool_type.unwrap_or("bool").into(),
-            VType::Int => options.int_type.unwrap_or("i64").into(),
-            VType::Float => options.float_type.unwrap_or("f64").into(),
-            VType::String => options.string_type.unwrap_or("String").into(),
+            VType::Bool => ctx.options.bool_type.unwrap_or("bool").into(),
+            VType::Int => ctx.options.int_type.unwrap_or("i64").into(),
+            VType::Float => ctx.options.float_type.unwrap_or("f64").into(),
+            VType::String => ctx.options.string_type.unwrap_or("String").into(),
             VType::Object => "serde_json::Value".into(),
             VType::Typename(v) => v.into(),
             VType::Enum(ref v) => {
-                v.to_tokenstream(name, tokenstream, options);
+                v.to_tokenstream(name, tokenstream, ctx);
                 Cow::Owned(name.to_string())
             }
             VType::Struct(ref v) => {
-                v.to_tokenstream(name, tokenstream, options);
+                v.to_tokenstream(name, tokenstream, ctx);
                 Cow::Owned(name.to_string())
             }
         }
@@ -113,25 +402,27 @@ impl<'short, 'long: 'short> ToRustString<'short, 'long> for VTypeExt<'long> {
         &'long self,
         name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     ) -> Cow<'long, str> {
         match *self {
-            VTypeExt::Plain(ref vtype) => vtype.to_rust_string(name, tokenstream, options),
+            VTypeExt::Plain(ref vtype) => vtype.to_rust_string(name, tokenstream, ctx),
             VTypeExt::Array(ref v) => {
-                format!("Vec<{}>", v.to_rust_string(name, tokenstream, options)).into()
+                format!("Vec<{}>", v.to_rust_string(name, tokenstream, ctx)).into()
             }
             VTypeExt::Dict(ref v) => match *v.as_ref() {
-                VTypeExt::Plain(VType::Struct(ref s)) if s.elts.is_empty() => {
-                    "varlink::StringHashSet".into()
-                }
-                _ => format!(
-                    "varlink::StringHashMap<{}>",
-                    v.to_rust_string(name, tokenstream, options)
+                VTypeExt::Plain(VType::Struct(ref s)) if s.elts.is_empty() => ctx
+                    .options
+                    .set_type
+                    .unwrap_or("varlink::StringHashSet")
+                    .into(),
+                _ => replace_placeholder(
+                    ctx.options.dict_type.unwrap_or("varlink::StringHashMap<_>"),
+                    &v.to_rust_string(name, tokenstream, ctx),
                 )
                 .into(),
             },
             VTypeExt::Option(ref v) => {
-                format!("Option<{}>", v.to_rust_string(name, tokenstream, options)).into()
+                format!("Option<{}>", v.to_rust_string(name, tokenstream, ctx)).into()
             }
         }
     }
@@ -172,34 +463,9 @@ impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for VStruct<'long> {
         &'long self,
         name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     ) {
-        let tname: Ident = format_ident!("r#{}", name);
-
-        let mut enames = vec![];
-        let mut etypes = vec![];
-        for e in &self.elts {
-            let ename_ident: Ident = syn::parse_str(&(String::from("r#") + e.name)).unwrap();
-            enames.push(ename_ident);
-            etypes.push(
-                TokenStream::from_str(
-                    e.vtype
-                        .to_rust_string(
-                            format!("{}_{}", name, e.name).as_ref(),
-                            tokenstream,
-                            options,
-                        )
-                        .as_ref(),
-                )
-                .unwrap(),
-            );
-        }
-        tokenstream.extend(quote!(
-            #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-            pub struct #tname {
-                #(pub #enames: #etypes,)*
-            }
-        ));
+        generate_struct(name, self, TokenStream::new(), tokenstream, ctx)
     }
 }
 
@@ -208,23 +474,81 @@ impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for VEnum<'long> {
         &'long self,
         name: &str,
         tokenstream: &mut TokenStream,
-        _options: &'long GeneratorOptions,
+        _ctx: &'long Context<'long>,
     ) {
-        let tname: Ident = syn::parse_str(&(String::from("r#") + name)).unwrap();
-
-        let mut enames = vec![];
+        generate_enum(name, self, TokenStream::new(), tokenstream)
+    }
+}
 
-        for elt in &self.elts {
-            let ename_ident: Ident = syn::parse_str(&(String::from("r#") + elt)).unwrap();
-            enames.push(ename_ident);
+fn generate_struct<'long>(
+    name: &str,
+    v: &'long VStruct<'long>,
+    doc: TokenStream,
+    tokenstream: &mut TokenStream,
+    ctx: &'long Context<'long>,
+) {
+    let tname: Ident = format_ident!("r#{}", name);
+
+    let mut enames = vec![];
+    let mut etypes = vec![];
+    let mut anot = vec![];
+    for e in &v.elts {
+        let skip_empty = ctx.skip_empty(&e.vtype);
+        let rename = ctx.field_rename(e.name);
+        anot.push(quote!(#rename #skip_empty));
+        enames.push(ctx.field_ident(e.name));
+        etypes.push(
+            TokenStream::from_str(
+                e.vtype
+                    .to_rust_string(format!("{}_{}", name, e.name).as_ref(), tokenstream, ctx)
+                    .as_ref(),
+            )
+            .unwrap(),
+        );
+    }
+    let derive = ctx.derive(v.elts.iter().all(|e| ctx.is_hashable(&e.vtype)));
+    tokenstream.extend(quote!(
+        #doc
+        #derive
+        pub struct #tname {
+            #(#anot pub #enames: #etypes,)*
         }
-        tokenstream.extend(quote!(
-            #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-            pub enum #tname {
-                #(#enames, )*
-            }
-        ));
+    ));
+}
+
+fn generate_enum<'long>(
+    name: &str,
+    v: &'long VEnum<'long>,
+    doc: TokenStream,
+    tokenstream: &mut TokenStream,
+) {
+    let tname: Ident = syn::parse_str(&(String::from("r#") + name)).unwrap();
+
+    let mut enames = vec![];
+
+    for elt in &v.elts {
+        let ename_ident: Ident = syn::parse_str(&(String::from("r#") + elt)).unwrap();
+        enames.push(ename_ident);
     }
+    // the first value is the default, and the values are fieldless, so they can always be
+    // compared, ordered and hashed, ordered like in the interface
+    let (derive, enames) = match enames.split_first() {
+        Some((first, rest)) => (
+            quote!(#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord, Default)]),
+            quote!(#[default] #first, #(#rest, )*),
+        ),
+        None => (
+            quote!(#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord)]),
+            quote!(),
+        ),
+    };
+    tokenstream.extend(quote!(
+        #doc
+        #derive
+        pub enum #tname {
+            #enames
+        }
+    ));
 }
 
 impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for Typedef<'long> {
@@ -232,11 +556,29 @@ impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for Typedef<'long> {
         &'long self,
         _name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     ) {
+        let doc = ctx.doc(self.doc);
         match self.elt {
-            VStructOrEnum::VStruct(ref v) => v.to_tokenstream(self.name, tokenstream, options),
-            VStructOrEnum::VEnum(ref v) => v.to_tokenstream(self.name, tokenstream, options),
+            VStructOrEnum::VStruct(ref v) if has_oneof_annotation(self.doc) => {
+                generate_oneof(self.name, v, doc, tokenstream, ctx)
+            }
+            VStructOrEnum::VStruct(ref v) => {
+                generate_struct(self.name, v, doc, tokenstream, ctx);
+                let tname: Ident = format_ident!("r#{}", self.name);
+                tokenstream.extend(quote!(
+                    impl ::std::convert::TryFrom<serde_json::Value> for #tname {
+                        type Error = Error;
+
+                        fn try_from(value: serde_json::Value) -> ::std::result::Result<Self, Self::Error> {
+                            serde_json::from_value(value).map_err(|e| {
+                                varlink::context!(varlink::ErrorKind::SerdeJsonDe(e.to_string())).into()
+                            })
+                        }
+                    }
+                ));
+            }
+            VStructOrEnum::VEnum(ref v) => generate_enum(self.name, v, doc, tokenstream),
         }
     }
 }
@@ -246,7 +588,7 @@ impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for VError<'long> {
         &'long self,
         _name: &str,
         tokenstream: &mut TokenStream,
-        options: &'long GeneratorOptions,
+        ctx: &'long Context<'long>,
     ) {
         let args_name = Ident::new(&format!("{}_Args", self.name), Span::call_site());
         let mut args_enames = vec![];
@@ -254,28 +596,30 @@ impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for VError<'long> {
         let mut args_anot = vec![];
 
         for e in &self.parm.elts {
-            args_anot.push(if let VTypeExt::Option(_) = e.vtype {
+            let skip = if let VTypeExt::Option(_) = e.vtype {
                 quote!(#[serde(skip_serializing_if = "Option::is_none")])
             } else {
-                quote!()
-            });
-            let ename_ident: Ident = syn::parse_str(&(String::from("r#") + e.name)).unwrap();
-            args_enames.push(ename_ident);
+                ctx.skip_empty(&e.vtype)
+            };
+            let rename = ctx.field_rename(e.name);
+            args_anot.push(quote!(#rename #skip));
+            args_enames.push(ctx.field_ident(e.name));
             args_etypes.push(
                 TokenStream::from_str(
                     e.vtype
                         .to_rust_string(
                             format!("{}_Args_{}", self.name, e.name).as_ref(),
                             tokenstream,
-                            options,
+                            ctx,
                         )
                         .as_ref(),
                 )
                 .unwrap(),
             );
         }
+        let derive = ctx.derive(self.parm.elts.iter().all(|e| ctx.is_hashable(&e.vtype)));
         tokenstream.extend(quote!(
-            #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+            #derive
             pub struct #args_name {
                 #(#args_anot pub #args_enames: #args_etypes,)*
             }
@@ -283,7 +627,154 @@ impl<'short, 'long: 'short> ToTokenStream<'short, 'long> for VError<'long> {
     }
 }
 
+/// `true`, if a line of the doc comment consists of the `@oneof` annotation
+fn has_oneof_annotation(doc: &str) -> bool {
+    doc.lines()
+        .any(|line| line.trim_start_matches('#').trim() == "@oneof")
+}
+
+/// Checks, that `@oneof` is only used on structs with optional fields.
+fn check_oneof_annotations(idl: &IDL) -> Result<()> {
+    for t in idl.typedef_keys.iter().filter_map(|k| idl.typedefs.get(k)) {
+        if !has_oneof_annotation(t.doc) {
+            continue;
+        }
+        let valid = match t.elt {
+            VStructOrEnum::VStruct(ref v) => {
+                !v.elts.is_empty()
+                    && v.elts
+                        .iter()
+                        .all(|e| matches!(e.vtype, VTypeExt::Option(_)))
+            }
+            VStructOrEnum::VEnum(_) => false,
+        };
+        if !valid {
+            return Err(Error::Annotation(format!(
+                "{}.{}: @oneof requires a struct with only optional fields",
+                idl.name, t.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Generates an enum with one variant per field for a `@oneof` typedef.
+///
+/// On the wire, the enum is the struct with exactly one field set.
+fn generate_oneof<'long>(
+    name: &str,
+    v: &'long VStruct<'long>,
+    doc: TokenStream,
+    tokenstream: &mut TokenStream,
+    ctx: &'long Context<'long>,
+) {
+    let tname: Ident = format_ident!("r#{}", name);
+    let fields_name = format!("{}_Fields", name);
+    let fields_ident = Ident::new(&fields_name, Span::call_site());
+
+    let mut enames = vec![];
+    let mut fnames = vec![];
+    let mut fanot = vec![];
+    let mut etypes = vec![];
+    let mut accessors = vec![];
+    for e in &v.elts {
+        let inner = match e.vtype {
+            VTypeExt::Option(ref inner) => inner,
+            _ => unreachable!("checked by check_oneof_annotations"),
+        };
+        let rename = ctx.field_rename(e.name);
+        fanot.push(quote!(#rename #[serde(default, skip_serializing_if = "Option::is_none")]));
+        fnames.push(ctx.field_ident(e.name));
+        enames.push(syn::parse_str::<Ident>(&(String::from("r#") + e.name)).unwrap());
+        etypes.push(
+            TokenStream::from_str(
+                inner
+                    .to_rust_string(format!("{}_{}", name, e.name).as_ref(), tokenstream, ctx)
+                    .as_ref(),
+            )
+            .unwrap(),
+        );
+        accessors.push(format_ident!("as_{}", to_snake_case(e.name)));
+    }
+
+    let mut from_fields_arms = vec![];
+    let mut to_fields_arms = vec![];
+    for (i, ename) in enames.iter().enumerate() {
+        let fields = fnames.iter().enumerate().map(|(j, n)| {
+            if i == j {
+                quote!(#n: Some(v))
+            } else {
+                quote!(#n: None)
+            }
+        });
+        let fields = quote!(#(#fields),*);
+        from_fields_arms.push(quote!(#fields_ident { #fields } => Ok(#tname::#ename(v)),));
+        to_fields_arms.push(quote!(#tname::#ename(v) => #fields_ident { #fields },));
+    }
+
+    let error = format!("exactly one field of `{}` has to be set", name);
+    let hashable = v.elts.iter().all(|e| ctx.is_hashable(&e.vtype));
+    let derive = ctx.derive(hashable);
+    let enum_derive = ctx.derive_without_default(hashable);
+    // checked by check_oneof_annotations to have at least one field
+    let first = &enames[0];
+    let etypes = &etypes;
+    let enames = &enames;
+    tokenstream.extend(quote!(
+        #derive
+        pub struct #fields_ident {
+            #(#fanot pub #fnames: Option<#etypes>,)*
+        }
+
+        #doc
+        #enum_derive
+        #[serde(try_from = #fields_name, into = #fields_name)]
+        pub enum #tname {
+            #(#enames(#etypes),)*
+        }
+
+        impl Default for #tname {
+            fn default() -> Self {
+                #tname::#first(Default::default())
+            }
+        }
+
+        impl ::std::convert::TryFrom<#fields_ident> for #tname {
+            type Error = &'static str;
+
+            fn try_from(fields: #fields_ident) -> ::std::result::Result<Self, Self::Error> {
+                match fields {
+                    #(#from_fields_arms)*
+                    _ => Err(#error),
+                }
+            }
+        }
+
+        impl From<#tname> for #fields_ident {
+            fn from(v: #tname) -> Self {
+                match v {
+                    #(#to_fields_arms)*
+                }
+            }
+        }
+
+        impl #tname {
+            #(
+                #[allow(dead_code, unreachable_patterns)]
+                pub fn #accessors(&self) -> Option<&#etypes> {
+                    match self {
+                        #tname::#enames(v) => Some(v),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+    ));
+}
+
 fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Result<TokenStream> {
+    check_oneof_annotations(idl)?;
+
     let mut ts = TokenStream::new();
 
     if tosource {
@@ -294,35 +785,58 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
         ));
     }
 
-    ts.extend(quote!(
-        use serde_derive::{Deserialize, Serialize};
-        use std::io::BufRead;
-        use std::sync::{Arc, RwLock};
-        use varlink::{self, CallTrait};
-    ));
+    ts.extend(match options.target {
+        Target::Both => quote!(
+            use serde_derive::{Deserialize, Serialize};
+            use std::io::BufRead;
+            use std::sync::{Arc, RwLock};
+            use varlink::{self, CallTrait};
+        ),
+        Target::ClientOnly => quote!(
+            use serde_derive::{Deserialize, Serialize};
+            use std::sync::{Arc, RwLock};
+        ),
+        Target::ServerOnly => quote!(
+            use serde_derive::{Deserialize, Serialize};
+            use std::io::BufRead;
+            use std::sync::Arc;
+            use varlink::{self, CallTrait};
+        ),
+    });
 
     if let Some(ref v) = options.preamble {
         ts.extend(v.clone());
     }
 
-    generate_error_code(options, idl, &mut ts);
+    let ctx = Context::new(idl, options);
 
-    for t in idl.typedefs.values() {
-        t.to_tokenstream("", &mut ts, options);
+    generate_error_code(&ctx, idl, &mut ts);
+
+    for t in idl.typedef_keys.iter().filter_map(|k| idl.typedefs.get(k)) {
+        t.to_tokenstream("", &mut ts, &ctx);
     }
 
-    for t in idl.errors.values() {
-        t.to_tokenstream("", &mut ts, options);
+    for t in idl.error_keys.iter().filter_map(|k| idl.errors.get(k)) {
+        t.to_tokenstream("", &mut ts, &ctx);
     }
 
     let mut server_method_decls = TokenStream::new();
+    let mut server_method_forwards = TokenStream::new();
     let mut client_method_decls = TokenStream::new();
     let mut server_method_impls = TokenStream::new();
     let mut client_method_impls = TokenStream::new();
+    let mut client_builders = TokenStream::new();
+    let mut handler_method_decls = TokenStream::new();
+    let mut handler_adapter_impls = TokenStream::new();
     let iname = idl.name;
     let description = idl.description;
+    let mut server_doc = format!(
+        "Server side of the `{}` interface.\n\nThe methods are called for these varlink methods:\n",
+        idl.name
+    );
+    let interface_doc = ctx.doc(idl.doc);
 
-    for t in idl.methods.values() {
+    for t in idl.method_keys.iter().filter_map(|k| idl.methods.get(k)) {
         let mut in_field_types = Vec::new();
         let mut in_field_names = Vec::new();
         let in_struct_name = Ident::new(&format!("{}_Args", t.name), Span::call_site());
@@ -336,11 +850,13 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
         let call_name = Ident::new(&format!("Call_{}", t.name), Span::call_site());
         let method_name = Ident::new(&to_snake_case(t.name), Span::call_site());
         let varlink_method_name = format!("{}.{}", idl.name, t.name);
+        let method_doc = ctx.doc(t.doc);
+        server_doc += &format!("\n- `{}`: `{}()`", varlink_method_name, method_name);
 
         generate_anon_struct(
             &format!("{}_{}", t.name, "Args"),
             &t.input,
-            options,
+            &ctx,
             &mut ts,
             &mut in_field_types,
             &mut in_field_names,
@@ -350,7 +866,7 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
         generate_anon_struct(
             &format!("{}_{}", t.name, "Reply"),
             &t.output,
-            options,
+            &ctx,
             &mut ts,
             &mut out_field_types,
             &mut out_field_names,
@@ -362,23 +878,30 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
             let out_field_types = out_field_types.iter();
             let in_field_names = in_field_names.iter();
             let in_field_types = in_field_types.iter();
+            let out_derive = ctx.derive(t.output.elts.iter().all(|e| ctx.is_hashable(&e.vtype)));
+            let in_derive = ctx.derive(t.input.elts.iter().all(|e| ctx.is_hashable(&e.vtype)));
+            let in_serde = match options.deny_unknown_fields {
+                true => quote!(#[serde(deny_unknown_fields)]),
+                false => quote!(),
+            };
 
             ts.extend(quote!(
-                #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+                #out_derive
                 pub struct #out_struct_name {
                                 #(#out_anot pub #out_field_names: #out_field_types,)*
                 }
 
                 impl varlink::VarlinkReply for #out_struct_name {}
 
-                #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+                #in_derive
+                #in_serde
                 pub struct #in_struct_name {
                                 #(#in_anot pub #in_field_names: #in_field_types,)*
                 }
             ));
         }
 
-        {
+        if options.target.server() {
             let field_names_1 = out_field_names.iter();
             let field_names_2 = out_field_names.iter();
             let field_types_1 = out_field_types.iter();
@@ -401,27 +924,68 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
                     }
                 ));
             }
-        }
 
-        ts.extend(quote!(
-            impl #call_name for varlink::Call<'_> {}
-        ));
+            ts.extend(quote!(
+                impl #call_name for varlink::Call<'_> {}
+            ));
+        }
 
         // #server_method_decls
         {
             let in_field_names = in_field_names.iter();
             let in_field_types = in_field_types.iter();
             server_method_decls.extend(quote!(
+                #method_doc
                 fn #method_name (&self, call: &mut dyn #call_name, #(#in_field_names: #in_field_types),*) ->
                 varlink::Result<()>;
             ));
         }
 
+        // #server_method_forwards
+        {
+            let in_field_names_2 = in_field_names.iter();
+            let in_field_names = in_field_names.iter();
+            let in_field_types = in_field_types.iter();
+            server_method_forwards.extend(quote!(
+                fn #method_name (&self, call: &mut dyn #call_name, #(#in_field_names: #in_field_types),*) ->
+                varlink::Result<()> {
+                    (**self).#method_name(call, #(#in_field_names_2),*)
+                }
+            ));
+        }
+
+        // #handler_method_decls and #handler_adapter_impls
+        {
+            let in_field_names_2 = in_field_names.iter();
+            let in_field_names_3 = in_field_names.iter();
+            let in_field_types_2 = in_field_types.iter();
+            let in_field_names = in_field_names.iter();
+            let in_field_types = in_field_types.iter();
+            handler_method_decls.extend(quote!(
+                #method_doc
+                fn #method_name(&self, #(#in_field_names: #in_field_types),*) ->
+                std::result::Result<#out_struct_name, Error>;
+            ));
+            handler_adapter_impls.extend(quote!(
+                fn #method_name(&self, call: &mut dyn #call_name, #(#in_field_names_2: #in_field_types_2),*) ->
+                varlink::Result<()> {
+                    match self.0.#method_name(#(#in_field_names_3),*) {
+                        Ok(reply) => call.reply_struct(reply.into()),
+                        Err(e) => match ::std::convert::TryFrom::try_from(e.0) {
+                            Ok(reply) => call.reply_struct(reply),
+                            Err(e) => Err(varlink::context!(e, varlink::ErrorKind::Server)),
+                        },
+                    }
+                }
+            ));
+        }
+
         // #client_method_decls
         {
             let in_field_names = in_field_names.iter();
             let in_field_types = in_field_types.iter();
             client_method_decls.extend(quote!(
+                #method_doc
                 fn #method_name(&mut self, #(#in_field_names: #in_field_types),*) ->
                 varlink::MethodCall<#in_struct_name, #out_struct_name, Error>;
             ));
@@ -444,6 +1008,19 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
             ));
         }
 
+        if options.generate_builders && options.target.client() && t.input.elts.len() > 3 {
+            generate_builder(
+                t,
+                &in_struct_name,
+                &out_struct_name,
+                &in_field_names,
+                &in_field_types,
+                &varlink_method_name,
+                &mut ts,
+                &mut client_builders,
+            );
+        }
+
         // #server_method_impls
         {
             let in_field_names = in_field_names.iter();
@@ -466,6 +1043,20 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
                         }
                     },
                 ));
+            } else if options.deny_unknown_fields {
+                server_method_impls.extend(quote!(
+                    #varlink_method_name => {
+                        if let Some(name) = req
+                            .parameters
+                            .as_ref()
+                            .and_then(|p| p.as_object())
+                            .and_then(|p| p.keys().next())
+                        {
+                            return call.reply_invalid_parameter(name.clone());
+                        }
+                        self.inner.#method_name(call as &mut dyn #call_name)
+                    },
+                ));
             } else {
                 server_method_impls.extend(quote!(
                     #varlink_method_name => self.inner.#method_name(call as &mut dyn #call_name),
@@ -474,98 +1065,336 @@ fn varlink_to_rust(idl: &IDL, options: &GeneratorOptions, tosource: bool) -> Res
         }
     }
 
-    ts.extend(quote!(
-        #[allow(dead_code)]
-        pub trait VarlinkInterface {
-            #server_method_decls
-
-            fn call_upgraded(&self, _call: &mut varlink::Call, _bufreader: &mut dyn BufRead) -> varlink::Result<Vec<u8>> {
-                Ok(Vec::new())
+    generate_method_enum(idl, &mut ts);
+
+    let more_check = match options.allow_more {
+        Some(ref allowed) => {
+            let rejected = idl
+                .method_keys
+                .iter()
+                .filter_map(|k| idl.methods.get(k))
+                .filter(|m| !allowed.iter().any(|a| a == m.name))
+                .map(|m| format!("{}.{}", idl.name, m.name))
+                .collect::<Vec<_>>();
+            if rejected.is_empty() {
+                quote!()
+            } else {
+                quote!(
+                    if call.wants_more() && matches!(req.method.as_ref(), #(#rejected)|*) {
+                        return call.reply_invalid_parameter("more".into());
+                    }
+                )
             }
         }
+        None => quote!(),
+    };
+
+    if options.target.server() {
+        ts.extend(quote!(
+            #interface_doc
+            #[doc = #server_doc]
+            #[allow(dead_code)]
+            pub trait VarlinkInterface {
+                #server_method_decls
+
+                fn call_upgraded(&self, _call: &mut varlink::Call, _bufreader: &mut dyn BufRead) -> varlink::Result<Vec<u8>> {
+                    Ok(Vec::new())
+                }
+            }
+
+            impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Box<T> {
+                #server_method_forwards
+
+                fn call_upgraded(&self, call: &mut varlink::Call, bufreader: &mut dyn BufRead) -> varlink::Result<Vec<u8>> {
+                    (**self).call_upgraded(call, bufreader)
+                }
+            }
+
+            impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Arc<T> {
+                #server_method_forwards
+
+                fn call_upgraded(&self, call: &mut varlink::Call, bufreader: &mut dyn BufRead) -> varlink::Result<Vec<u8>> {
+                    (**self).call_upgraded(call, bufreader)
+                }
+            }
+        ));
+    }
+
+    if options.generate_return_handler && options.target.server() {
+        ts.extend(quote!(
+            #interface_doc
+            /// Server side of the interface, whose methods return their reply.
+            ///
+            /// Wrap it in a `VarlinkHandlerAdapter` to serve it. A returned error of the interface
+            /// is replied to the client, `Varlink_Error` and `VarlinkReply_Error` fail the call
+            /// with `varlink::ErrorKind::Server`.
+            #[allow(dead_code)]
+            pub trait VarlinkHandler {
+                #handler_method_decls
+            }
+
+            /// The `VarlinkInterface` replying with the results of a `VarlinkHandler`
+            #[allow(dead_code)]
+            pub struct VarlinkHandlerAdapter<H>(pub H);
+
+            impl<H: VarlinkHandler> VarlinkInterface for VarlinkHandlerAdapter<H> {
+                #handler_adapter_impls
+            }
+        ));
+    }
+
+    if options.target.client() {
+        ts.extend(quote!(
+            #interface_doc
+            #[allow(dead_code)]
+            pub trait VarlinkClientInterface {
+                #client_method_decls
+            }
+
+            #[allow(dead_code)]
+            pub struct VarlinkClient {
+                connection: Arc<RwLock<varlink::Connection>>,
+            }
+
+            impl VarlinkClient {
+                #[allow(dead_code)]
+                pub fn new(connection: Arc<RwLock<varlink::Connection>>) -> Self {
+                    VarlinkClient {
+                        connection,
+                    }
+                }
+
+                /// Call the method `method` of this interface with untyped parameters and replies.
+                #[allow(dead_code)]
+                pub fn call_raw(
+                    &mut self,
+                    method: &str,
+                    params: serde_json::Value,
+                ) -> varlink::MethodCall<serde_json::Value, serde_json::Value, Error> {
+                    varlink::MethodCall::<serde_json::Value, serde_json::Value, Error>::new(
+                        self.connection.clone(),
+                        format!("{}.{}", #iname, method),
+                        params,
+                    )
+                }
+
+                #client_builders
+            }
+
+            impl VarlinkClientInterface for VarlinkClient {
+                #client_method_impls
+            }
+        ));
+    }
+
+    if options.target.server() {
+        ts.extend(quote!(
+            #[allow(dead_code)]
+            pub struct VarlinkInterfaceProxy {
+                inner: Box<dyn VarlinkInterface + Send + Sync>,
+            }
+
+            #[allow(dead_code)]
+            pub fn new(inner: Box<dyn VarlinkInterface + Send + Sync>) -> VarlinkInterfaceProxy {
+                VarlinkInterfaceProxy { inner }
+            }
+
+            impl varlink::Interface for VarlinkInterfaceProxy {
+                fn get_description(&self) -> &'static str {
+                    #description
+                }
+
+                fn get_name(&self) -> &'static str {
+                    #iname
+                }
+
+                fn call_upgraded(&self, call: &mut varlink::Call, bufreader: &mut dyn BufRead) -> varlink::Result<Vec<u8>> {
+                    self.inner.call_upgraded(call, bufreader)
+                }
+
+                fn call(&self, call: &mut varlink::Call) -> varlink::Result<()> {
+                    let req = call.request.unwrap();
+                    #more_check
+                    match req.method.as_ref() {
+                        #server_method_impls
+                        m => {
+                            call.reply_method_not_found(String::from(m))
+                        }
+                    }
+                }
+            }
+        ));
+    }
+
+    Ok(ts)
+}
 
+/// Generate `enum Method` with a variant for every method of the interface
+///
+/// The enum is prefixed with `Varlink`, until it doesn't collide with a type of the interface.
+fn generate_method_enum(idl: &IDL, ts: &mut TokenStream) {
+    let mut enum_name = String::from("Method");
+    while idl.typedefs.contains_key(enum_name.as_str()) {
+        enum_name.insert_str(0, "Varlink");
+    }
+    let enum_name = format_ident!("{}", enum_name);
+    let variants = idl
+        .method_keys
+        .iter()
+        .map(|name| format_ident!("{}", name))
+        .collect::<Vec<_>>();
+    let names = idl
+        .method_keys
+        .iter()
+        .map(|name| format!("{}.{}", idl.name, name))
+        .collect::<Vec<_>>();
+    let doc = format!("The methods of the `{}` interface", idl.name);
+
+    ts.extend(quote!(
+        #[doc = #doc]
         #[allow(dead_code)]
-        pub trait VarlinkClientInterface {
-            #client_method_decls
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum #enum_name {
+            #(#variants,)*
         }
 
         #[allow(dead_code)]
-        pub struct VarlinkClient {
-            connection: Arc<RwLock<varlink::Connection>>,
+        impl #enum_name {
+            /// All methods in the order of the interface definition
+            pub const ALL: &'static [#enum_name] = &[#(#enum_name::#variants),*];
+
+            /// The fully qualified varlink name of the method
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(#enum_name::#variants => #names,)*
+                }
+            }
         }
 
-        impl VarlinkClient {
-            #[allow(dead_code)]
-            pub fn new(connection: Arc<RwLock<varlink::Connection>>) -> Self {
-                VarlinkClient {
-                    connection,
+        impl ::std::str::FromStr for #enum_name {
+            type Err = varlink::Error;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match s {
+                    #(#names => Ok(#enum_name::#variants),)*
+                    _ => Err(varlink::context!(varlink::ErrorKind::MethodNotFound(s.into()))),
                 }
             }
         }
 
-        impl VarlinkClientInterface for VarlinkClient {
-            #client_method_impls
+        impl ::std::fmt::Display for #enum_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
         }
+    ));
+}
 
-        #[allow(dead_code)]
-        pub struct VarlinkInterfaceProxy {
-            inner: Box<dyn VarlinkInterface + Send + Sync>,
+/// Generate the `<Method>ArgsBuilder` for `method` and the `VarlinkClient` method returning it
+#[allow(clippy::too_many_arguments)]
+fn generate_builder(
+    method: &Method,
+    in_struct_name: &Ident,
+    out_struct_name: &Ident,
+    field_names: &[Ident],
+    field_types: &[TokenStream],
+    varlink_method_name: &str,
+    ts: &mut TokenStream,
+    client_builders: &mut TokenStream,
+) {
+    let builder_name = format_ident!("{}ArgsBuilder", method.name);
+    let builder_method = format_ident!("{}_builder", to_snake_case(method.name));
+    let values = method.input.elts.iter().zip(field_names).map(|(e, name)| {
+        let field_str = e.name;
+        if let VTypeExt::Option(_) = e.vtype {
+            quote!(self.#name.unwrap_or(None))
+        } else {
+            quote!(self.#name.ok_or_else(|| {
+                varlink::context!(varlink::ErrorKind::InvalidParameter(#field_str.into()))
+            })?)
         }
+    });
+    let field_names_1 = field_names.iter();
+    let field_names_2 = field_names.iter();
+    let field_names_3 = field_names.iter();
+    let field_names_4 = field_names.iter();
+    let field_types_1 = field_types.iter();
+    let field_types_2 = field_types.iter();
+    let builder_doc = format!(
+        "Builder for the parameters of `{}`, returned by `VarlinkClient::{}()`.",
+        varlink_method_name, builder_method
+    );
 
+    ts.extend(quote!(
+        #[doc = #builder_doc]
         #[allow(dead_code)]
-        pub fn new(inner: Box<dyn VarlinkInterface + Send + Sync>) -> VarlinkInterfaceProxy {
-            VarlinkInterfaceProxy { inner }
+        pub struct #builder_name {
+            connection: Arc<RwLock<varlink::Connection>>,
+            #(#field_names_1: Option<#field_types_1>,)*
         }
 
-        impl varlink::Interface for VarlinkInterfaceProxy {
-            fn get_description(&self) -> &'static str {
-                #description
-            }
+        #[allow(dead_code)]
+        impl #builder_name {
+            #(
+                pub fn #field_names_2(mut self, value: #field_types_2) -> Self {
+                    self.#field_names_3 = Some(value);
+                    self
+                }
+            )*
 
-            fn get_name(&self) -> &'static str {
-                #iname
+            /// Returns the parameters or `ErrorKind::InvalidParameter` for a missing parameter.
+            pub fn build(self) -> varlink::Result<#in_struct_name> {
+                Ok(#in_struct_name {
+                    #(#field_names_4: #values,)*
+                })
             }
 
-            fn call_upgraded(&self, call: &mut varlink::Call, bufreader: &mut dyn BufRead) -> varlink::Result<Vec<u8>> {
-                self.inner.call_upgraded(call, bufreader)
+            /// Build the parameters and prepare the method call.
+            pub fn call(self) -> varlink::Result<varlink::MethodCall<#in_struct_name, #out_struct_name, Error>> {
+                let connection = self.connection.clone();
+                Ok(varlink::MethodCall::<#in_struct_name, #out_struct_name, Error>::new(
+                    connection,
+                    #varlink_method_name,
+                    self.build()?,
+                ))
             }
+        }
+    ));
 
-            fn call(&self, call: &mut varlink::Call) -> varlink::Result<()> {
-                let req = call.request.unwrap();
-                match req.method.as_ref() {
-                    #server_method_impls
-                    m => {
-                        call.reply_method_not_found(String::from(m))
-                    }
-                }
+    let field_names = field_names.iter();
+    client_builders.extend(quote!(
+        #[allow(dead_code)]
+        pub fn #builder_method(&self) -> #builder_name {
+            #builder_name {
+                connection: self.connection.clone(),
+                #(#field_names: None,)*
             }
         }
     ));
-
-    Ok(ts)
 }
 
 fn generate_anon_struct(
     name: &str,
     vstruct: &VStruct,
-    options: &GeneratorOptions,
+    ctx: &Context,
     ts: &mut TokenStream,
     field_types: &mut Vec<TokenStream>,
     field_names: &mut Vec<Ident>,
     anot: &mut Vec<TokenStream>,
 ) {
     for e in &vstruct.elts {
-        anot.push(if let VTypeExt::Option(_) = e.vtype {
+        let skip = if let VTypeExt::Option(_) = e.vtype {
             quote!(#[serde(skip_serializing_if = "Option::is_none")])
         } else {
-            quote!()
-        });
-        let ename_ident: Ident = syn::parse_str(&(String::from("r#") + e.name)).unwrap();
-        field_names.push(ename_ident);
+            ctx.skip_empty(&e.vtype)
+        };
+        let rename = ctx.field_rename(e.name);
+        anot.push(quote!(#rename #skip));
+        field_names.push(ctx.field_ident(e.name));
         field_types.push(
             TokenStream::from_str(
                 e.vtype
-                    .to_rust_string(format!("{}_{}", name, e.name).as_ref(), ts, options)
+                    .to_rust_string(format!("{}_{}", name, e.name).as_ref(), ts, ctx)
                     .as_ref(),
             )
             .unwrap(),
@@ -573,11 +1402,7 @@ fn generate_anon_struct(
     }
 }
 
-fn generate_error_code(
-    options: &GeneratorOptions,
-    idl: &varlink_parser::IDL,
-    ts: &mut TokenStream,
-) {
+fn generate_error_code(ctx: &Context, idl: &varlink_parser::IDL, ts: &mut TokenStream) {
     // Errors traits
     {
         let mut error_structs_and_enums = TokenStream::new();
@@ -585,14 +1410,15 @@ fn generate_error_code(
         {
             let mut errors = Vec::new();
             let mut errors_display = Vec::new();
-            for t in idl.errors.values() {
-                errors.push(
-                    TokenStream::from_str(&format!(
-                        "{ename}(Option<{ename}_Args>)",
-                        ename = t.name,
-                    ))
-                    .unwrap(),
-                );
+            let mut errors_args = Vec::new();
+            for t in idl.error_keys.iter().filter_map(|k| idl.errors.get(k)) {
+                let doc = ctx.doc(t.doc);
+                let variant = TokenStream::from_str(&format!(
+                    "{ename}(Option<{ename}_Args>)",
+                    ename = t.name,
+                ))
+                .unwrap();
+                errors.push(quote!(#doc #variant));
                 errors_display.push(
                     TokenStream::from_str(&format!(
                         "ErrorKind::{ename}(v) => write!(f, \"{iname}.{ename}: {{:#?}}\", v)",
@@ -601,12 +1427,43 @@ fn generate_error_code(
                     ))
                     .unwrap(),
                 );
+                errors_args.push(Ident::new(t.name, Span::call_site()));
+
+                // `org.example.NotFound (name: "foo", id: 1)`
+                let args_name = Ident::new(&format!("{}_Args", t.name), Span::call_site());
+                let mut format = format!("{}.{}", idl.name, t.name);
+                if !t.parm.elts.is_empty() {
+                    let fields = t
+                        .parm
+                        .elts
+                        .iter()
+                        .map(|e| format!("{}: {{:?}}", e.name))
+                        .collect::<Vec<_>>();
+                    format += &format!(" ({})", fields.join(", "));
+                }
+                let fields = t.parm.elts.iter().map(|e| ctx.field_ident(e.name));
+                error_structs_and_enums.extend(quote!(
+                    impl std::fmt::Display for #args_name {
+                        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                            write!(f, #format #(, self.#fields)*)
+                        }
+                    }
+
+                    impl std::error::Error for #args_name {}
+                ));
             }
 
+            let non_exhaustive = if ctx.options.non_exhaustive_errors {
+                quote!(#[non_exhaustive])
+            } else {
+                quote!()
+            };
+
             ts.extend(quote!(
                 #[allow(dead_code)]
                 #[derive(Clone, PartialEq, Debug)]
                 #[allow(clippy::enum_variant_names)]
+                #non_exhaustive
                 pub enum ErrorKind {
                     Varlink_Error,
                     VarlinkReply_Error,
@@ -621,6 +1478,17 @@ fn generate_error_code(
                         }
                     }
                 }
+                impl ErrorKind {
+                    /// The parameters of an error of the interface as a `std::error::Error`,
+                    /// whose `Display` includes their values.
+                    #[allow(dead_code)]
+                    pub fn into_args_error(self) -> Option<Box<dyn std::error::Error + Send + Sync>> {
+                        match self {
+                            #(ErrorKind::#errors_args(Some(v)) => Some(Box::new(v)),)*
+                            _ => None,
+                        }
+                    }
+                }
             ));
         }
         ts.extend(quote!(
@@ -709,7 +1577,7 @@ fn generate_error_code(
     ));
         {
             let mut arms = TokenStream::new();
-            for t in idl.errors.values() {
+            for t in idl.error_keys.iter().filter_map(|k| idl.errors.get(k)) {
                 let error_name = format!("{iname}.{ename}", iname = idl.name, ename = t.name);
                 let ename = TokenStream::from_str(&format!("ErrorKind::{}", t.name)).unwrap();
                 arms.extend(quote!(
@@ -740,7 +1608,49 @@ fn generate_error_code(
                 }
             ));
         }
-        for t in idl.errors.values() {
+        {
+            let mut arms = TokenStream::new();
+            for t in idl.error_keys.iter().filter_map(|k| idl.errors.get(k)) {
+                let error_name = format!("{iname}.{ename}", iname = idl.name, ename = t.name);
+                let ename = TokenStream::from_str(&format!("ErrorKind::{}", t.name)).unwrap();
+                if t.parm.elts.is_empty() {
+                    arms.extend(quote!(
+                        #ename(_) => Ok(varlink::Reply::error(#error_name, None)),
+                    ));
+                } else {
+                    arms.extend(quote!(
+                        #ename(v) => Ok(varlink::Reply::error(
+                            #error_name,
+                            match v {
+                                Some(v) => Some(
+                                    serde_json::to_value(v)
+                                        .map_err(varlink::map_context!())
+                                        .map_err(Error::from)?,
+                                ),
+                                None => None,
+                            },
+                        )),
+                    ));
+                }
+            }
+
+            // the other kinds are no errors of the interface, so the caller has to decide
+            ts.extend(quote!(
+                impl ::std::convert::TryFrom<ErrorKind> for varlink::Reply {
+                    type Error = Error;
+
+                    fn try_from(e: ErrorKind) -> std::result::Result<Self, Error> {
+                        match e {
+                            #arms
+                            ErrorKind::Varlink_Error | ErrorKind::VarlinkReply_Error => {
+                                Err(Error::from(e))
+                            }
+                        }
+                    }
+                }
+            ));
+        }
+        for t in idl.error_keys.iter().filter_map(|k| idl.errors.get(k)) {
             let mut inparms_name = Vec::new();
             let mut inparms_type = Vec::new();
 
@@ -749,16 +1659,14 @@ fn generate_error_code(
             let args_name = Ident::new(&format!("{}_Args", t.name), Span::call_site());
             if !t.parm.elts.is_empty() {
                 for e in &t.parm.elts {
-                    let ename_ident: Ident =
-                        syn::parse_str(&(String::from("r#") + e.name)).unwrap();
-                    inparms_name.push(ename_ident);
+                    inparms_name.push(ctx.field_ident(e.name));
                     inparms_type.push(
                         TokenStream::from_str(
                             e.vtype
                                 .to_rust_string(
                                     format!("{}_Args_{}", t.name, e.name).as_ref(),
                                     &mut error_structs_and_enums,
-                                    options,
+                                    ctx,
                                 )
                                 .as_ref(),
                         )
@@ -785,30 +1693,45 @@ fn generate_error_code(
                 }
             ));
         }
-        ts.extend(quote!(
-            #error_structs_and_enums
-            #[allow(dead_code)]
-            pub trait VarlinkCallError: varlink::CallTrait {
-                #funcs
-            }
-        ));
+        ts.extend(error_structs_and_enums);
+        if ctx.options.target.server() {
+            ts.extend(quote!(
+                #[allow(dead_code)]
+                pub trait VarlinkCallError: varlink::CallTrait {
+                    #funcs
+                }
+
+                impl VarlinkCallError for varlink::Call<'_> {}
+            ));
+        }
     }
-    ts.extend(quote!(
-        impl VarlinkCallError for varlink::Call<'_> {}
-    ));
 }
 
 pub fn compile(source: String) -> Result<TokenStream> {
-    let idl = IDL::try_from(source.as_str()).map_err(Error::Parse)?;
-    varlink_to_rust(
-        &idl,
+    compile_with_options(
+        source,
         &GeneratorOptions {
             ..Default::default()
         },
-        true,
     )
 }
 
+/// `compile_with_options` generates the rust code for the varlink interface definition `source`
+/// like [`compile`](fn.compile.html), but with the given `options`.
+pub fn compile_with_options(source: String, options: &GeneratorOptions) -> Result<TokenStream> {
+    let idl = IDL::try_from(source.as_str()).map_err(Error::Parse)?;
+    varlink_to_rust(&idl, options, true)
+}
+
+/// `compile_to_string` generates the rust code for the varlink interface definition `source`.
+///
+/// The code is not formatted and is meant to be included with `include!()`,
+/// like the output of [`cargo_build`](fn.cargo_build.html).
+pub fn compile_to_string(source: &str, options: &GeneratorOptions) -> Result<String> {
+    let idl = IDL::try_from(source).map_err(Error::Parse)?;
+    Ok(varlink_to_rust(&idl, options, false)?.to_string())
+}
+
 /// `generate` reads a varlink interface definition from `reader` and writes
 /// the rust code to `writer`.
 pub fn generate(reader: &mut dyn Read, writer: &mut dyn Write, tosource: bool) -> Result<()> {
@@ -842,6 +1765,59 @@ pub fn generate_with_options(
         .map_err(Error::Io)
 }
 
+/// Generates the rust code for the varlink file `input_path`.
+///
+/// Returns the rust code and the warnings, which should be passed on to cargo.
+fn generate_file(
+    input_path: &Path,
+    options: &GeneratorOptions,
+    tosource: bool,
+) -> Result<(String, Vec<String>)> {
+    let mut buffer = String::new();
+
+    File::open(input_path)
+        .and_then(|mut f| f.read_to_string(&mut buffer))
+        .map_err(Error::Io)?;
+
+    let idl = IDL::try_from(buffer.as_str()).map_err(Error::Parse)?;
+    let ts = varlink_to_rust(&idl, options, tosource)?;
+
+    let warnings = if options.derive_hash {
+        unhashable_typedefs(&idl).into_values().collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok((ts.to_string(), warnings))
+}
+
+/// The path of the generated rust code for `input_path` in `out_dir`.
+fn out_dir_rust_path(out_dir: &Path, input_path: &Path) -> PathBuf {
+    out_dir
+        .join(input_path.file_name().unwrap())
+        .with_extension("rs")
+}
+
+/// Generates the rust code for the varlink file `input_path` in a `build.rs` program.
+///
+/// Warnings are emitted for cargo, errors are emitted to stderr and terminate the process.
+fn cargo_generate(input_path: &Path, options: &GeneratorOptions, tosource: bool) -> String {
+    let (source, warnings) = generate_file(input_path, options, tosource).unwrap_or_else(|e| {
+        eprintln!(
+            "Could not generate rust code from varlink file `{}`: {}",
+            input_path.display(),
+            e,
+        );
+        exit(1);
+    });
+
+    for warning in warnings {
+        println!("cargo:warning={}", warning);
+    }
+
+    source
+}
+
 /// cargo build helper function
 ///
 /// `cargo_build` is used in a `build.rs` program to build the rust code
@@ -961,40 +1937,154 @@ where
         let input_path = input_path.as_ref();
 
         let out_dir: PathBuf = env::var_os("OUT_DIR").unwrap().into();
-        let rust_path = out_dir
-            .join(input_path.file_name().unwrap())
-            .with_extension("rs");
+        let rust_path = out_dir_rust_path(&out_dir, input_path);
+
+        let source = cargo_generate(input_path, options, false);
 
-        let writer: &mut dyn Write = &mut (File::create(&rust_path).unwrap_or_else(|e| {
+        if let Err(e) = std::fs::write(&rust_path, source) {
             eprintln!(
-                "Could not open varlink output file `{}`: {}",
+                "Could not write varlink output file `{}`: {}",
                 rust_path.display(),
                 e
             );
             exit(1);
-        }));
+        }
 
-        let reader: &mut dyn Read = &mut (File::open(input_path).unwrap_or_else(|e| {
+        println!("cargo:rerun-if-changed={}", input_path.display());
+    }
+}
+
+/// cargo build helper function
+///
+/// `cargo_build_dir` is used in a `build.rs` program to build the rust code for all
+/// `*.varlink` files in the directory `dir` like [`cargo_build_options_many`].
+///
+/// Additionally the file `varlink_interfaces.rs` is written to `OUT_DIR`, which declares
+/// a module for every interface file. The name of the module is the file name without
+/// the extension and "." replaced with "_".
+///
+/// Errors are emitted to stderr and terminate the process.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// extern crate varlink_generator;
+///
+/// fn main() {
+///     varlink_generator::cargo_build_dir(
+///         "src/interfaces",
+///         &varlink_generator::GeneratorOptions::default(),
+///     );
+/// }
+/// ```
+///
+/// and in your crate:
+///
+/// ```rust,ignore
+/// include!(concat!(env!("OUT_DIR"), "/varlink_interfaces.rs"));
+///
+/// use crate::org_example_ping::*;
+/// ```
+pub fn cargo_build_dir<T: AsRef<Path> + ?Sized>(dir: &T, options: &GeneratorOptions) {
+    let dir = dir.as_ref();
+
+    let mut input_paths = std::fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<Vec<_>>>()
+        })
+        .unwrap_or_else(|e| {
             eprintln!(
-                "Could not read varlink input file `{}`: {}",
-                input_path.display(),
+                "Could not read varlink directory `{}`: {}",
+                dir.display(),
                 e
             );
             exit(1);
-        }));
+        });
+    input_paths.retain(|p| p.is_file() && p.extension().is_some_and(|e| e == "varlink"));
+    input_paths.sort();
+
+    cargo_build_options_many(&input_paths, options);
+
+    let out_dir: PathBuf = env::var_os("OUT_DIR").unwrap().into();
+    let mut modules = String::new();
+    for input_path in &input_paths {
+        let module = input_path
+            .file_stem()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .replace(['.', '-'], "_");
+        let file_name = out_dir_rust_path(&out_dir, input_path);
+        modules += &format!(
+            "pub mod {} {{\n    include!(concat!(env!(\"OUT_DIR\"), \"/{}\"));\n}}\n",
+            module,
+            file_name.file_name().unwrap().to_str().unwrap()
+        );
+    }
 
-        if let Err(e) = generate_with_options(reader, writer, options, false) {
-            eprintln!(
-                "Could not generate rust code from varlink file `{}`: {}",
-                input_path.display(),
-                e,
-            );
+    let rust_path = out_dir.join("varlink_interfaces.rs");
+    if let Err(e) = std::fs::write(&rust_path, modules) {
+        eprintln!(
+            "Could not write varlink output file `{}`: {}",
+            rust_path.display(),
+            e
+        );
+        exit(1);
+    }
 
-            exit(1);
-        }
+    println!("cargo:rerun-if-changed={}", dir.display());
+}
 
-        println!("cargo:rerun-if-changed={}", input_path.display());
-    }
+/// cargo build helper function
+///
+/// `cargo_build_dry_run` generates the rust code like [`cargo_build_options_many`],
+/// but does not write anything. It returns the path of the output file in `OUT_DIR`
+/// and the generated rust code for every input file.
+///
+/// Use this to debug a `build.rs` program or to inspect the generated code in a test.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// extern crate varlink_generator;
+///
+/// fn main() {
+///     for (path, source) in varlink_generator::cargo_build_dry_run(
+///         &["src/org.example.ping.varlink"],
+///         &varlink_generator::GeneratorOptions::default(),
+///     )
+///     .unwrap()
+///     {
+///         eprintln!("{}: {} bytes", path.display(), source.len());
+///     }
+/// }
+/// ```
+pub fn cargo_build_dry_run<T>(
+    input_paths: &[T],
+    options: &GeneratorOptions,
+) -> Result<Vec<(PathBuf, String)>>
+where
+    T: AsRef<Path>,
+{
+    let out_dir: PathBuf = env::var_os("OUT_DIR")
+        .ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "environment variable OUT_DIR is not set",
+            ))
+        })?
+        .into();
+
+    input_paths
+        .iter()
+        .map(|input_path| {
+            let input_path = input_path.as_ref();
+            let (source, _) = generate_file(input_path, options, false)?;
+            Ok((out_dir_rust_path(&out_dir, input_path), source))
+        })
+        .collect()
 }
 
 /// cargo build helper function
@@ -1044,7 +2134,8 @@ pub fn cargo_build_tosource<T: AsRef<Path> + ?Sized>(input_path: &T, rustfmt: bo
 ///
 /// Set `rustfmt` to `true`, if you want the generator to run rustfmt on the
 /// generated code. This might be good practice to avoid large changes after a
-/// global `cargo fmt` run.
+/// global `cargo fmt` run. Use `rustfmt_edition` and `rustfmt_config_path` of
+/// [`GeneratorOptions`] to format the code like the rest of your crate.
 ///
 /// Errors are emitted to stderr and terminate the process.
 ///
@@ -1082,38 +2173,26 @@ pub fn cargo_build_tosource_options<T: AsRef<Path> + ?Sized>(
         .unwrap()
         .join(Path::new(&newfilename).with_extension("rs"));
 
-    let writer: &mut dyn Write = &mut (File::create(&rust_path).unwrap_or_else(|e| {
-        eprintln!(
-            "Could not open varlink output file `{}`: {}",
-            rust_path.display(),
-            e
-        );
-        exit(1);
-    }));
+    let source = cargo_generate(input_path, options, true);
 
-    let reader: &mut dyn Read = &mut (File::open(input_path).unwrap_or_else(|e| {
+    if let Err(e) = std::fs::write(&rust_path, source) {
         eprintln!(
-            "Could not read varlink input file `{}`: {}",
-            input_path.display(),
+            "Could not write varlink output file `{}`: {}",
+            rust_path.display(),
             e
         );
         exit(1);
-    }));
-
-    if let Err(e) = generate_with_options(reader, writer, options, true) {
-        eprintln!(
-            "Could not generate rust code from varlink file `{}`: {}",
-            input_path.display(),
-            e,
-        );
-        exit(1);
     }
 
     if rustfmt {
-        if let Err(e) = Command::new("rustfmt")
-            .arg(rust_path.to_str().unwrap())
-            .output()
-        {
+        let mut cmd = Command::new("rustfmt");
+        if let Some(ref edition) = options.rustfmt_edition {
+            cmd.arg("--edition").arg(edition);
+        }
+        if let Some(ref config_path) = options.rustfmt_config_path {
+            cmd.arg("--config-path").arg(config_path);
+        }
+        if let Err(e) = cmd.arg(&rust_path).output() {
             eprintln!(
                 "Could not run rustfmt on file `{}` {}",
                 rust_path.display(),
@@ -2,9 +2,50 @@ extern crate varlink_generator;
 
 use std::fs;
 use std::io::prelude::*;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
 use tempdir::TempDir;
 use unified_diff::diff;
+use varlink_generator::GeneratorOptions;
+
+// Tests setting `OUT_DIR` must not run concurrently.
+static OUT_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+fn rustfmt(code: &str) -> String {
+    let mut child = Command::new("rustfmt")
+        .arg("--edition")
+        .arg("2018")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(code.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The code generated for the interface `source`, formatted by rustfmt.
+fn generate(source: &str, options: &GeneratorOptions) -> String {
+    rustfmt(&varlink_generator::compile_to_string(source, options).unwrap())
+}
+
+/// Assert, that every snippet of formatted code is contained in `generated`.
+fn assert_contains(generated: &str, snippets: &[&str]) {
+    for snippet in snippets {
+        assert!(
+            generated.contains(snippet),
+            "`{}` not generated:\n{}",
+            snippet,
+            generated
+        );
+    }
+}
 
 #[cfg(unix)]
 #[test]
@@ -33,6 +74,7 @@ fn test_generate() {
         }
     }
 
+    let _lock = OUT_DIR_LOCK.lock().unwrap();
     let tmpdir = TempDir::new("test_generate").unwrap();
 
     std::env::set_var("OUT_DIR", tmpdir.path().as_os_str());
@@ -76,3 +118,579 @@ fn test_generate() {
     let _ = std::fs::remove_file(path);
     let _ = std::fs::remove_file(path2);
 }
+
+#[test]
+fn test_derive_hash() {
+    let source = r#"
+interface org.example.hash
+
+type Point (x: int, y: int, label: ?string, kind: (cartesian, polar))
+
+type Measurement (point: Point, value: float)
+
+type Series (measurements: []Measurement)
+
+type Tags (tags: [string]())
+
+method Get(point: Point) -> (measurement: Measurement)
+"#;
+
+    let generated = generate(
+        source,
+        &GeneratorOptions {
+            derive_hash: true,
+            ..Default::default()
+        },
+    );
+
+    let hashable =
+        "#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, Default)]\npub ";
+    let unhashable = "#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]\npub ";
+
+    for t in ["struct r#Point {", "struct Get_Args {"] {
+        assert!(
+            generated.contains(&format!("{}{}", hashable, t)),
+            "`{}` should derive Hash:\n{}",
+            t,
+            generated
+        );
+    }
+
+    for t in [
+        "struct r#Measurement {",
+        "struct r#Series {",
+        "struct r#Tags {",
+        "struct Get_Reply {",
+    ] {
+        assert!(
+            generated.contains(&format!("{}{}", unhashable, t)),
+            "`{}` should not derive Hash:\n{}",
+            t,
+            generated
+        );
+    }
+
+    // enums always derive `Hash`
+    let derive_enum =
+        "#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Eq, Hash, PartialOrd, Ord, Default)]";
+    assert!(
+        generated.contains(&format!("{}\npub enum r#Point_kind {{", derive_enum)),
+        "{}",
+        generated
+    );
+}
+
+#[test]
+fn test_oneof() {
+    let source = r#"
+interface org.example.oneof
+
+# @oneof
+type State (start: ?bool, progress: ?int, end: ?bool)
+
+method Get() -> (state: State)
+"#;
+
+    assert_contains(
+        &generate(source, &GeneratorOptions::default()),
+        &[
+            "pub struct State_Fields {",
+            "#[serde(try_from = \"State_Fields\", into = \"State_Fields\")]\npub enum r#State {\n    \
+             r#start(bool),\n    r#progress(i64),\n    r#end(bool),\n}",
+            "pub fn as_progress(&self) -> Option<&i64> {",
+        ],
+    );
+
+    let source = r#"
+interface org.example.oneof
+
+# @oneof
+type State (start: ?bool, progress: int)
+
+method Get() -> (state: State)
+"#;
+
+    let e = varlink_generator::generate(&mut source.as_bytes(), &mut Vec::new(), false)
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "org.example.oneof.State: @oneof requires a struct with only optional fields"
+    );
+}
+
+#[test]
+fn test_skip_empty_collections() {
+    let source = r#"
+interface org.example.skip
+
+type Tags (names: []string, set: [string](), map: [string]int, opt: ?[]string)
+
+method Get(ids: []int) -> (tags: Tags)
+"#;
+
+    let options = |skip_empty_collections| GeneratorOptions {
+        skip_empty_collections,
+        ..Default::default()
+    };
+
+    let generated = generate(source, &options(true));
+    assert_contains(
+        &generated,
+        &[
+            "    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]\n    \
+             pub r#names: Vec<String>,",
+            "    #[serde(default, skip_serializing_if = \"varlink::StringHashSet::is_empty\")]\n    \
+             pub r#set: varlink::StringHashSet,",
+            "    #[serde(default, skip_serializing_if = \"std::collections::HashMap::is_empty\")]\n    \
+             pub r#map: varlink::StringHashMap<i64>,",
+            "    pub r#map: varlink::StringHashMap<i64>,\n    pub r#opt: Option<Vec<String>>,",
+            "    #[serde(default, skip_serializing_if = \"Vec::is_empty\")]\n    \
+             pub r#ids: Vec<i64>,",
+        ],
+    );
+
+    assert!(!generate(source, &options(false)).contains("is_empty"));
+}
+
+#[test]
+fn test_generate_builders() {
+    let source = r#"
+interface org.example.builder
+
+method Small(a: int, b: int, c: int) -> ()
+method Large(a: int, b: string, c: ?bool, d: []int) -> ()
+"#;
+
+    let options = |generate_builders| GeneratorOptions {
+        generate_builders,
+        ..Default::default()
+    };
+
+    let generated = generate(source, &options(true));
+    assert_contains(
+        &generated,
+        &[
+            "pub struct LargeArgsBuilder {",
+            "    pub fn large_builder(&self) -> LargeArgsBuilder {",
+            "            r#c: self.r#c.unwrap_or(None),",
+        ],
+    );
+    assert!(!generated.contains("SmallArgsBuilder"));
+
+    assert!(!generate(source, &options(false)).contains("ArgsBuilder"));
+}
+
+#[test]
+fn test_emit_doc_comments() {
+    let source = r#"
+# The interface
+interface org.example.doc
+
+# A type
+# with two lines
+type Item (name: string)
+
+# Get the item
+method Get() -> (item: Item)
+
+# No item found
+error NotFound ()
+"#;
+
+    let options = |emit_doc_comments| GeneratorOptions {
+        emit_doc_comments,
+        ..Default::default()
+    };
+
+    assert_contains(
+        &generate(source, &options(true)),
+        &[
+            "#[doc = \"The interface\"]\n#[doc = \"Server side",
+            "#[doc = \"A type\\nwith two lines\"]\n#[derive",
+            "    #[doc = \"Get the item\"]\n    fn get(&self, call",
+            "    #[doc = \"Get the item\"]\n    fn get(&mut self",
+            "    #[doc = \"No item found\"]\n    NotFound(Option<NotFound_Args>),",
+        ],
+    );
+
+    assert!(!generate(source, &options(false)).contains("Get the item\""));
+}
+
+#[test]
+fn test_generate_return_handler() {
+    let source = r#"
+interface org.example.handler
+
+method Get(name: string) -> (value: int)
+"#;
+
+    let options = |generate_return_handler| GeneratorOptions {
+        generate_return_handler,
+        ..Default::default()
+    };
+
+    assert_contains(
+        &generate(source, &options(true)),
+        &[
+            "pub trait VarlinkHandler {\n    \
+             fn get(&self, r#name: String) -> std::result::Result<Get_Reply, Error>;\n}",
+            "impl<H: VarlinkHandler> VarlinkInterface for VarlinkHandlerAdapter<H> {",
+        ],
+    );
+
+    assert!(!generate(source, &options(false)).contains("VarlinkHandler"));
+}
+
+#[test]
+fn test_method_enum_collision() {
+    let generated = generate(
+        r#"
+interface org.example.methods
+
+type Method (name: string)
+
+method Call(method: Method) -> ()
+"#,
+        &GeneratorOptions::default(),
+    );
+    assert_contains(
+        &generated,
+        &[
+            "pub struct r#Method {",
+            "pub enum VarlinkMethod {\n    Call,\n}",
+        ],
+    );
+    assert!(!generated.contains("pub enum Method "), "{}", generated);
+
+    let generated = generate(
+        r#"
+interface org.example.methods
+
+type Method (name: string)
+
+type VarlinkMethod (name: string)
+
+method Call(method: Method, other: VarlinkMethod) -> ()
+"#,
+        &GeneratorOptions::default(),
+    );
+    assert_contains(
+        &generated,
+        &["pub enum VarlinkVarlinkMethod {\n    Call,\n}"],
+    );
+}
+
+#[test]
+fn test_dict_and_set_type() {
+    let source = r#"
+interface org.example.dict
+
+type Counts (counts: [string]int, names: [string](), nested: [string][string]bool)
+"#;
+
+    let generated = generate(
+        source,
+        &GeneratorOptions {
+            dict_type: Some("std::collections::BTreeMap<String, _>"),
+            set_type: Some("MySet"),
+            skip_empty_collections: true,
+            ..Default::default()
+        },
+    );
+
+    assert_contains(
+        &generated,
+        &[
+            "    #[serde(default, skip_serializing_if = \"std::collections::BTreeMap::is_empty\")]\n    \
+             pub r#counts: std::collections::BTreeMap<String, i64>,",
+            "    #[serde(default, skip_serializing_if = \"MySet::is_empty\")]\n    \
+             pub r#names: MySet,",
+            "    pub r#nested: std::collections::BTreeMap<String, std::collections::BTreeMap<String, bool>>,",
+        ],
+    );
+    assert!(!generated.contains("StringHash"));
+}
+
+#[test]
+fn test_non_exhaustive_errors() {
+    let source = "interface org.example.errors\nmethod Ping() -> ()\nerror Failed ()\n";
+    let options = |non_exhaustive_errors| GeneratorOptions {
+        non_exhaustive_errors,
+        ..Default::default()
+    };
+
+    assert_contains(
+        &generate(source, &options(true)),
+        &["#[non_exhaustive]\npub enum ErrorKind {"],
+    );
+    assert!(!generate(source, &options(false)).contains("non_exhaustive"));
+}
+
+#[test]
+fn test_target() {
+    use varlink_generator::Target;
+
+    let source = "interface org.example.target\nmethod Ping() -> ()\nerror Failed ()\n";
+
+    for (target, client, server) in [
+        (Target::Both, true, true),
+        (Target::ClientOnly, true, false),
+        (Target::ServerOnly, false, true),
+    ] {
+        let generated = generate(
+            source,
+            &GeneratorOptions {
+                target,
+                ..Default::default()
+            },
+        );
+        assert_contains(
+            &generated,
+            &["pub enum ErrorKind {", "pub struct Failed_Args {}"],
+        );
+        assert_eq!(generated.contains("pub struct VarlinkClient {"), client);
+        assert_eq!(
+            generated.contains("pub struct VarlinkInterfaceProxy {"),
+            server
+        );
+        assert_eq!(generated.contains("pub trait Call_Ping:"), server);
+        assert_eq!(generated.contains("pub trait VarlinkCallError:"), server);
+    }
+}
+
+#[test]
+fn test_declaration_order() {
+    let source = r#"
+interface org.example.order
+
+type Zulu (a: int)
+type Bravo (b: int)
+
+method Zeta() -> ()
+method Alpha() -> ()
+
+error Yankee ()
+error Charlie ()
+"#;
+
+    let generated = generate(source, &GeneratorOptions::default());
+
+    for (first, second) in [
+        ("pub struct r#Zulu {", "pub struct r#Bravo {"),
+        ("pub struct Zeta_Reply {}", "pub struct Alpha_Reply {}"),
+        ("pub struct Yankee_Args {}", "pub struct Charlie_Args {}"),
+        ("    fn zeta(", "    fn alpha("),
+    ] {
+        assert!(
+            generated.find(first).unwrap() < generated.find(second).unwrap(),
+            "`{}` not generated before `{}`:\n{}",
+            first,
+            second,
+            generated
+        );
+    }
+}
+
+#[test]
+fn test_removed_method() {
+    let generate = |source| generate(source, &GeneratorOptions::default());
+
+    let both =
+        generate("interface org.example.methods\nmethod Ping() -> ()\nmethod Pong() -> ()\n");
+    let ping_only = generate("interface org.example.methods\nmethod Ping() -> ()\n");
+
+    let pong_trait = "    fn pong(&self, call: &mut dyn Call_Pong) -> varlink::Result<()>;";
+    let pong_dispatch = "\"org.example.methods.Pong\" =>";
+    let pong_doc = "- `org.example.methods.Pong`: `pong()`";
+
+    for t in [pong_trait, pong_dispatch, pong_doc] {
+        assert!(both.contains(t), "`{}` not generated:\n{}", t, both);
+        assert!(
+            !ping_only.contains(t),
+            "`{}` generated for removed method:\n{}",
+            t,
+            ping_only
+        );
+    }
+    assert!(ping_only.contains("\"org.example.methods.Ping\" =>"));
+}
+
+#[test]
+fn test_dry_run() {
+    let _lock = OUT_DIR_LOCK.lock().unwrap();
+    let tmpdir = TempDir::new("test_dry_run").unwrap();
+
+    std::env::set_var("OUT_DIR", tmpdir.path().as_os_str());
+    let generated = varlink_generator::cargo_build_dry_run(
+        &["tests/org.example.complex.varlink"],
+        &varlink_generator::GeneratorOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(generated.len(), 1);
+    let (path, source) = &generated[0];
+    assert_eq!(*path, tmpdir.path().join("org.example.complex.rs"));
+    assert!(source.contains("VarlinkInterface"));
+
+    // nothing is written
+    assert_eq!(fs::read_dir(tmpdir.path()).unwrap().count(), 0);
+
+    assert!(varlink_generator::cargo_build_dry_run(
+        &["tests/nonexistent.varlink"],
+        &varlink_generator::GeneratorOptions::default(),
+    )
+    .is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_build_dir() {
+    let _lock = OUT_DIR_LOCK.lock().unwrap();
+    let out_dir = TempDir::new("test_build_dir_out").unwrap();
+    let varlink_dir = TempDir::new("test_build_dir").unwrap();
+
+    for name in ["org.example.ping", "org.example.more"] {
+        fs::copy(
+            format!("tests/golden/{}.varlink", name),
+            varlink_dir.path().join(format!("{}.varlink", name)),
+        )
+        .unwrap();
+    }
+    fs::write(varlink_dir.path().join("README"), "not an interface").unwrap();
+
+    std::env::set_var("OUT_DIR", out_dir.path().as_os_str());
+    varlink_generator::cargo_build_dir(
+        varlink_dir.path(),
+        &varlink_generator::GeneratorOptions::default(),
+    );
+
+    let modules = fs::read_to_string(out_dir.path().join("varlink_interfaces.rs")).unwrap();
+    assert_eq!(
+        modules,
+        r#"pub mod org_example_more {
+    include!(concat!(env!("OUT_DIR"), "/org.example.more.rs"));
+}
+pub mod org_example_ping {
+    include!(concat!(env!("OUT_DIR"), "/org.example.ping.rs"));
+}
+"#
+    );
+
+    for file in [
+        "varlink_interfaces.rs",
+        "org.example.more.rs",
+        "org.example.ping.rs",
+    ] {
+        let status = Command::new("rustfmt")
+            .arg("--emit")
+            .arg("stdout")
+            .arg(out_dir.path().join(file))
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "{}", file);
+    }
+}
+
+/// Compares the generated code for the interfaces in `tests/golden` with the committed
+/// `.rs` files. Set `VARLINK_UPDATE_GOLDEN=1` to regenerate them.
+#[cfg(unix)]
+#[test]
+fn test_golden() {
+    let update = std::env::var_os("VARLINK_UPDATE_GOLDEN").is_some();
+    let mut failed = Vec::new();
+
+    for name in [
+        "org.example.ping",
+        "org.example.more",
+        "org.varlink.certification",
+    ] {
+        let input = format!("tests/golden/{}.varlink", name);
+        let golden = format!("tests/golden/{}.rs", name);
+
+        let source = fs::read_to_string(&input).unwrap();
+        let generated = generate(&source, &GeneratorOptions::default());
+
+        if update {
+            fs::write(&golden, &generated).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&golden).unwrap_or_default();
+        if expected != generated {
+            std::io::stdout()
+                .write_all(&diff(
+                    expected.as_bytes(),
+                    &golden,
+                    generated.as_bytes(),
+                    &input,
+                    1,
+                ))
+                .unwrap();
+            failed.push(golden);
+        }
+    }
+
+    assert!(
+        failed.is_empty(),
+        "Generated code differs from {:?}, run with VARLINK_UPDATE_GOLDEN=1 to update",
+        failed
+    );
+}
+
+#[test]
+fn test_deny_unknown_fields() {
+    let source = r#"
+interface org.example.ping
+
+method Ping(ping: string) -> (pong: string)
+"#;
+
+    let options = |deny_unknown_fields| GeneratorOptions {
+        deny_unknown_fields,
+        ..Default::default()
+    };
+
+    let generated = generate(source, &options(true));
+    assert_contains(
+        &generated,
+        &["#[serde(deny_unknown_fields)]\npub struct Ping_Args {"],
+    );
+    assert!(!generated.contains("#[serde(deny_unknown_fields)]\npub struct Ping_Reply {"));
+    assert!(!generate(source, &options(false)).contains("deny_unknown_fields"));
+}
+
+#[test]
+fn test_snake_case_fields() {
+    let source = r#"
+interface org.example.dhcp
+
+type Lease (clientId: string, address: string)
+
+method Release(ifIndex: int, lease: Lease) -> ()
+
+error UnknownClient (clientId: string)
+"#;
+
+    let options = |snake_case_fields| GeneratorOptions {
+        snake_case_fields,
+        ..Default::default()
+    };
+
+    let generated = generate(source, &options(true));
+    assert_contains(
+        &generated,
+        &[
+            "    #[serde(rename = \"clientId\")]\n    pub r#client_id: String,\n    \
+             pub r#address: String,",
+            "    #[serde(rename = \"ifIndex\")]\n    pub r#if_index: i64,",
+            "    fn reply_unknown_client(&mut self, r#client_id: String) -> varlink::Result<()> {",
+        ],
+    );
+    assert!(!generated.contains("rename = \"address\""));
+
+    let generated = generate(source, &options(false));
+    assert_contains(&generated, &["    pub r#clientId: String,"]);
+    assert!(!generated.contains("rename"));
+}
@@ -0,0 +1,512 @@
+use serde_derive::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::sync::{Arc, RwLock};
+use varlink::{self, CallTrait};
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum ErrorKind {
+    Varlink_Error,
+    VarlinkReply_Error,
+    TestMoreError(Option<TestMoreError_Args>),
+}
+impl ::std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match self {
+            ErrorKind::Varlink_Error => write!(f, "Varlink Error"),
+            ErrorKind::VarlinkReply_Error => write!(f, "Varlink error reply"),
+            ErrorKind::TestMoreError(v) => write!(f, "org.example.more.TestMoreError: {:#?}", v),
+        }
+    }
+}
+impl ErrorKind {
+    #[doc = r" The parameters of an error of the interface as a `std::error::Error`,"]
+    #[doc = r" whose `Display` includes their values."]
+    #[allow(dead_code)]
+    pub fn into_args_error(self) -> Option<Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            ErrorKind::TestMoreError(Some(v)) => Some(Box::new(v)),
+            _ => None,
+        }
+    }
+}
+pub struct Error(
+    pub ErrorKind,
+    pub Option<Box<dyn std::error::Error + 'static + Send + Sync>>,
+    pub Option<&'static str>,
+);
+impl Error {
+    #[allow(dead_code)]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.0
+    }
+}
+impl From<ErrorKind> for Error {
+    fn from(e: ErrorKind) -> Self {
+        Error(e, None, None)
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.1
+            .as_ref()
+            .map(|e| e.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+impl std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use std::error::Error as StdError;
+        if let Some(ref o) = self.2 {
+            std::fmt::Display::fmt(o, f)?;
+        }
+        std::fmt::Debug::fmt(&self.0, f)?;
+        if let Some(e) = self.source() {
+            std::fmt::Display::fmt("\nCaused by:\n", f)?;
+            std::fmt::Debug::fmt(&e, f)?;
+        }
+        Ok(())
+    }
+}
+#[allow(dead_code)]
+pub type Result<T> = std::result::Result<T, Error>;
+impl From<varlink::Error> for Error {
+    fn from(e: varlink::Error) -> Self {
+        match e.kind() {
+            varlink::ErrorKind::VarlinkErrorReply(r) => Error(
+                ErrorKind::from(r),
+                Some(Box::from(e)),
+                Some(concat!(file!(), ":", line!(), ": ")),
+            ),
+            _ => Error(
+                ErrorKind::Varlink_Error,
+                Some(Box::from(e)),
+                Some(concat!(file!(), ":", line!(), ": ")),
+            ),
+        }
+    }
+}
+#[allow(dead_code)]
+impl Error {
+    pub fn source_varlink_kind(&self) -> Option<&varlink::ErrorKind> {
+        use std::error::Error as StdError;
+        let mut s: &dyn StdError = self;
+        while let Some(c) = s.source() {
+            let k = self
+                .source()
+                .and_then(|e| e.downcast_ref::<varlink::Error>())
+                .map(|e| e.kind());
+            if k.is_some() {
+                return k;
+            }
+            s = c;
+        }
+        None
+    }
+}
+impl From<&varlink::Reply> for ErrorKind {
+    #[allow(unused_variables)]
+    fn from(e: &varlink::Reply) -> Self {
+        match e {
+            varlink::Reply {
+                error: Some(ref t), ..
+            } if t == "org.example.more.TestMoreError" => match e {
+                varlink::Reply {
+                    parameters: Some(p),
+                    ..
+                } => match serde_json::from_value(p.clone()) {
+                    Ok(v) => ErrorKind::TestMoreError(v),
+                    Err(_) => ErrorKind::TestMoreError(None),
+                },
+                _ => ErrorKind::TestMoreError(None),
+            },
+            _ => ErrorKind::VarlinkReply_Error,
+        }
+    }
+}
+impl ::std::convert::TryFrom<ErrorKind> for varlink::Reply {
+    type Error = Error;
+    fn try_from(e: ErrorKind) -> std::result::Result<Self, Error> {
+        match e {
+            ErrorKind::TestMoreError(v) => Ok(varlink::Reply::error(
+                "org.example.more.TestMoreError",
+                match v {
+                    Some(v) => Some(
+                        serde_json::to_value(v)
+                            .map_err(varlink::map_context!())
+                            .map_err(Error::from)?,
+                    ),
+                    None => None,
+                },
+            )),
+            ErrorKind::Varlink_Error | ErrorKind::VarlinkReply_Error => Err(Error::from(e)),
+        }
+    }
+}
+impl std::fmt::Display for TestMoreError_Args {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "org.example.more.TestMoreError (reason: {:?})",
+            self.r#reason
+        )
+    }
+}
+impl std::error::Error for TestMoreError_Args {}
+#[allow(dead_code)]
+pub trait VarlinkCallError: varlink::CallTrait {
+    fn reply_test_more_error(&mut self, r#reason: String) -> varlink::Result<()> {
+        self.reply_struct(varlink::Reply::error(
+            "org.example.more.TestMoreError",
+            Some(
+                serde_json::to_value(TestMoreError_Args { r#reason })
+                    .map_err(varlink::map_context!())?,
+            ),
+        ))
+    }
+}
+impl VarlinkCallError for varlink::Call<'_> {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct State_Fields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#start: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#progress: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#end: Option<bool>,
+}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(try_from = "State_Fields", into = "State_Fields")]
+pub enum r#State {
+    r#start(bool),
+    r#progress(i64),
+    r#end(bool),
+}
+impl Default for r#State {
+    fn default() -> Self {
+        r#State::r#start(Default::default())
+    }
+}
+impl ::std::convert::TryFrom<State_Fields> for r#State {
+    type Error = &'static str;
+    fn try_from(fields: State_Fields) -> ::std::result::Result<Self, Self::Error> {
+        match fields {
+            State_Fields {
+                r#start: Some(v),
+                r#progress: None,
+                r#end: None,
+            } => Ok(r#State::r#start(v)),
+            State_Fields {
+                r#start: None,
+                r#progress: Some(v),
+                r#end: None,
+            } => Ok(r#State::r#progress(v)),
+            State_Fields {
+                r#start: None,
+                r#progress: None,
+                r#end: Some(v),
+            } => Ok(r#State::r#end(v)),
+            _ => Err("exactly one field of `State` has to be set"),
+        }
+    }
+}
+impl From<r#State> for State_Fields {
+    fn from(v: r#State) -> Self {
+        match v {
+            r#State::r#start(v) => State_Fields {
+                r#start: Some(v),
+                r#progress: None,
+                r#end: None,
+            },
+            r#State::r#progress(v) => State_Fields {
+                r#start: None,
+                r#progress: Some(v),
+                r#end: None,
+            },
+            r#State::r#end(v) => State_Fields {
+                r#start: None,
+                r#progress: None,
+                r#end: Some(v),
+            },
+        }
+    }
+}
+impl r#State {
+    #[allow(dead_code, unreachable_patterns)]
+    pub fn as_start(&self) -> Option<&bool> {
+        match self {
+            r#State::r#start(v) => Some(v),
+            _ => None,
+        }
+    }
+    #[allow(dead_code, unreachable_patterns)]
+    pub fn as_progress(&self) -> Option<&i64> {
+        match self {
+            r#State::r#progress(v) => Some(v),
+            _ => None,
+        }
+    }
+    #[allow(dead_code, unreachable_patterns)]
+    pub fn as_end(&self) -> Option<&bool> {
+        match self {
+            r#State::r#end(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct TestMoreError_Args {
+    pub r#reason: String,
+}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Ping_Reply {
+    pub r#pong: String,
+}
+impl varlink::VarlinkReply for Ping_Reply {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct Ping_Args {
+    pub r#ping: String,
+}
+#[allow(dead_code)]
+pub trait Call_Ping: VarlinkCallError {
+    fn reply(&mut self, r#pong: String) -> varlink::Result<()> {
+        self.reply_struct(Ping_Reply { r#pong }.into())
+    }
+}
+impl Call_Ping for varlink::Call<'_> {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct TestMore_Reply {
+    pub r#state: State,
+}
+impl varlink::VarlinkReply for TestMore_Reply {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct TestMore_Args {
+    pub r#n: i64,
+}
+#[allow(dead_code)]
+pub trait Call_TestMore: VarlinkCallError {
+    fn reply(&mut self, r#state: State) -> varlink::Result<()> {
+        self.reply_struct(TestMore_Reply { r#state }.into())
+    }
+}
+impl Call_TestMore for varlink::Call<'_> {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct StopServing_Reply {}
+impl varlink::VarlinkReply for StopServing_Reply {}
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct StopServing_Args {}
+#[allow(dead_code)]
+pub trait Call_StopServing: VarlinkCallError {
+    fn reply(&mut self) -> varlink::Result<()> {
+        self.reply_struct(varlink::Reply::parameters(None))
+    }
+}
+impl Call_StopServing for varlink::Call<'_> {}
+#[doc = "The methods of the `org.example.more` interface"]
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Method {
+    Ping,
+    TestMore,
+    StopServing,
+}
+#[allow(dead_code)]
+impl Method {
+    #[doc = r" All methods in the order of the interface definition"]
+    pub const ALL: &'static [Method] = &[Method::Ping, Method::TestMore, Method::StopServing];
+    #[doc = r" The fully qualified varlink name of the method"]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Ping => "org.example.more.Ping",
+            Method::TestMore => "org.example.more.TestMore",
+            Method::StopServing => "org.example.more.StopServing",
+        }
+    }
+}
+impl ::std::str::FromStr for Method {
+    type Err = varlink::Error;
+    fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+        match s {
+            "org.example.more.Ping" => Ok(Method::Ping),
+            "org.example.more.TestMore" => Ok(Method::TestMore),
+            "org.example.more.StopServing" => Ok(Method::StopServing),
+            _ => Err(varlink::context!(varlink::ErrorKind::MethodNotFound(
+                s.into()
+            ))),
+        }
+    }
+}
+impl ::std::fmt::Display for Method {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+#[doc = "Server side of the `org.example.more` interface.\n\nThe methods are called for these varlink methods:\n\n- `org.example.more.Ping`: `ping()`\n- `org.example.more.TestMore`: `test_more()`\n- `org.example.more.StopServing`: `stop_serving()`"]
+#[allow(dead_code)]
+pub trait VarlinkInterface {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()>;
+    fn test_more(&self, call: &mut dyn Call_TestMore, r#n: i64) -> varlink::Result<()>;
+    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()>;
+    fn call_upgraded(
+        &self,
+        _call: &mut varlink::Call,
+        _bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+}
+impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Box<T> {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()> {
+        (**self).ping(call, r#ping)
+    }
+    fn test_more(&self, call: &mut dyn Call_TestMore, r#n: i64) -> varlink::Result<()> {
+        (**self).test_more(call, r#n)
+    }
+    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()> {
+        (**self).stop_serving(call)
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        (**self).call_upgraded(call, bufreader)
+    }
+}
+impl<T: VarlinkInterface + ?Sized> VarlinkInterface for Arc<T> {
+    fn ping(&self, call: &mut dyn Call_Ping, r#ping: String) -> varlink::Result<()> {
+        (**self).ping(call, r#ping)
+    }
+    fn test_more(&self, call: &mut dyn Call_TestMore, r#n: i64) -> varlink::Result<()> {
+        (**self).test_more(call, r#n)
+    }
+    fn stop_serving(&self, call: &mut dyn Call_StopServing) -> varlink::Result<()> {
+        (**self).stop_serving(call)
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        (**self).call_upgraded(call, bufreader)
+    }
+}
+#[allow(dead_code)]
+pub trait VarlinkClientInterface {
+    fn ping(&mut self, r#ping: String) -> varlink::MethodCall<Ping_Args, Ping_Reply, Error>;
+    fn test_more(&mut self, r#n: i64) -> varlink::MethodCall<TestMore_Args, TestMore_Reply, Error>;
+    fn stop_serving(&mut self) -> varlink::MethodCall<StopServing_Args, StopServing_Reply, Error>;
+}
+#[allow(dead_code)]
+pub struct VarlinkClient {
+    connection: Arc<RwLock<varlink::Connection>>,
+}
+impl VarlinkClient {
+    #[allow(dead_code)]
+    pub fn new(connection: Arc<RwLock<varlink::Connection>>) -> Self {
+        VarlinkClient { connection }
+    }
+    #[doc = r" Call the method `method` of this interface with untyped parameters and replies."]
+    #[allow(dead_code)]
+    pub fn call_raw(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> varlink::MethodCall<serde_json::Value, serde_json::Value, Error> {
+        varlink::MethodCall::<serde_json::Value, serde_json::Value, Error>::new(
+            self.connection.clone(),
+            format!("{}.{}", "org.example.more", method),
+            params,
+        )
+    }
+}
+impl VarlinkClientInterface for VarlinkClient {
+    fn ping(&mut self, r#ping: String) -> varlink::MethodCall<Ping_Args, Ping_Reply, Error> {
+        varlink::MethodCall::<Ping_Args, Ping_Reply, Error>::new(
+            self.connection.clone(),
+            "org.example.more.Ping",
+            Ping_Args { r#ping },
+        )
+    }
+    fn test_more(&mut self, r#n: i64) -> varlink::MethodCall<TestMore_Args, TestMore_Reply, Error> {
+        varlink::MethodCall::<TestMore_Args, TestMore_Reply, Error>::new(
+            self.connection.clone(),
+            "org.example.more.TestMore",
+            TestMore_Args { r#n },
+        )
+    }
+    fn stop_serving(&mut self) -> varlink::MethodCall<StopServing_Args, StopServing_Reply, Error> {
+        varlink::MethodCall::<StopServing_Args, StopServing_Reply, Error>::new(
+            self.connection.clone(),
+            "org.example.more.StopServing",
+            StopServing_Args {},
+        )
+    }
+}
+#[allow(dead_code)]
+pub struct VarlinkInterfaceProxy {
+    inner: Box<dyn VarlinkInterface + Send + Sync>,
+}
+#[allow(dead_code)]
+pub fn new(inner: Box<dyn VarlinkInterface + Send + Sync>) -> VarlinkInterfaceProxy {
+    VarlinkInterfaceProxy { inner }
+}
+impl varlink::Interface for VarlinkInterfaceProxy {
+    fn get_description(&self) -> &'static str {
+        "# Example Varlink service\ninterface org.example.more\n\n# Enum, returning either start, progress or end\n# progress: [0-100]\n# @oneof\ntype State (\n  start: ?bool,\n  progress: ?int,\n  end: ?bool\n)\n\n# Returns the same string\nmethod Ping(ping: string) -> (pong: string)\n\n# Dummy progress method\n# n: number of progress steps\nmethod TestMore(n: int) -> (state: State)\n\n# Stop serving\nmethod StopServing() -> ()\n\n# Something failed in TestMore\nerror TestMoreError (reason: string)\n"
+    }
+    fn get_name(&self) -> &'static str {
+        "org.example.more"
+    }
+    fn call_upgraded(
+        &self,
+        call: &mut varlink::Call,
+        bufreader: &mut dyn BufRead,
+    ) -> varlink::Result<Vec<u8>> {
+        self.inner.call_upgraded(call, bufreader)
+    }
+    fn call(&self, call: &mut varlink::Call) -> varlink::Result<()> {
+        let req = call.request.unwrap();
+        match req.method.as_ref() {
+            "org.example.more.Ping" => {
+                if let Some(args) = req.parameters.clone() {
+                    let args: Ping_Args = match serde_json::from_value(args) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            let es = format!("{}", e);
+                            let _ = call.reply_invalid_parameter(es.clone());
+                            return Err(varlink::context!(varlink::ErrorKind::SerdeJsonDe(es)));
+                        }
+                    };
+                    self.inner.ping(call as &mut dyn Call_Ping, args.r#ping)
+                } else {
+                    call.reply_invalid_parameter("parameters".into())
+                }
+            }
+            "org.example.more.TestMore" => {
+                if let Some(args) = req.parameters.clone() {
+                    let args: TestMore_Args = match serde_json::from_value(args) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            let es = format!("{}", e);
+                            let _ = call.reply_invalid_parameter(es.clone());
+                            return Err(varlink::context!(varlink::ErrorKind::SerdeJsonDe(es)));
+                        }
+                    };
+                    self.inner
+                        .test_more(call as &mut dyn Call_TestMore, args.r#n)
+                } else {
+                    call.reply_invalid_parameter("parameters".into())
+                }
+            }
+            "org.example.more.StopServing" => {
+                self.inner.stop_serving(call as &mut dyn Call_StopServing)
+            }
+            m => call.reply_method_not_found(String::from(m)),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+extern crate varlink_generator;
+
+use std::fs;
+use tempdir::TempDir;
+
+/// Replaces `rustfmt` in `PATH` with a script recording its arguments.
+///
+/// This lives in its own test binary, so the other tests still find the real `rustfmt`.
+#[cfg(unix)]
+#[test]
+fn test_rustfmt_options() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmpdir = TempDir::new("test_rustfmt_options").unwrap();
+    let bin = tmpdir.path().join("bin");
+    let args_file = tmpdir.path().join("args");
+    fs::create_dir(&bin).unwrap();
+
+    let stub = bin.join("rustfmt");
+    fs::write(
+        &stub,
+        format!("#!/bin/sh\necho \"$@\" > '{}'\n", args_file.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin];
+    paths.extend(std::env::split_paths(&path));
+    std::env::set_var("PATH", std::env::join_paths(paths).unwrap());
+
+    let varlink = tmpdir.path().join("org.example.ping.varlink");
+    fs::copy("tests/golden/org.example.ping.varlink", &varlink).unwrap();
+    let config = tmpdir.path().join("rustfmt.toml");
+
+    varlink_generator::cargo_build_tosource_options(
+        &varlink,
+        true,
+        &varlink_generator::GeneratorOptions {
+            rustfmt_edition: Some("2021".into()),
+            rustfmt_config_path: Some(config.clone()),
+            ..Default::default()
+        },
+    );
+
+    let rust_path = tmpdir.path().join("org_example_ping.rs");
+    assert!(rust_path.exists());
+    assert_eq!(
+        fs::read_to_string(&args_file).unwrap().trim_end(),
+        format!(
+            "--edition 2021 --config-path {} {}",
+            config.display(),
+            rust_path.display()
+        )
+    );
+}
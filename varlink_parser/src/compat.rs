@@ -0,0 +1,292 @@
+use std::fmt;
+
+use crate::*;
+
+/// A change of an interface, which breaks existing clients or services
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+    /// The method was removed.
+    RemovedMethod(String),
+    /// The error was removed.
+    RemovedError(String),
+    /// The field was removed.
+    RemovedField { path: String },
+    /// A field, which is not optional, was added, where a client has to send it.
+    AddedRequiredField { path: String },
+    /// The type of the field changed.
+    ChangedType {
+        path: String,
+        old: String,
+        new: String,
+    },
+    /// The optional field is required now, where a client has to send it.
+    FieldBecameRequired { path: String },
+    /// The required field is optional now, where a client receives it.
+    FieldBecameOptional { path: String },
+    /// The value was removed from the enum.
+    RemovedEnumVariant { path: String, variant: String },
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::RemovedMethod(name) => write!(f, "method `{}` was removed", name),
+            Incompatibility::RemovedError(name) => write!(f, "error `{}` was removed", name),
+            Incompatibility::RemovedField { path } => write!(f, "field `{}` was removed", path),
+            Incompatibility::AddedRequiredField { path } => {
+                write!(f, "required field `{}` was added", path)
+            }
+            Incompatibility::ChangedType { path, old, new } => {
+                write!(f, "type of `{}` changed from `{}` to `{}`", path, old, new)
+            }
+            Incompatibility::FieldBecameRequired { path } => {
+                write!(f, "optional field `{}` became required", path)
+            }
+            Incompatibility::FieldBecameOptional { path } => {
+                write!(f, "required field `{}` became optional", path)
+            }
+            Incompatibility::RemovedEnumVariant { path, variant } => {
+                write!(f, "value `{}` was removed from `{}`", variant, path)
+            }
+        }
+    }
+}
+
+/// Compare two versions of an interface and return the changes of `new`, which break
+/// users of `old`.
+///
+/// Additions, like new methods, errors, types or optional fields, are compatible and
+/// not reported. Fields are named by a path like `Method.input.field`, `Method.output.field`,
+/// `Error.field` or `Type.field`.
+///
+/// Whether a field may become required or optional depends on who sends it: clients send the
+/// method input, services send the method output and the error parameters. Types count as
+/// sent by both, if they are used in both, in either version of the interface.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::convert::TryFrom;
+/// use varlink_parser::{compatibility, Incompatibility, IDL};
+///
+/// let old = IDL::try_from("interface org.example.foo\nmethod Foo(a: ?int) -> ()").unwrap();
+/// let new = IDL::try_from("interface org.example.foo\nmethod Foo(a: int) -> ()").unwrap();
+///
+/// assert_eq!(
+///     compatibility(&old, &new),
+///     vec![Incompatibility::FieldBecameRequired {
+///         path: "Foo.input.a".into()
+///     }]
+/// );
+/// ```
+pub fn compatibility(old: &IDL, new: &IDL) -> Vec<Incompatibility> {
+    let mut result = Vec::new();
+
+    let mut directions = BTreeMap::new();
+    for idl in [old, new] {
+        for m in idl.methods.values() {
+            mark_struct(&m.input, INPUT, idl, &mut directions);
+            mark_struct(&m.output, OUTPUT, idl, &mut directions);
+        }
+        for e in idl.errors.values() {
+            mark_struct(&e.parm, OUTPUT, idl, &mut directions);
+        }
+    }
+
+    for name in &old.typedef_keys {
+        let t = &old.typedefs[name];
+        let n = match new.typedefs.get(name) {
+            Some(n) => n,
+            // a removed type only matters, if it is still used
+            None => continue,
+        };
+        let direction = directions.get(*name).copied().unwrap_or_default();
+        match (&t.elt, &n.elt) {
+            (VStructOrEnum::VStruct(o), VStructOrEnum::VStruct(n)) => {
+                compare_struct(o, n, name, direction, &mut result)
+            }
+            (VStructOrEnum::VEnum(o), VStructOrEnum::VEnum(n)) => {
+                compare_enum(o, n, name, &mut result)
+            }
+            (o, n) => result.push(Incompatibility::ChangedType {
+                path: name.to_string(),
+                old: o.get_oneline(),
+                new: n.get_oneline(),
+            }),
+        }
+    }
+
+    for name in &old.method_keys {
+        let m = &old.methods[name];
+        match new.methods.get(name) {
+            Some(n) => {
+                compare_struct(
+                    &m.input,
+                    &n.input,
+                    &format!("{}.input", name),
+                    INPUT,
+                    &mut result,
+                );
+                compare_struct(
+                    &m.output,
+                    &n.output,
+                    &format!("{}.output", name),
+                    OUTPUT,
+                    &mut result,
+                );
+            }
+            None => result.push(Incompatibility::RemovedMethod(name.to_string())),
+        }
+    }
+
+    for name in &old.error_keys {
+        let e = &old.errors[name];
+        match new.errors.get(name) {
+            Some(n) => compare_struct(&e.parm, &n.parm, name, OUTPUT, &mut result),
+            None => result.push(Incompatibility::RemovedError(name.to_string())),
+        }
+    }
+
+    result
+}
+
+/// Who sends a struct: clients send the `input`, services the `output`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Direction {
+    input: bool,
+    output: bool,
+}
+
+const INPUT: Direction = Direction {
+    input: true,
+    output: false,
+};
+
+const OUTPUT: Direction = Direction {
+    input: false,
+    output: true,
+};
+
+/// Add `direction` to the types used by `s` and the types used by those.
+fn mark_struct(
+    s: &VStruct,
+    direction: Direction,
+    idl: &IDL,
+    directions: &mut BTreeMap<String, Direction>,
+) {
+    for e in &s.elts {
+        mark_vtype(&e.vtype, direction, idl, directions);
+    }
+}
+
+fn mark_vtype(
+    vtype: &VTypeExt,
+    direction: Direction,
+    idl: &IDL,
+    directions: &mut BTreeMap<String, Direction>,
+) {
+    match vtype {
+        VTypeExt::Array(t) | VTypeExt::Dict(t) | VTypeExt::Option(t) => {
+            mark_vtype(t, direction, idl, directions)
+        }
+        VTypeExt::Plain(VType::Struct(s)) => mark_struct(s, direction, idl, directions),
+        VTypeExt::Plain(VType::Typename(name)) => {
+            let d = directions.entry(name.to_string()).or_default();
+            if (d.input || !direction.input) && (d.output || !direction.output) {
+                // already marked, which also stops at recursive types
+                return;
+            }
+            d.input |= direction.input;
+            d.output |= direction.output;
+            if let Some(Typedef {
+                elt: VStructOrEnum::VStruct(s),
+                ..
+            }) = idl.typedefs.get(name)
+            {
+                mark_struct(s, direction, idl, directions);
+            }
+        }
+        VTypeExt::Plain(_) => {}
+    }
+}
+
+fn compare_struct(
+    old: &VStruct,
+    new: &VStruct,
+    path: &str,
+    direction: Direction,
+    result: &mut Vec<Incompatibility>,
+) {
+    for o in &old.elts {
+        let path = format!("{}.{}", path, o.name);
+        match new.elts.iter().find(|n| n.name == o.name) {
+            Some(n) => compare_vtype(&o.vtype, &n.vtype, &path, direction, result),
+            None => result.push(Incompatibility::RemovedField { path }),
+        }
+    }
+
+    // new required fields break existing clients, but not services
+    if direction.input {
+        for n in &new.elts {
+            if old.elts.iter().all(|o| o.name != n.name) && !matches!(n.vtype, VTypeExt::Option(_))
+            {
+                result.push(Incompatibility::AddedRequiredField {
+                    path: format!("{}.{}", path, n.name),
+                });
+            }
+        }
+    }
+}
+
+fn compare_enum(old: &VEnum, new: &VEnum, path: &str, result: &mut Vec<Incompatibility>) {
+    for variant in old.elts.iter().filter(|v| !new.elts.contains(v)) {
+        result.push(Incompatibility::RemovedEnumVariant {
+            path: path.into(),
+            variant: variant.to_string(),
+        });
+    }
+}
+
+fn compare_vtype(
+    old: &VTypeExt,
+    new: &VTypeExt,
+    path: &str,
+    direction: Direction,
+    result: &mut Vec<Incompatibility>,
+) {
+    match (old, new) {
+        (VTypeExt::Option(o), VTypeExt::Option(n))
+        | (VTypeExt::Array(o), VTypeExt::Array(n))
+        | (VTypeExt::Dict(o), VTypeExt::Dict(n)) => compare_vtype(o, n, path, direction, result),
+        (VTypeExt::Option(o), n) => {
+            // clients might not send the field
+            if direction.input {
+                result.push(Incompatibility::FieldBecameRequired { path: path.into() });
+            }
+            compare_vtype(o, n, path, direction, result)
+        }
+        (o, VTypeExt::Option(n)) => {
+            // clients might rely on getting the field
+            if direction.output {
+                result.push(Incompatibility::FieldBecameOptional { path: path.into() });
+            }
+            compare_vtype(o, n, path, direction, result)
+        }
+        (VTypeExt::Plain(VType::Struct(o)), VTypeExt::Plain(VType::Struct(n))) => {
+            compare_struct(o, n, path, direction, result)
+        }
+        (VTypeExt::Plain(VType::Enum(o)), VTypeExt::Plain(VType::Enum(n))) => {
+            compare_enum(o, n, path, result)
+        }
+        (o, n) => {
+            let (old, new) = (o.get_oneline(), n.get_oneline());
+            if old != new {
+                result.push(Incompatibility::ChangedType {
+                    path: path.into(),
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+}
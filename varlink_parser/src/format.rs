@@ -335,6 +335,20 @@ impl FormatColored for VEnum<'_> {
     }
 }
 
+impl IDL<'_> {
+    /// The first declaration is always separated from the `interface` line by a blank line,
+    /// the others only if the source had one or `preserve_blank_lines` is not set.
+    fn separator(&self, first: &mut bool, preceded_by_blank_line: bool) -> &'static str {
+        let separator = if *first || preceded_by_blank_line || !self.preserve_blank_lines {
+            "\n"
+        } else {
+            ""
+        };
+        *first = false;
+        separator
+    }
+}
+
 impl fmt::Display for IDL<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(&self.get_multiline(0, 80))
@@ -344,6 +358,7 @@ impl fmt::Display for IDL<'_> {
 impl Format for IDL<'_> {
     fn get_oneline(&self) -> String {
         let mut f = String::new();
+        let mut first = true;
 
         if !self.doc.is_empty() {
             f += self.doc;
@@ -352,7 +367,7 @@ impl Format for IDL<'_> {
         f += &format!("{} {}\n", "interface", self.name);
 
         for t in self.typedef_keys.iter().map(|k| &self.typedefs[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += t.doc;
                 f += "\n";
@@ -362,7 +377,7 @@ impl Format for IDL<'_> {
         }
 
         for m in self.method_keys.iter().map(|k| &self.methods[k]) {
-            f += "\n";
+            f += self.separator(&mut first, m.preceded_by_blank_line);
             if !m.doc.is_empty() {
                 f += m.doc;
                 f += "\n";
@@ -379,7 +394,7 @@ impl Format for IDL<'_> {
         }
 
         for t in self.error_keys.iter().map(|k| &self.errors[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += t.doc;
                 f += "\n";
@@ -392,6 +407,7 @@ impl Format for IDL<'_> {
 
     fn get_multiline(&self, indent: usize, max: usize) -> String {
         let mut f = String::new();
+        let mut first = true;
 
         if !self.doc.is_empty() {
             f += &self
@@ -412,7 +428,7 @@ impl Format for IDL<'_> {
         );
 
         for t in self.typedef_keys.iter().map(|k| &self.typedefs[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += &format!("{:indent$}{}", "", t.doc, indent = indent);
                 f += "\n";
@@ -442,7 +458,7 @@ impl Format for IDL<'_> {
         }
 
         for m in self.method_keys.iter().map(|k| &self.methods[k]) {
-            f += "\n";
+            f += self.separator(&mut first, m.preceded_by_blank_line);
             if !m.doc.is_empty() {
                 f += &m
                     .doc
@@ -505,7 +521,7 @@ impl Format for IDL<'_> {
             }
         }
         for t in self.error_keys.iter().map(|k| &self.errors[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += &t
                     .doc
@@ -545,6 +561,7 @@ impl Format for IDL<'_> {
 impl FormatColored for IDL<'_> {
     fn get_oneline_colored(&self) -> String {
         let mut f = String::new();
+        let mut first = true;
 
         if !self.doc.is_empty() {
             f += &self.doc.blue();
@@ -553,7 +570,7 @@ impl FormatColored for IDL<'_> {
         f += &format!("{} {}\n", "interface", self.name.purple());
 
         for t in self.typedef_keys.iter().map(|k| &self.typedefs[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += &t.doc.blue();
                 f += "\n";
@@ -568,7 +585,7 @@ impl FormatColored for IDL<'_> {
         }
 
         for m in self.method_keys.iter().map(|k| &self.methods[k]) {
-            f += "\n";
+            f += self.separator(&mut first, m.preceded_by_blank_line);
             if !m.doc.is_empty() {
                 f += &m.doc.blue();
                 f += "\n";
@@ -584,7 +601,7 @@ impl FormatColored for IDL<'_> {
             );
         }
         for t in self.error_keys.iter().map(|k| &self.errors[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += &t.doc.blue();
                 f += "\n";
@@ -602,6 +619,7 @@ impl FormatColored for IDL<'_> {
 
     fn get_multiline_colored(&self, indent: usize, max: usize) -> String {
         let mut f = String::new();
+        let mut first = true;
 
         if !self.doc.is_empty() {
             f += &self
@@ -621,7 +639,7 @@ impl FormatColored for IDL<'_> {
         );
 
         for t in self.typedef_keys.iter().map(|k| &self.typedefs[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += &t
                     .doc
@@ -656,7 +674,7 @@ impl FormatColored for IDL<'_> {
         }
 
         for m in self.method_keys.iter().map(|k| &self.methods[k]) {
-            f += "\n";
+            f += self.separator(&mut first, m.preceded_by_blank_line);
             if !m.doc.is_empty() {
                 f += &m
                     .doc
@@ -720,7 +738,7 @@ impl FormatColored for IDL<'_> {
             }
         }
         for t in self.error_keys.iter().map(|k| &self.errors[k]) {
-            f += "\n";
+            f += self.separator(&mut first, t.preceded_by_blank_line);
             if !t.doc.is_empty() {
                 f += &t
                     .doc
@@ -0,0 +1,102 @@
+use serde_json::{json, Value};
+
+use crate::*;
+
+impl IDL<'_> {
+    /// Returns the interface definition as a JSON tree for tools in other languages.
+    ///
+    /// The typedefs, methods and errors are arrays in the order of `typedef_keys`,
+    /// `method_keys` and `error_keys`. Every type is an object with a `kind`, one of
+    /// `bool`, `int`, `float`, `string`, `object`, `name` (with `name`), `struct`
+    /// (with `fields`), `enum` (with `values`), or `array`, `dict` and `optional` (with `of`).
+    ///
+    /// Only available with the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::convert::TryFrom;
+    /// # use varlink_parser::IDL;
+    /// let idl = IDL::try_from("interface org.example.foo\nmethod F() -> (a: []Bar)\ntype Bar ()")
+    ///     .unwrap();
+    /// let json = idl.to_json();
+    /// assert_eq!(
+    ///     json["methods"][0]["output"][0]["type"],
+    ///     serde_json::json!({"kind": "array", "of": {"kind": "name", "name": "Bar"}})
+    /// );
+    /// ```
+    pub fn to_json(&self) -> Value {
+        let typedefs: Vec<Value> = self
+            .typedef_keys
+            .iter()
+            .filter_map(|k| self.typedefs.get(k))
+            .map(|t| {
+                let vtype = match t.elt {
+                    VStructOrEnum::VStruct(ref s) => struct_to_json(s),
+                    VStructOrEnum::VEnum(ref e) => enum_to_json(e),
+                };
+                json!({ "name": t.name, "doc": t.doc, "type": vtype })
+            })
+            .collect();
+
+        let methods: Vec<Value> = self
+            .method_keys
+            .iter()
+            .filter_map(|k| self.methods.get(k))
+            .map(|m| {
+                json!({
+                    "name": m.name,
+                    "doc": m.doc,
+                    "input": fields_to_json(&m.input),
+                    "output": fields_to_json(&m.output),
+                })
+            })
+            .collect();
+
+        let errors: Vec<Value> = self
+            .error_keys
+            .iter()
+            .filter_map(|k| self.errors.get(k))
+            .map(|e| json!({ "name": e.name, "doc": e.doc, "parameters": fields_to_json(&e.parm) }))
+            .collect();
+
+        json!({
+            "interface": self.name,
+            "doc": self.doc,
+            "typedefs": typedefs,
+            "methods": methods,
+            "errors": errors,
+        })
+    }
+}
+
+fn fields_to_json(s: &VStruct) -> Value {
+    s.elts
+        .iter()
+        .map(|a| json!({ "name": a.name, "type": vtype_to_json(&a.vtype) }))
+        .collect()
+}
+
+fn struct_to_json(s: &VStruct) -> Value {
+    json!({ "kind": "struct", "fields": fields_to_json(s) })
+}
+
+fn enum_to_json(e: &VEnum) -> Value {
+    json!({ "kind": "enum", "values": e.elts })
+}
+
+fn vtype_to_json(t: &VTypeExt) -> Value {
+    match t {
+        VTypeExt::Plain(VType::Bool) => json!({ "kind": "bool" }),
+        VTypeExt::Plain(VType::Int) => json!({ "kind": "int" }),
+        VTypeExt::Plain(VType::Float) => json!({ "kind": "float" }),
+        VTypeExt::Plain(VType::String) => json!({ "kind": "string" }),
+        VTypeExt::Plain(VType::Object) => json!({ "kind": "object" }),
+        VTypeExt::Plain(VType::Typename(name)) => json!({ "kind": "name", "name": name }),
+        VTypeExt::Plain(VType::Struct(s)) => struct_to_json(s),
+        VTypeExt::Plain(VType::Enum(e)) => enum_to_json(e),
+        VTypeExt::Array(t) => json!({ "kind": "array", "of": vtype_to_json(t) }),
+        VTypeExt::Dict(t) => json!({ "kind": "dict", "of": vtype_to_json(t) }),
+        VTypeExt::Option(t) => json!({ "kind": "optional", "of": vtype_to_json(t) }),
+    }
+}
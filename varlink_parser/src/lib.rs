@@ -44,13 +44,19 @@
     html_favicon_url = "https://varlink.org/images/varlink-small.png"
 )]
 
-use self::varlink_grammar::ParseInterface;
+use self::varlink_grammar::{InterfaceStarts, ParseInterface};
 use std::collections::BTreeMap;
 use std::collections::HashSet;
 
+mod compat;
 mod format;
+#[cfg(feature = "serde")]
+mod json;
+mod visit;
 
+pub use crate::compat::{compatibility, Incompatibility};
 pub use crate::format::{Format, FormatColored};
+pub use crate::visit::{walk_idl, Visitor};
 use std::convert::TryFrom;
 
 #[cfg(test)]
@@ -60,8 +66,17 @@ mod varlink_grammar;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Varlink parse error\n{line}\n{marker:>column$}", marker = "^")]
-    Parse { line: String, column: usize },
+    #[error(
+        "Varlink parse error\n{line}\n{marker:>column$}{expected}",
+        marker = "^",
+        expected = format_expected(.expected)
+    )]
+    Parse {
+        line: String,
+        column: usize,
+        /// The tokens, which would have been valid at the position of the error
+        expected: Vec<String>,
+    },
     #[error("Interface definition error: {0}")]
     Idl(String),
 }
@@ -84,9 +99,46 @@ pub enum VTypeExt<'a> {
     Plain(VType<'a>),
 }
 
+/// The location of a declaration in the interface definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    /// Byte offset of the start
+    pub start: usize,
+    /// Byte offset after the end
+    pub end: usize,
+    /// Line of the start, counting from 1
+    pub line: usize,
+    /// Column of the start in characters, counting from 1
+    pub column: usize,
+}
+
+fn format_expected(expected: &[String]) -> String {
+    if expected.is_empty() {
+        return String::new();
+    }
+    let tokens: Vec<String> = expected.iter().map(|t| format!("'{}'", t)).collect();
+    format!("\nexpected one of: {}", tokens.join(", "))
+}
+
+impl Span {
+    fn new(input: &str, start: usize, end: usize) -> Self {
+        let before = &input[..start];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Span {
+            start,
+            end,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
 pub struct Argument<'a> {
     pub name: &'a str,
+    /// The comment lines in front of the field, with the indentation of all but the first line
+    pub doc: &'a str,
     pub vtype: VTypeExt<'a>,
+    pub span: Span,
 }
 
 pub struct VStruct<'a> {
@@ -101,6 +153,9 @@ pub struct VError<'a> {
     pub name: &'a str,
     pub doc: &'a str,
     pub parm: VStruct<'a>,
+    /// `true`, if the declaration was separated by a blank line from the previous one
+    pub preceded_by_blank_line: bool,
+    pub span: Span,
 }
 
 pub enum VStructOrEnum<'a> {
@@ -112,6 +167,9 @@ pub struct Typedef<'a> {
     pub name: &'a str,
     pub doc: &'a str,
     pub elt: VStructOrEnum<'a>,
+    /// `true`, if the declaration was separated by a blank line from the previous one
+    pub preceded_by_blank_line: bool,
+    pub span: Span,
 }
 
 pub struct Method<'a> {
@@ -119,6 +177,9 @@ pub struct Method<'a> {
     pub doc: &'a str,
     pub input: VStruct<'a>,
     pub output: VStruct<'a>,
+    /// `true`, if the declaration was separated by a blank line from the previous one
+    pub preceded_by_blank_line: bool,
+    pub span: Span,
 }
 
 enum MethodOrTypedefOrError<'a> {
@@ -138,6 +199,34 @@ pub struct IDL<'a> {
     pub errors: BTreeMap<&'a str, VError<'a>>,
     pub error_keys: Vec<&'a str>,
     pub error: HashSet<String>,
+    /// If set, the formatter only separates declarations by a blank line, where the source did
+    /// (see `preceded_by_blank_line`), instead of separating all declarations.
+    pub preserve_blank_lines: bool,
+}
+
+/// `true`, if the whitespace and comments in front of a declaration start with an empty line
+fn starts_with_blank_line(s: &str) -> bool {
+    s.split('#')
+        .next()
+        .unwrap_or_default()
+        .contains(&['\n', '\r', '\u{2028}', '\u{2029}'] as &[_])
+}
+
+/// The offset of the comment lines directly in front of an interface in the whitespace and
+/// comments `s` following a declaration. The first line of `s` is the rest of the line of
+/// the declaration.
+fn doc_comment_start(s: &str) -> usize {
+    let mut start = None;
+    let mut offset = 0;
+    for (i, line) in s.split_inclusive('\n').enumerate() {
+        if i > 0 && line.trim_start().starts_with('#') {
+            start.get_or_insert(offset);
+        } else {
+            start = None;
+        }
+        offset += line.len();
+    }
+    start.unwrap_or(offset)
 }
 
 fn trim_doc(s: &str) -> &str {
@@ -166,6 +255,7 @@ impl<'a> IDL<'a> {
             errors: BTreeMap::new(),
             error_keys: Vec::new(),
             error: HashSet::new(),
+            preserve_blank_lines: false,
         };
 
         for o in mt {
@@ -219,26 +309,155 @@ impl<'a> IDL<'a> {
             };
         }
 
+        i.check_typenames();
+        i.check_recursion();
+
         i
     }
 
+    /// Record an error for every type, which contains itself without an optional, array or
+    /// map in between and so would have an infinite size.
+    fn check_recursion(&mut self) {
+        /// Collect the typenames embedded directly in `s`.
+        fn direct_typenames<'a>(s: &VStruct<'a>, names: &mut Vec<&'a str>) {
+            for arg in &s.elts {
+                match arg.vtype {
+                    VTypeExt::Plain(VType::Typename(name)) => names.push(name),
+                    VTypeExt::Plain(VType::Struct(ref s)) => direct_typenames(s, names),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut recursive = Vec::new();
+        for &start in &self.typedef_keys {
+            let mut seen = HashSet::new();
+            let mut todo = vec![start];
+            while let Some(name) = todo.pop() {
+                if let Some(Typedef {
+                    elt: VStructOrEnum::VStruct(ref s),
+                    ..
+                }) = self.typedefs.get(name)
+                {
+                    let mut names = Vec::new();
+                    direct_typenames(s, &mut names);
+                    if names.contains(&start) {
+                        recursive.push(start);
+                        break;
+                    }
+                    todo.extend(names.into_iter().filter(|n| seen.insert(*n)));
+                }
+            }
+        }
+
+        for name in recursive {
+            self.error.insert(format!(
+                "Interface `{}`: recursive type `{}` requires indirection!",
+                self.name, name
+            ));
+        }
+    }
+
+    /// Record an error for every `Typename`, which is not defined in the interface.
+    fn check_typenames(&mut self) {
+        struct Typenames<'a>(Vec<&'a str>);
+
+        impl<'a> Visitor<'a> for Typenames<'a> {
+            fn visit_vtype(&mut self, t: &VTypeExt<'a>) {
+                if let VTypeExt::Plain(VType::Typename(name)) = *t {
+                    self.0.push(name);
+                }
+            }
+        }
+
+        let mut names = Typenames(Vec::new());
+        walk_idl(self, &mut names);
+
+        for name in names.0 {
+            if !self.typedefs.contains_key(name) {
+                self.error.insert(format!(
+                    "Interface `{}`: unknown type `{}`!",
+                    self.name, name
+                ));
+            }
+        }
+    }
+
+    /// Returns the interface definition as varlink text.
+    ///
+    /// Every declaration is written on one line, preceded by its doc comment, in the order of
+    /// `typedef_keys`, `method_keys` and `error_keys`. Parsing the text again results in
+    /// the same interface.
+    pub fn to_idl_string(&self) -> String {
+        self.get_oneline()
+    }
+
     #[deprecated(since = "4.1.0", note = "please use `IDL::try_from` instead")]
     pub fn from_string(s: &'a str) -> Result<Self, Error> {
         IDL::try_from(s)
     }
 }
 
+/// Parse a text containing several interface definitions.
+///
+/// A new interface starts with its `interface` keyword, together with the comment lines
+/// directly in front of it. The `description` of every returned interface is its part of
+/// `input`. An interface name used more than once is an error.
+///
+/// # Examples
+///
+/// ```rust
+/// let interfaces = varlink_parser::parse_all(
+///     "interface org.example.a\nmethod A() -> ()\n\
+///      ## The second interface\n\
+///      interface org.example.b\nmethod B() -> ()\n",
+/// )
+/// .unwrap();
+/// assert_eq!(interfaces[1].name, "org.example.b");
+/// assert_eq!(interfaces[1].doc, "# The second interface");
+/// ```
+pub fn parse_all(input: &str) -> Result<Vec<IDL<'_>>, Error> {
+    // byte offsets of the interfaces, including their doc comments
+    let starts = InterfaceStarts(input).map_err(|e| parse_error(input, e))?;
+
+    let mut interfaces: Vec<IDL> = Vec::new();
+    // the first interface also gets everything in front of it
+    let mut start = 0;
+    for end in starts[1..].iter().copied().chain([input.len()]) {
+        let idl = IDL::try_from(&input[start..end])?;
+        if interfaces.iter().any(|i| i.name == idl.name) {
+            return Err(Error::Idl(format!(
+                "multiple definitions of interface `{}`!\n",
+                idl.name
+            )));
+        }
+        interfaces.push(idl);
+        start = end;
+    }
+
+    Ok(interfaces)
+}
+
+fn parse_error(input: &str, e: peg::error::ParseError<peg::str::LineCol>) -> Error {
+    let line = input.split('\n').nth(e.location.line - 1).unwrap();
+    let mut expected: Vec<String> = e
+        .expected
+        .tokens()
+        .map(|t| t.trim_matches('"').to_string())
+        .collect();
+    expected.sort();
+    Error::Parse {
+        line: line.to_string(),
+        column: e.location.column,
+        expected,
+    }
+}
+
 impl<'a> TryFrom<&'a str> for IDL<'a> {
     type Error = Error;
 
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        let interface = ParseInterface(value).map_err(|e| {
-            let line = value.split('\n').nth(e.location.line - 1).unwrap();
-            Error::Parse {
-                line: line.to_string(),
-                column: e.location.column,
-            }
-        })?;
+        let interface = ParseInterface(value).map_err(|e| parse_error(value, e))?;
 
         if !interface.error.is_empty() {
             let mut v: Vec<_> = interface.error.into_iter().collect();
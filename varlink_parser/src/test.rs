@@ -313,6 +313,29 @@ fn test_format() {
     );
 }
 
+#[test]
+fn test_format_preserve_blank_lines() {
+    let source = "interface foo.bar
+
+type I (b: []bool)
+type J (i: int)
+
+# Does F
+method F() -> ()
+method G() -> ()
+
+error E ()
+";
+    let mut v = IDL::try_from(source).unwrap();
+    assert!(v.typedefs["I"].preceded_by_blank_line);
+    assert!(!v.typedefs["J"].preceded_by_blank_line);
+    assert!(v.methods["F"].preceded_by_blank_line);
+    assert!(!v.methods["G"].preceded_by_blank_line);
+
+    v.preserve_blank_lines = true;
+    assert_eq!(v.to_string(), source);
+}
+
 #[test]
 fn test_duplicate() {
     let e = IDL::try_from(
@@ -337,3 +360,459 @@ Interface `foo.example`: multiple definitions of type `T`!
 "
     );
 }
+
+#[test]
+fn test_span() {
+    let source = "interface foo.bar
+
+method First() -> ()
+
+# The second method
+method Second(a: int,
+              b: string) -> ()
+";
+    let idl = IDL::try_from(source).unwrap();
+
+    let span = idl.methods["Second"].span;
+    assert_eq!(span.line, 6);
+    assert_eq!(span.column, 1);
+    assert_eq!(
+        &source[span.start..span.end],
+        "method Second(a: int,\n              b: string) -> ()"
+    );
+
+    let b = &idl.methods["Second"].input.elts[1];
+    assert_eq!((b.span.line, b.span.column), (7, 15));
+    assert_eq!(&source[b.span.start..b.span.end], "b: string");
+}
+
+#[test]
+fn test_to_idl_string() {
+    let source = "
+# The Varlink Service Interface is provided by every varlink service. It
+# describes the service and the interfaces it implements.
+interface org.varlink.service
+
+# Get a list of all the interfaces a service provides and information
+# about the implementation.
+method GetInfo() -> (
+  vendor: string,
+  product: string,
+  version: string,
+  url: string,
+  interfaces: []string
+)
+
+# Get the description of an interface that is implemented by this service.
+method GetInterfaceDescription(interface: string) -> (description: string)
+
+# The requested interface was not found.
+error InterfaceNotFound (interface: string)
+
+# The requested method was not found
+error MethodNotFound (method: string)
+
+# The interface defines the requested method, but the service does not
+# implement it.
+error MethodNotImplemented (method: string)
+
+# One of the passed parameters is invalid.
+error InvalidParameter (parameter: string)
+";
+    let mut idl = IDL::try_from(source).unwrap();
+    // drop a method by removing it from the keys
+    idl.method_keys.retain(|m| *m != "GetInterfaceDescription");
+
+    let text = idl.to_idl_string();
+    assert!(text.contains(
+        "method GetInfo() -> (vendor: string, product: string, version: string, url: string, interfaces: []string)\n"
+    ));
+
+    let reparsed = IDL::try_from(text.as_str()).unwrap();
+    assert_eq!(reparsed.name, idl.name);
+    assert_eq!(reparsed.doc, idl.doc);
+    assert_eq!(reparsed.method_keys, vec!["GetInfo"]);
+    assert_eq!(reparsed.error_keys, idl.error_keys);
+    assert_eq!(reparsed.methods["GetInfo"].doc, idl.methods["GetInfo"].doc);
+    assert_eq!(reparsed.to_idl_string(), text);
+}
+
+#[test]
+fn test_unknown_type() {
+    let e = IDL::try_from("interface foo.example\nmethod Foo() -> (x: Nonexistent)")
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "Interface definition error: Interface `foo.example`: unknown type `Nonexistent`!\n"
+    );
+
+    // types may be used before they are declared
+    assert!(IDL::try_from(
+        "interface foo.example
+method Foo(a: A) -> (b: B)
+error E (b: [string]B)
+type A (b: B)
+type B (i: int)
+"
+    )
+    .is_ok());
+
+    let e = IDL::try_from(
+        "interface foo.example
+type A (a: (nested: []?[string]Missing))
+method Foo(a: A) -> ()
+",
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "Interface definition error: Interface `foo.example`: unknown type `Missing`!\n"
+    );
+}
+
+#[test]
+fn test_recursive_type() {
+    let e = IDL::try_from("interface foo.example\ntype Node (next: Node)\nmethod F() -> ()")
+        .err()
+        .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "Interface definition error: \
+         Interface `foo.example`: recursive type `Node` requires indirection!\n"
+    );
+
+    let e = IDL::try_from(
+        "interface foo.example
+type A (b: (inner: B))
+type B (a: A, i: int)
+type C (a: A)
+method F() -> ()
+",
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "Interface definition error: \
+Interface `foo.example`: recursive type `A` requires indirection!
+Interface `foo.example`: recursive type `B` requires indirection!
+"
+    );
+
+    assert!(IDL::try_from(
+        "interface foo.example
+type Node (next: ?Node, children: []Node, named: [string]Node)
+method F() -> ()
+"
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_visitor() {
+    struct CountInts(usize);
+
+    impl<'a> Visitor<'a> for CountInts {
+        fn visit_vtype(&mut self, t: &VTypeExt<'a>) {
+            if let VTypeExt::Plain(VType::Int) = t {
+                self.0 += 1;
+            }
+        }
+    }
+
+    let idl = IDL::try_from(
+        "interface org.example.count
+type T (a: int, b: []?int, c: (d: [string]int, e: string), f: (x, y))
+method M(i: int, t: T) -> (f: float)
+error E (i: ?int)
+",
+    )
+    .unwrap();
+
+    let mut count = CountInts(0);
+    walk_idl(&idl, &mut count);
+    assert_eq!(count.0, 5);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_to_json() {
+    let idl = IDL::try_from(
+        "# Ping service
+interface org.example.ping
+
+# Ping
+method Ping(ping: string) -> (pong: string)
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+        idl.to_json(),
+        serde_json::json!({
+            "interface": "org.example.ping",
+            "doc": "# Ping service",
+            "typedefs": [],
+            "methods": [{
+                "name": "Ping",
+                "doc": "# Ping",
+                "input": [{ "name": "ping", "type": { "kind": "string" } }],
+                "output": [{ "name": "pong", "type": { "kind": "string" } }],
+            }],
+            "errors": [],
+        })
+    );
+}
+
+#[test]
+fn test_compatibility() {
+    let old = IDL::try_from(
+        "interface org.example.compat
+type Color (red, green, blue)
+type Point (x: int, y: int, label: ?string)
+method Draw(point: Point, color: ?Color) -> (id: int)
+method Clear() -> ()
+error NotFound (id: int)
+",
+    )
+    .unwrap();
+
+    // purely additive
+    let new = IDL::try_from(
+        "interface org.example.compat
+type Color (red, green, blue, black)
+type Point (x: int, y: int, label: ?string, z: ?int)
+type Size (w: int, h: int)
+method Draw(point: Point, color: ?Color, size: ?Size) -> (id: int, size: Size)
+method Clear() -> ()
+method Resize(size: Size) -> ()
+error NotFound (id: int)
+error Busy ()
+",
+    )
+    .unwrap();
+    assert_eq!(compatibility(&old, &new), vec![]);
+
+    let new = IDL::try_from(
+        "interface org.example.compat
+type Color (red, blue)
+type Point (x: int, y: float, label: string)
+method Draw(point: Point, color: Color, size: int) -> ()
+",
+    )
+    .unwrap();
+    assert_eq!(
+        compatibility(&old, &new),
+        vec![
+            Incompatibility::RemovedEnumVariant {
+                path: "Color".into(),
+                variant: "green".into()
+            },
+            Incompatibility::ChangedType {
+                path: "Point.y".into(),
+                old: "int".into(),
+                new: "float".into()
+            },
+            Incompatibility::FieldBecameRequired {
+                path: "Point.label".into()
+            },
+            Incompatibility::FieldBecameRequired {
+                path: "Draw.input.color".into()
+            },
+            Incompatibility::AddedRequiredField {
+                path: "Draw.input.size".into()
+            },
+            Incompatibility::RemovedField {
+                path: "Draw.output.id".into()
+            },
+            Incompatibility::RemovedMethod("Clear".into()),
+            Incompatibility::RemovedError("NotFound".into()),
+        ]
+    );
+    assert_eq!(
+        Incompatibility::RemovedMethod("Clear".into()).to_string(),
+        "method `Clear` was removed"
+    );
+}
+
+#[test]
+fn test_compatibility_output() {
+    let old = IDL::try_from(
+        "interface org.example.compat
+type Info (name: string, size: ?int)
+type Filter (name: ?string)
+method Get(filter: Filter) -> (info: Info, count: int, next: ?string)
+method Find(filter: ?Filter) -> (filter: Filter)
+error NotFound (name: string)
+",
+    )
+    .unwrap();
+
+    // only sent by the service, so fields may become required and required fields may be added
+    let new = IDL::try_from(
+        "interface org.example.compat
+type Info (name: string, size: int, owner: string)
+type Filter (name: ?string)
+method Get(filter: Filter) -> (info: Info, count: int, next: string)
+method Find(filter: ?Filter) -> (filter: Filter)
+error NotFound (name: string)
+",
+    )
+    .unwrap();
+    assert_eq!(compatibility(&old, &new), vec![]);
+
+    // clients rely on getting required fields of the output and error parameters, and a type
+    // used in the input and output counts for both
+    let new = IDL::try_from(
+        "interface org.example.compat
+type Info (name: ?string, size: ?int)
+type Filter (name: string, limit: int)
+method Get(filter: Filter) -> (info: Info, count: ?int, next: ?string)
+method Find(filter: ?Filter) -> (filter: Filter)
+error NotFound (name: ?string)
+",
+    )
+    .unwrap();
+    assert_eq!(
+        compatibility(&old, &new),
+        vec![
+            Incompatibility::FieldBecameOptional {
+                path: "Info.name".into()
+            },
+            Incompatibility::FieldBecameRequired {
+                path: "Filter.name".into()
+            },
+            Incompatibility::AddedRequiredField {
+                path: "Filter.limit".into()
+            },
+            Incompatibility::FieldBecameOptional {
+                path: "Get.output.count".into()
+            },
+            Incompatibility::FieldBecameOptional {
+                path: "NotFound.name".into()
+            },
+        ]
+    );
+    assert_eq!(
+        Incompatibility::FieldBecameOptional {
+            path: "Info.name".into()
+        }
+        .to_string(),
+        "required field `Info.name` became optional"
+    );
+}
+
+#[test]
+fn test_argument_doc() {
+    let idl = IDL::try_from(
+        "interface org.example.doc
+type Point (
+  # The horizontal position
+  x: int,
+  y: int,
+  # The name of the point,
+  # if any
+  label: ?string
+)
+
+method Move(
+  # The point to move
+  point: Point, dx: int) -> ()
+",
+    )
+    .unwrap();
+
+    let point = match idl.typedefs["Point"].elt {
+        VStructOrEnum::VStruct(ref s) => s,
+        _ => panic!("Point is not a struct"),
+    };
+    let docs: Vec<&str> = point.elts.iter().map(|a| a.doc).collect();
+    assert_eq!(
+        docs,
+        vec![
+            "# The horizontal position",
+            "",
+            "# The name of the point,\n  # if any"
+        ]
+    );
+
+    let input = &idl.methods["Move"].input;
+    assert_eq!(input.elts[0].doc, "# The point to move");
+    assert_eq!(input.elts[1].doc, "");
+}
+
+#[test]
+fn test_parse_all() {
+    let input = "# The first interface
+interface org.example.first
+
+# Get the interface
+method GetInterfaceDescription(
+  interface : string
+) -> ()
+method Ping(
+interface
+: string) -> ()
+
+# The second
+# interface
+interface org.example.second
+method Pong() -> ()
+";
+    let interfaces = parse_all(input).unwrap();
+    assert_eq!(interfaces.len(), 2);
+
+    assert_eq!(interfaces[0].name, "org.example.first");
+    assert_eq!(interfaces[0].method_keys.len(), 2);
+    assert_eq!(interfaces[1].name, "org.example.second");
+    assert_eq!(interfaces[1].method_keys.len(), 1);
+    assert_eq!(interfaces[1].doc, "# The second\n# interface");
+    assert_eq!(
+        interfaces[1].description,
+        "# The second
+# interface
+interface org.example.second
+method Pong() -> ()
+"
+    );
+    assert_eq!(
+        [interfaces[0].description, interfaces[1].description].concat(),
+        input
+    );
+
+    let e = parse_all(
+        "interface org.example.a\nmethod A() -> ()\ninterface org.example.a\nmethod B() -> ()\n",
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        e.to_string(),
+        "Interface definition error: multiple definitions of interface `org.example.a`!\n"
+    );
+}
+
+#[test]
+fn test_parse_error_expected() {
+    let e = IDL::try_from("interface org.x\nmethd Foo()").err().unwrap();
+    match e {
+        Error::Parse {
+            ref line,
+            column,
+            ref expected,
+        } => {
+            assert_eq!(line, "methd Foo()");
+            assert_eq!(column, 1);
+            assert!(expected.iter().any(|t| t == "method"), "{:?}", expected);
+        }
+        _ => panic!("unexpected error {:?}", e),
+    }
+    assert_eq!(
+        e.to_string(),
+        "Varlink parse error
+methd Foo()
+^
+expected one of: '<newline> <whitespace> or <comment>', 'error', 'method', 'type'"
+    );
+}
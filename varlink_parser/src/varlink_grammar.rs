@@ -71,12 +71,16 @@ peg::parser! {
             / o:option() a:dict() v:type_() { VTypeExt::Option(Box::new(VTypeExt::Dict(Box::new(v)))) }
 
         use crate::Argument;
+        use crate::Span;
+        use crate::trim_doc;
         rule object_field() -> Argument<'input>
-            = wce()* n:$(field_name()) wce()* [':'] wce()* v:type_() { Argument { name : n, vtype : v } }
+            = d:$(wce()*) s:position!() n:$(field_name()) wce()* [':'] wce()* v:type_() e:position!() {
+                Argument { name : n, doc: trim_doc(d), vtype : v, span: Span::new(__input, s, e) }
+            }
 
         use crate::VStruct;
         rule vstruct() -> VStruct<'input>
-            = ['('] wce()* e:object_field() ** [','] wce()* [')'] { VStruct{ elts: e} }
+            = ['('] e:object_field() ** [','] wce()* [')'] { VStruct{ elts: e} }
 
         use crate::VEnum;
         rule venum() -> VEnum<'input>
@@ -84,28 +88,32 @@ peg::parser! {
 
         use crate::Typedef;
         use crate::VStructOrEnum;
-        use crate::trim_doc;
+        use crate::starts_with_blank_line;
 
         rule vtypedef() -> Typedef<'input>
-            = d:$(wce()*) "type" wce()+ n:$(name()) wce()* v:vstruct() {
-                Typedef{name: n, doc: trim_doc(d), elt: VStructOrEnum::VStruct(Box::new(v))}
+            = d:$(wce()*) s:position!() "type" wce()+ n:$(name()) wce()* v:vstruct() e:position!() {
+                Typedef{name: n, doc: trim_doc(d), elt: VStructOrEnum::VStruct(Box::new(v)), preceded_by_blank_line: starts_with_blank_line(d), span: Span::new(__input, s, e)}
             }
-            / d:$(wce()*) "type" wce()+ n:$(name()) wce()* v:venum() {
-                Typedef{name: n, doc: trim_doc(d), elt: VStructOrEnum::VEnum(Box::new(v))}
+            / d:$(wce()*) s:position!() "type" wce()+ n:$(name()) wce()* v:venum() e:position!() {
+                Typedef{name: n, doc: trim_doc(d), elt: VStructOrEnum::VEnum(Box::new(v)), preceded_by_blank_line: starts_with_blank_line(d), span: Span::new(__input, s, e)}
             }
 
         use crate::VError;
         rule error() -> VError<'input>
-            = d:$(wce()*) "error" wce()+ n:$(name()) wce()* p:vstruct() { VError{name: n, doc: trim_doc(d), parm: p} }
+            = d:$(wce()*) s:position!() "error" wce()+ n:$(name()) wce()* p:vstruct() e:position!() {
+                VError{name: n, doc: trim_doc(d), parm: p, preceded_by_blank_line: starts_with_blank_line(d), span: Span::new(__input, s, e)}
+            }
 
         use crate::Method;
         rule method() -> Method<'input>
-            = d:$(wce()*) "method" wce()+ n:$(name()) wce()* i:vstruct() wce()* "->" wce()* o:vstruct() {
+            = d:$(wce()*) s:position!() "method" wce()+ n:$(name()) wce()* i:vstruct() wce()* "->" wce()* o:vstruct() e:position!() {
                 Method {
                     name: n,
                     doc: trim_doc(d),
                     input: i,
-                    output: o
+                    output: o,
+                    preceded_by_blank_line: starts_with_blank_line(d),
+                    span: Span::new(__input, s, e)
                 }
              }
 
@@ -121,5 +129,14 @@ peg::parser! {
                 IDL::from_token(__input, n, mt, trim_doc(d))
              }
 
+        use crate::doc_comment_start;
+        rule interface_start() -> usize
+            = p:position!() d:$(wce()*) "interface" wce()+ interface_name() eol() (member()++ eol()) {
+                p + doc_comment_start(d)
+             }
+
+        pub rule InterfaceStarts() -> Vec<usize>
+            = v:interface_start()+ wce()* { v }
+
     }
 }
@@ -0,0 +1,75 @@
+use crate::*;
+
+/// Callbacks for walking an [`IDL`] with [`walk_idl`]
+///
+/// All methods do nothing by default, so an implementation only overrides the ones it
+/// is interested in.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::convert::TryFrom;
+/// use varlink_parser::{walk_idl, Method, Visitor, IDL};
+///
+/// struct MethodNames(Vec<String>);
+///
+/// impl<'a> Visitor<'a> for MethodNames {
+///     fn visit_method(&mut self, m: &Method<'a>) {
+///         self.0.push(m.name.to_string());
+///     }
+/// }
+///
+/// let idl = IDL::try_from("interface org.example.foo\nmethod A() -> ()\nmethod B() -> ()").unwrap();
+/// let mut names = MethodNames(Vec::new());
+/// walk_idl(&idl, &mut names);
+/// assert_eq!(names.0, vec!["A", "B"]);
+/// ```
+pub trait Visitor<'a> {
+    fn visit_typedef(&mut self, _t: &Typedef<'a>) {}
+    fn visit_method(&mut self, _m: &Method<'a>) {}
+    fn visit_error(&mut self, _e: &VError<'a>) {}
+    /// Called for every field of a struct, before its type is visited
+    fn visit_argument(&mut self, _a: &Argument<'a>) {}
+    /// Called for every type, including the element types of arrays, maps and optionals
+    fn visit_vtype(&mut self, _t: &VTypeExt<'a>) {}
+}
+
+/// Walk all declarations of `idl` and everything nested in them.
+///
+/// The typedefs, methods and errors are visited in the order of `typedef_keys`,
+/// `method_keys` and `error_keys`. Every declaration is visited before its fields.
+pub fn walk_idl<'a, V: Visitor<'a> + ?Sized>(idl: &IDL<'a>, v: &mut V) {
+    for t in idl.typedef_keys.iter().filter_map(|k| idl.typedefs.get(k)) {
+        v.visit_typedef(t);
+        if let VStructOrEnum::VStruct(ref s) = t.elt {
+            walk_struct(s, v);
+        }
+    }
+
+    for m in idl.method_keys.iter().filter_map(|k| idl.methods.get(k)) {
+        v.visit_method(m);
+        walk_struct(&m.input, v);
+        walk_struct(&m.output, v);
+    }
+
+    for e in idl.error_keys.iter().filter_map(|k| idl.errors.get(k)) {
+        v.visit_error(e);
+        walk_struct(&e.parm, v);
+    }
+}
+
+fn walk_struct<'a, V: Visitor<'a> + ?Sized>(s: &VStruct<'a>, v: &mut V) {
+    for arg in &s.elts {
+        v.visit_argument(arg);
+        walk_vtype(&arg.vtype, v);
+    }
+}
+
+fn walk_vtype<'a, V: Visitor<'a> + ?Sized>(t: &VTypeExt<'a>, v: &mut V) {
+    v.visit_vtype(t);
+    match t {
+        VTypeExt::Array(t) | VTypeExt::Dict(t) | VTypeExt::Option(t) => walk_vtype(t, v),
+        VTypeExt::Plain(VType::Struct(s)) => walk_struct(s, v),
+        VTypeExt::Plain(_) => {}
+    }
+}